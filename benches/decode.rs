@@ -67,6 +67,92 @@ macro_rules! bench_decode_files_simd {
     };
 }
 
+/// Macro for easily adding benchmark data files (SIMD tape)
+#[cfg(feature = "simd")]
+macro_rules! bench_decode_tape_files {
+    ($group:expr, $( $name:literal => $path:literal ),* $(,)?) => {
+        $(
+            {
+                const DATA: &[u8] = include_bytes!($path);
+                let size_str = format_size(DATA.len());
+                let bench_name = format!("{} ({})", $name, size_str);
+
+                $group.throughput(Throughput::Bytes(DATA.len() as u64));
+                $group.bench_function(&bench_name, |b| {
+                    b.iter(|| bencodex::simd::tape::decode_tape(black_box(DATA)))
+                });
+            }
+        )*
+    };
+}
+
+/// Macro for easily adding benchmark data files (SIMD stage 1 only, with each available backend)
+#[cfg(feature = "simd")]
+macro_rules! bench_structural_index_files {
+    ($group:expr, $( $name:literal => $path:literal ),* $(,)?) => {
+        $(
+            {
+                const DATA: &[u8] = include_bytes!($path);
+                let size_str = format_size(DATA.len());
+                let bench_name = format!("{} ({})", $name, size_str);
+
+                $group.throughput(Throughput::Bytes(DATA.len() as u64));
+                for backend in bencodex::simd::available_backends() {
+                    $group.bench_function(&format!("{} {}", bench_name, backend), |b| {
+                        b.iter(|| {
+                            bencodex::codec::simd::stage1::build_structural_index_with_backend::<u32>(
+                                black_box(DATA),
+                                backend,
+                            )
+                        })
+                    });
+                }
+            }
+        )*
+    };
+}
+
+/// Macro for easily adding benchmark data files (parallel)
+#[cfg(feature = "rayon")]
+macro_rules! bench_decode_files_parallel {
+    ($group:expr, $( $name:literal => $path:literal ),* $(,)?) => {
+        $(
+            {
+                const DATA: &[u8] = include_bytes!($path);
+                let size_str = format_size(DATA.len());
+                let bench_name = format!("{} ({})", $name, size_str);
+
+                $group.throughput(Throughput::Bytes(DATA.len() as u64));
+                $group.bench_function(&bench_name, |b| {
+                    b.iter(|| bencodex::simd::decode_simd_parallel(black_box(DATA)))
+                });
+            }
+        )*
+    };
+}
+
+/// Macro for easily adding benchmark data files (arena)
+#[cfg(feature = "arena")]
+macro_rules! bench_decode_files_arena {
+    ($group:expr, $( $name:literal => $path:literal ),* $(,)?) => {
+        $(
+            {
+                const DATA: &[u8] = include_bytes!($path);
+                let size_str = format_size(DATA.len());
+                let bench_name = format!("{} ({})", $name, size_str);
+
+                $group.throughput(Throughput::Bytes(DATA.len() as u64));
+                $group.bench_function(&bench_name, |b| {
+                    b.iter(|| {
+                        let bump = bencodex::arena::Bump::new();
+                        black_box(bencodex::arena::decode_in(black_box(DATA), &bump).is_ok())
+                    })
+                });
+            }
+        )*
+    };
+}
+
 pub fn decode_scalar(c: &mut Criterion) {
     let mut group = c.benchmark_group("decode_scalar");
 
@@ -105,9 +191,151 @@ pub fn decode_simd(c: &mut Criterion) {
 }
 
 #[cfg(feature = "simd")]
-criterion_group!(benches, decode_scalar, decode_borrowed, decode_simd);
+pub fn decode_tape(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_tape");
+
+    bench_decode_tape_files!(group,
+        "ncavatar_1" => "../_data/ncavatar_1.bin",
+        "ncinventory_1" => "../_data/ncinventory_1.bin",
+        "large_random_0" => "../_data/large_random_0.bin",
+    );
+
+    group.finish();
+}
+
+#[cfg(feature = "simd")]
+pub fn structural_index(c: &mut Criterion) {
+    let mut group = c.benchmark_group("structural_index");
+
+    bench_structural_index_files!(group,
+        "ncavatar_1" => "../_data/ncavatar_1.bin",
+        "ncinventory_1" => "../_data/ncinventory_1.bin",
+        "large_random_0" => "../_data/large_random_0.bin",
+    );
+
+    group.finish();
+}
+
+/// A dictionary like a small transaction, with `entries` entries
+#[cfg(feature = "simd")]
+fn small_transaction(entries: usize) -> Vec<u8> {
+    use bencodex::{BencodexDictionary, BencodexValue, Encode};
+
+    let mut dict = BencodexDictionary::new();
+    for i in 0..entries {
+        let value: BencodexValue = match i % 3 {
+            0 => (i as i64 * 12345).into(),
+            1 => format!("text value {}", i).into(),
+            _ => vec![i as u8; 20].into(),
+        };
+        dict.insert(format!("key{}", i).into(), value);
+    }
+    let mut buf = Vec::new();
+    BencodexValue::Dictionary(dict).encode(&mut buf).unwrap();
+    buf
+}
+
+#[cfg(feature = "simd")]
+pub fn decode_small(c: &mut Criterion) {
+    use bencodex::simd::SimdDecoder;
+
+    let mut group = c.benchmark_group("decode_small");
+
+    for entries in [1, 4, 16, 64] {
+        let data = small_transaction(entries);
+        let data = data.as_slice();
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        let size = format_size(data.len());
+        group.bench_function(format!("borrowed/{}", size), |b| {
+            b.iter(|| bencodex::decode_borrowed(black_box(data)))
+        });
+        group.bench_function(format!("decode_simd/{}", size), |b| {
+            b.iter(|| bencodex::simd::decode_simd(black_box(data)))
+        });
+        let mut decoder = SimdDecoder::with_scalar_threshold(0);
+        group.bench_function(format!("simd_decoder/{}", size), |b| {
+            b.iter(|| decoder.decode(black_box(data)).map(|_| ()))
+        });
+        let mut decoder = SimdDecoder::new();
+        group.bench_function(format!("simd_decoder_default/{}", size), |b| {
+            b.iter(|| decoder.decode(black_box(data)).map(|_| ()))
+        });
+    }
+
+    group.finish();
+}
+
+#[cfg(feature = "simd")]
+pub fn utf8(c: &mut Criterion) {
+    let mut group = c.benchmark_group("utf8");
+
+    for (name, unit) in [
+        ("ascii", "bencodex "),
+        ("hangul", "벤코덱스 "),
+        ("mixed", "bencodex 벤코덱스 😀 "),
+    ] {
+        for len in [64, 4096, 1 << 20] {
+            let text = unit.repeat(len / unit.len() + 1);
+            let text = &text.as_bytes()[..text.floor_char_boundary(len)];
+            group.throughput(Throughput::Bytes(text.len() as u64));
+            let id = format!("{}/{}", name, format_size(text.len()));
+            group.bench_function(format!("simd/{}", id), |b| {
+                b.iter(|| bencodex::codec::simd::utf8::validate_utf8(black_box(text)))
+            });
+            group.bench_function(format!("std/{}", id), |b| {
+                b.iter(|| core::str::from_utf8(black_box(text)).is_ok())
+            });
+        }
+    }
+
+    group.finish();
+}
+
+#[cfg(feature = "arena")]
+pub fn decode_arena(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_arena");
+
+    bench_decode_files_arena!(group,
+        "ncavatar_1" => "../_data/ncavatar_1.bin",
+        "ncinventory_1" => "../_data/ncinventory_1.bin",
+        "large_random_0" => "../_data/large_random_0.bin",
+    );
+
+    group.finish();
+}
+
+#[cfg(not(feature = "arena"))]
+pub fn decode_arena(_: &mut Criterion) {}
+
+#[cfg(feature = "rayon")]
+pub fn decode_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode_parallel");
+
+    bench_decode_files_parallel!(group,
+        "large_random_0" => "../_data/large_random_0.bin",
+    );
+
+    group.finish();
+}
+
+#[cfg(not(feature = "rayon"))]
+pub fn decode_parallel(_: &mut Criterion) {}
+
+#[cfg(feature = "simd")]
+criterion_group!(
+    benches,
+    decode_scalar,
+    decode_borrowed,
+    decode_arena,
+    decode_simd,
+    decode_parallel,
+    decode_tape,
+    structural_index,
+    decode_small,
+    utf8
+);
 
 #[cfg(not(feature = "simd"))]
-criterion_group!(benches, decode_scalar, decode_borrowed);
+criterion_group!(benches, decode_scalar, decode_borrowed, decode_arena);
 
 criterion_main!(benches);
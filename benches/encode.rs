@@ -38,6 +38,35 @@ macro_rules! bench_encode_files {
     };
 }
 
+/// Macro for easily adding benchmark data files for encoding with `encode_to_vec` and, with the
+/// `std` feature, `encode_vectored`
+macro_rules! bench_encode_buffered_files {
+    ($group:expr, $( $name:literal => $path:literal ),* $(,)?) => {
+        $(
+            {
+                const DATA: &[u8] = include_bytes!($path);
+                let size_str = format_size(DATA.len());
+
+                // Setup: decode to BencodexValue
+                let value: BencodexValue = DATA.to_vec().decode()
+                    .expect(concat!("Failed to decode ", $path));
+
+                $group.throughput(Throughput::Bytes(DATA.len() as u64));
+                $group.bench_function(&format!("{} to_vec ({})", $name, size_str), |b| {
+                    b.iter(|| black_box(&value).encode_to_vec())
+                });
+                #[cfg(feature = "std")]
+                $group.bench_function(&format!("{} vectored ({})", $name, size_str), |b| {
+                    b.iter(|| {
+                        let mut buf = Vec::with_capacity(DATA.len());
+                        black_box(&value).encode_vectored(&mut buf)
+                    })
+                });
+            }
+        )*
+    };
+}
+
 #[cfg(feature = "json")]
 macro_rules! bench_to_json_files {
     ($group:expr, $( $name:literal => $path:literal ),* $(,)?) => {
@@ -74,7 +103,7 @@ pub fn encode_primitives(c: &mut Criterion) {
     });
     group.bench_function("bigint (9223372036854775807)", |b| {
         let mut buf = Vec::new();
-        let bigint = BencodexValue::Number(BigInt::from(9223372036854775807i64));
+        let bigint = BencodexValue::Number(BigInt::from(9223372036854775807i64).into());
         b.iter(|| black_box(&bigint).encode(&mut buf));
     });
     group.bench_function("boolean (true)", |b| {
@@ -103,6 +132,18 @@ pub fn encode_files(c: &mut Criterion) {
     group.finish();
 }
 
+pub fn encode_files_buffered(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode_files_buffered");
+
+    bench_encode_buffered_files!(group,
+        "ncavatar_1" => "../_data/ncavatar_1.bin",
+        "ncinventory_1" => "../_data/ncinventory_1.bin",
+        "large_random_0" => "../_data/large_random_0.bin",
+    );
+
+    group.finish();
+}
+
 #[cfg(feature = "json")]
 pub fn encode_to_json(c: &mut Criterion) {
     let mut group = c.benchmark_group("encode_to_json");
@@ -117,9 +158,20 @@ pub fn encode_to_json(c: &mut Criterion) {
 }
 
 #[cfg(feature = "json")]
-criterion_group!(benches, encode_primitives, encode_files, encode_to_json);
+criterion_group!(
+    benches,
+    encode_primitives,
+    encode_files,
+    encode_files_buffered,
+    encode_to_json
+);
 
 #[cfg(not(feature = "json"))]
-criterion_group!(benches, encode_primitives, encode_files);
+criterion_group!(
+    benches,
+    encode_primitives,
+    encode_files,
+    encode_files_buffered
+);
 
 criterion_main!(benches);
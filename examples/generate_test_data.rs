@@ -3,7 +3,6 @@ use num_bigint::BigInt;
 use rand::distr::Alphanumeric;
 use rand::prelude::*;
 use std::borrow::Cow;
-use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
@@ -66,7 +65,7 @@ fn random_primitive(rng: &mut impl Rng) -> BencodexValue<'static> {
     match rng.random_range(0..5) {
         0 => BencodexValue::Null,
         1 => BencodexValue::Boolean(rng.random()),
-        2 => BencodexValue::Number(random_number(rng)),
+        2 => BencodexValue::Number(random_number(rng).into()),
         3 => BencodexValue::Text(Cow::Owned(random_text(rng, 100))),
         _ => BencodexValue::Binary(Cow::Owned(random_binary(rng, 1000))),
     }
@@ -95,7 +94,7 @@ fn random_value(rng: &mut impl Rng, depth: usize) -> BencodexValue<'static> {
 }
 
 fn generate_large_value(rng: &mut impl Rng, config: &GeneratorConfig) -> BencodexValue<'static> {
-    let mut dict = BTreeMap::new();
+    let mut dict = BencodexDictionary::new();
 
     for i in 0..config.binary_chunks {
         let key = BencodexKey::Text(Cow::Owned(format!("binary_chunk_{i}")));
@@ -106,7 +105,7 @@ fn generate_large_value(rng: &mut impl Rng, config: &GeneratorConfig) -> Bencode
     for i in 0..config.number_lists {
         let key = BencodexKey::Text(Cow::Owned(format!("number_list_{i}")));
         let list: BencodexList = (0..config.numbers_per_list)
-            .map(|_| BencodexValue::Number(random_number(rng)))
+            .map(|_| BencodexValue::Number(random_number(rng).into()))
             .collect();
         dict.insert(key, BencodexValue::List(list));
     }
@@ -0,0 +1,410 @@
+//! Decoding into a bump arena.
+//!
+//! [`decode_in`] places every list and dictionary of the decoded value into a caller-supplied
+//! [`Bump`], and borrows binary and text data from the input. So dropping the arena frees the
+//! whole value at once, instead of freeing each [`Vec`] and [`BTreeMap`] one by one.
+//!
+//! ```
+//! use bencodex::arena::{ ArenaKey, ArenaValue, Bump, decode_in };
+//!
+//! let bump = Bump::new();
+//! let value = decode_in(b"du3:fooli1ei2eee", &bump).unwrap();
+//!
+//! let list = value.get(&ArenaKey::Text("foo")).unwrap();
+//! assert_eq!(list.as_list().map(|l| l.len()), Some(2));
+//! ```
+
+use super::decode::{DecodeError, read_length, read_number};
+use super::types::*;
+use crate::prelude::*;
+use core::str;
+
+pub use bumpalo::Bump;
+
+/// A Bencodex value decoded by [`decode_in`].
+///
+/// Binary and text values borrow from the input, and lists and dictionaries are allocated in the
+/// arena. Both of them should outlive `'a`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ArenaValue<'a> {
+    Binary(&'a [u8]),
+    Text(&'a str),
+    Boolean(bool),
+    Number(ArenaNumber<'a>),
+    List(&'a [ArenaValue<'a>]),
+    /// The entries of a dictionary, sorted by their keys.
+    Dictionary(&'a [(ArenaKey<'a>, ArenaValue<'a>)]),
+    Null,
+}
+
+/// A dictionary key of [`ArenaValue`].
+#[derive(PartialEq, Eq, Debug, PartialOrd, Ord, Clone, Copy)]
+pub enum ArenaKey<'a> {
+    Binary(&'a [u8]),
+    Text(&'a str),
+}
+
+/// A number of [`ArenaValue`].
+///
+/// The arena doesn't run destructors, so numbers which don't fit in [`i64`] are kept as their
+/// decimal literals borrowed from the input instead of allocating a [`BigInt`](num_bigint::BigInt).
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ArenaNumber<'a> {
+    Small(i64),
+    Big(&'a str),
+}
+
+impl ArenaNumber<'_> {
+    /// Convert into [`BencodexNumber`].
+    pub fn to_number(&self) -> BencodexNumber {
+        match self {
+            ArenaNumber::Small(n) => BencodexNumber::from(*n),
+            // The literal was validated while decoding.
+            ArenaNumber::Big(literal) => literal.parse().unwrap(),
+        }
+    }
+}
+
+impl<'a> ArenaKey<'a> {
+    /// Convert into [`BencodexKey`] borrowing the same data.
+    pub fn to_key(&self) -> BencodexKey<'a> {
+        match self {
+            ArenaKey::Binary(b) => BencodexKey::Binary(Cow::Borrowed(b)),
+            ArenaKey::Text(s) => BencodexKey::Text(Cow::Borrowed(s)),
+        }
+    }
+}
+
+impl<'a> ArenaValue<'a> {
+    /// Get the type of this value.
+    pub fn value_type(&self) -> BencodexType {
+        match self {
+            ArenaValue::Binary(_) => BencodexType::Binary,
+            ArenaValue::Text(_) => BencodexType::Text,
+            ArenaValue::Boolean(_) => BencodexType::Boolean,
+            ArenaValue::Number(_) => BencodexType::Number,
+            ArenaValue::List(_) => BencodexType::List,
+            ArenaValue::Dictionary(_) => BencodexType::Dictionary,
+            ArenaValue::Null => BencodexType::Null,
+        }
+    }
+
+    /// Get the elements if this value is a list.
+    pub fn as_list(&self) -> Option<&'a [ArenaValue<'a>]> {
+        match self {
+            ArenaValue::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Get the entries if this value is a dictionary.
+    pub fn as_dictionary(&self) -> Option<&'a [(ArenaKey<'a>, ArenaValue<'a>)]> {
+        match self {
+            ArenaValue::Dictionary(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Look up `key` with binary search if this value is a dictionary.
+    pub fn get(&self, key: &ArenaKey<'_>) -> Option<&'a ArenaValue<'a>> {
+        let entries = self.as_dictionary()?;
+        entries
+            .binary_search_by(|(k, _)| k.cmp(key))
+            .ok()
+            .map(|i| &entries[i].1)
+    }
+
+    /// Convert into [`BencodexValue`], borrowing binary and text data from the input.
+    ///
+    /// This allocates every container on the heap again, so it is mainly for interoperating with
+    /// the other APIs.
+    pub fn to_value(&self) -> BencodexValue<'a> {
+        match self {
+            ArenaValue::Binary(b) => BencodexValue::Binary(Cow::Borrowed(b)),
+            ArenaValue::Text(s) => BencodexValue::Text(Cow::Borrowed(s)),
+            ArenaValue::Boolean(b) => BencodexValue::Boolean(*b),
+            ArenaValue::Number(n) => BencodexValue::Number(n.to_number()),
+            ArenaValue::List(list) => {
+                BencodexValue::List(list.iter().map(ArenaValue::to_value).collect())
+            }
+            ArenaValue::Dictionary(entries) => BencodexValue::Dictionary(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k.to_key(), v.to_value()))
+                    .collect(),
+            ),
+            ArenaValue::Null => BencodexValue::Null,
+        }
+    }
+}
+
+/// Decode a Bencodex value, allocating its lists and dictionaries in `bump`.
+///
+/// It accepts and rejects the same inputs as [`Decode::decode`](super::decode::Decode::decode).
+pub fn decode_in<'a>(input: &'a [u8], bump: &'a Bump) -> Result<ArenaValue<'a>, DecodeError> {
+    Ok(decode_impl(input, 0, bump)?.0)
+}
+
+#[inline]
+fn byte_at(vector: &[u8], index: usize) -> Result<u8, DecodeError> {
+    vector
+        .get(index)
+        .copied()
+        .ok_or(DecodeError::InvalidBencodexValueError)
+}
+
+#[inline]
+fn expect(vector: &[u8], index: usize, expected: u8) -> Result<(), DecodeError> {
+    match byte_at(vector, index)? {
+        token if token == expected => Ok(()),
+        token => Err(DecodeError::UnexpectedTokenError {
+            token,
+            point: index,
+        }),
+    }
+}
+
+fn decode_impl<'a>(
+    vector: &'a [u8],
+    start: usize,
+    bump: &'a Bump,
+) -> Result<(ArenaValue<'a>, usize), DecodeError> {
+    match byte_at(vector, start)? {
+        b'd' => decode_dict_impl(vector, start, bump),
+        b'l' => decode_list_impl(vector, start, bump),
+        b'u' => decode_unicode_string_impl(vector, start),
+        b'i' => decode_number_impl(vector, start),
+        b'0'..=b'9' => decode_byte_string_impl(vector, start),
+        b't' => Ok((ArenaValue::Boolean(true), 1)),
+        b'f' => Ok((ArenaValue::Boolean(false), 1)),
+        b'n' => Ok((ArenaValue::Null, 1)),
+        token => Err(DecodeError::UnexpectedTokenError {
+            token,
+            point: start,
+        }),
+    }
+}
+
+// start must be on 'd'
+fn decode_dict_impl<'a>(
+    vector: &'a [u8],
+    start: usize,
+    bump: &'a Bump,
+) -> Result<(ArenaValue<'a>, usize), DecodeError> {
+    let mut index = start + 1;
+    let mut entries = bumpalo::collections::Vec::new_in(bump);
+    let mut sorted = true;
+    while byte_at(vector, index)? != b'e' {
+        let (key, size) = decode_impl(vector, index, bump)?;
+        let key = match key {
+            ArenaValue::Text(s) => ArenaKey::Text(s),
+            ArenaValue::Binary(b) => ArenaKey::Binary(b),
+            _ => return Err(DecodeError::InvalidBencodexValueError),
+        };
+        index += size;
+        let (value, size) = decode_impl(vector, index, bump)?;
+        index += size;
+
+        if let Some((last, _)) = entries.last() {
+            sorted &= *last < key;
+        }
+        entries.push((key, value));
+    }
+
+    if !sorted {
+        entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        if entries.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err(DecodeError::InvalidBencodexValueError);
+        }
+    }
+
+    Ok((
+        ArenaValue::Dictionary(entries.into_bump_slice()),
+        index + 1 - start,
+    ))
+}
+
+// start must be on 'l'
+fn decode_list_impl<'a>(
+    vector: &'a [u8],
+    start: usize,
+    bump: &'a Bump,
+) -> Result<(ArenaValue<'a>, usize), DecodeError> {
+    let mut index = start + 1;
+    let mut list = bumpalo::collections::Vec::new_in(bump);
+    while byte_at(vector, index)? != b'e' {
+        let (value, size) = decode_impl(vector, index, bump)?;
+        list.push(value);
+        index += size;
+    }
+
+    Ok((ArenaValue::List(list.into_bump_slice()), index + 1 - start))
+}
+
+fn decode_byte_string_impl(
+    vector: &[u8],
+    start: usize,
+) -> Result<(ArenaValue<'_>, usize), DecodeError> {
+    let (length, size) =
+        read_length(&vector[start..]).ok_or(DecodeError::InvalidBencodexValueError)?;
+    let index = start + size;
+    expect(vector, index, b':')?;
+    let data = vector
+        .get(index + 1..)
+        .and_then(|rest| rest.get(..length))
+        .ok_or(DecodeError::InvalidBencodexValueError)?;
+    Ok((ArenaValue::Binary(data), size + 1 + length))
+}
+
+// start must be on 'u'
+fn decode_unicode_string_impl(
+    vector: &[u8],
+    start: usize,
+) -> Result<(ArenaValue<'_>, usize), DecodeError> {
+    let token = byte_at(vector, start + 1)?;
+    let (length, size) =
+        read_length(&vector[start + 1..]).ok_or(DecodeError::UnexpectedTokenError {
+            token,
+            point: start + 1,
+        })?;
+    let index = start + 1 + size;
+    expect(vector, index, b':')?;
+    let data = vector
+        .get(index + 1..)
+        .and_then(|rest| rest.get(..length))
+        .ok_or(DecodeError::InvalidBencodexValueError)?;
+    let text = str::from_utf8(data).map_err(|_| DecodeError::InvalidBencodexValueError)?;
+    Ok((ArenaValue::Text(text), 1 + size + 1 + length))
+}
+
+// start must be on 'i'
+fn decode_number_impl(vector: &[u8], start: usize) -> Result<(ArenaValue<'_>, usize), DecodeError> {
+    let token = byte_at(vector, start + 1)?;
+    let (number, size) =
+        read_number(&vector[start + 1..]).ok_or(DecodeError::UnexpectedTokenError {
+            token,
+            point: start + 1,
+        })?;
+    let index = start + 1 + size;
+    expect(vector, index, b'e')?;
+
+    let number = match number.as_i64() {
+        Some(n) => ArenaNumber::Small(n),
+        // SAFETY: `read_number` only accepts an optional '-' followed by ASCII digits.
+        None => ArenaNumber::Big(unsafe { str::from_utf8_unchecked(&vector[start + 1..index]) }),
+    };
+    Ok((ArenaValue::Number(number), size + 2))
+}
+
+#[cfg(test)]
+mod tests {
+    mod decode_in {
+        use super::super::*;
+        use crate::Decode;
+        use alloc::vec;
+
+        fn assert_same_as_scalar(input: &[u8]) {
+            let bump = Bump::new();
+            let expected = input.to_vec().decode();
+            let actual = decode_in(input, &bump).map(|v| v.to_value());
+            assert_eq!(expected, actual, "input: {:?}", input);
+        }
+
+        #[test]
+        fn scalars() {
+            for input in [
+                &b"n"[..],
+                b"t",
+                b"f",
+                b"i0e",
+                b"i-123e",
+                b"i9223372036854775807e",
+                b"i-9223372036854775809e",
+                b"i123456789012345678901234567890e",
+                b"0:",
+                b"3:abc",
+                b"u0:",
+                b"u6:\xed\x95\x9c\xea\xb8\x80",
+            ] {
+                assert_same_as_scalar(input);
+            }
+        }
+
+        #[test]
+        fn containers() {
+            for input in [
+                &b"le"[..],
+                b"de",
+                b"li1eli2eu1:aee",
+                b"du1:ali1ei2ee1:bdu1:cnee",
+                b"du1:bi2eu1:ai1e1:xte",
+            ] {
+                assert_same_as_scalar(input);
+            }
+        }
+
+        #[test]
+        fn invalid() {
+            for input in [
+                &b""[..],
+                b"x",
+                b"i:e",
+                b"i-e",
+                b"i1",
+                b"5:abc",
+                b"u5:abc",
+                b"u2:\xff\xfe",
+                b"l",
+                b"li1e",
+                b"d",
+                b"du1:a",
+                b"di1ei2ee",
+            ] {
+                assert_same_as_scalar(input);
+            }
+        }
+
+        #[test]
+        fn big_number() {
+            let bump = Bump::new();
+            let value = decode_in(b"i-18446744073709551616e", &bump).unwrap();
+            assert_eq!(
+                ArenaValue::Number(ArenaNumber::Big("-18446744073709551616")),
+                value
+            );
+        }
+
+        #[test]
+        fn sort_unordered_keys() {
+            let bump = Bump::new();
+            let value = decode_in(b"du1:b1:xu1:a1:y1:z1:we", &bump).unwrap();
+            assert_eq!(
+                vec![
+                    ArenaKey::Binary(b"z"),
+                    ArenaKey::Text("a"),
+                    ArenaKey::Text("b")
+                ],
+                value
+                    .as_dictionary()
+                    .unwrap()
+                    .iter()
+                    .map(|(k, _)| *k)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                Some(&ArenaValue::Binary(b"y")),
+                value.get(&ArenaKey::Text("a"))
+            );
+            assert_eq!(None, value.get(&ArenaKey::Text("c")));
+        }
+
+        #[test]
+        fn duplicated_keys() {
+            let bump = Bump::new();
+            assert_eq!(
+                DecodeError::InvalidBencodexValueError,
+                decode_in(b"du1:ai1eu1:ai2ee", &bump).unwrap_err()
+            );
+        }
+    }
+}
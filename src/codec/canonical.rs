@@ -0,0 +1,206 @@
+//! Lenient decoding of non-canonical Bencodex, and rewriting it into the canonical form.
+//!
+//! The canonical form has dictionary keys in order without duplicates, and no leading zeros in
+//! lengths and numbers. [`Decode::decode`](super::decode::Decode::decode) accepts most of the
+//! non-canonical forms silently; [`decode_lenient`] accepts the same input, and reports every
+//! [`CanonicalIssue`] it finds.
+//!
+//! ```
+//! use bencodex::canonical::{ CanonicalIssue, CanonicalIssueKind, canonicalize };
+//!
+//! let (canonical, issues) = canonicalize(b"d1:bi01e1:a01:xe").unwrap();
+//! assert_eq!(canonical, b"d1:a1:x1:bi1ee");
+//! assert_eq!(issues, vec![
+//!     CanonicalIssue { kind: CanonicalIssueKind::NonCanonicalNumber, point: 5 },
+//!     CanonicalIssue { kind: CanonicalIssueKind::UnorderedKey, point: 8 },
+//!     CanonicalIssue { kind: CanonicalIssueKind::LeadingZeroLength, point: 11 },
+//! ]);
+//! ```
+
+use super::decode::{DecodeError, Observer, decode_impl};
+use super::types::*;
+use crate::prelude::*;
+use core::fmt;
+
+/// The kind of a [`CanonicalIssue`].
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash)]
+pub enum CanonicalIssueKind {
+    /// A dictionary key is less than a key before it.
+    UnorderedKey,
+    /// A dictionary key appears more than once. The last value of the key is kept.
+    DuplicatedKey,
+    /// The length of a binary or a text has leading zeros, e.g., `01:a`.
+    LeadingZeroLength,
+    /// A number has leading zeros or is a negative zero, e.g., `i01e` or `i-0e`.
+    NonCanonicalNumber,
+    /// There are bytes after the value.
+    TrailingData,
+}
+
+/// A part of the input which is not in the canonical form, found by [`decode_lenient`].
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Hash)]
+pub struct CanonicalIssue {
+    pub kind: CanonicalIssueKind,
+    /// The offset of the issue in the input.
+    pub point: usize,
+}
+
+impl fmt::Display for CanonicalIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CanonicalIssueKind::UnorderedKey => "unordered dictionary key",
+            CanonicalIssueKind::DuplicatedKey => "duplicated dictionary key",
+            CanonicalIssueKind::LeadingZeroLength => "leading zeros in length",
+            CanonicalIssueKind::NonCanonicalNumber => "non-canonical number",
+            CanonicalIssueKind::TrailingData => "trailing data",
+        })
+    }
+}
+
+impl fmt::Display for CanonicalIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.kind, self.point)
+    }
+}
+
+impl Observer for Vec<CanonicalIssue> {
+    const CHECK_KEY_ORDER: bool = true;
+
+    fn observe(&mut self, kind: CanonicalIssueKind, point: usize) -> Result<(), DecodeError> {
+        self.push(CanonicalIssue { kind, point });
+        Ok(())
+    }
+}
+
+/// Decode `input` like [`decode_borrowed`](super::decode::decode_borrowed), accepting
+/// duplicated keys and trailing data, and return the value with the issues in the order they
+/// are found.
+///
+/// The input is canonical if and only if no issue is returned.
+pub fn decode_lenient(
+    input: &[u8],
+) -> Result<(BencodexValue<'_>, Vec<CanonicalIssue>), DecodeError> {
+    let mut issues = Vec::new();
+    let (value, size) = decode_impl::<true, _>(input, 0, &mut issues)?;
+    if size < input.len() {
+        issues.push(CanonicalIssue {
+            kind: CanonicalIssueKind::TrailingData,
+            point: size,
+        });
+    }
+    Ok((value, issues))
+}
+
+/// Decode `input` by [`decode_lenient`], and return the value encoded in the canonical form with
+/// the issues found. Trailing data is dropped.
+pub fn canonicalize(input: &[u8]) -> Result<(Vec<u8>, Vec<CanonicalIssue>), DecodeError> {
+    let (value, issues) = decode_lenient(input)?;
+    Ok((value.encode_to_vec(), issues))
+}
+
+#[cfg(test)]
+mod tests {
+    mod decode_lenient {
+        use super::super::*;
+        use crate::codec::decode::Decode;
+        use alloc::vec;
+
+        fn issue(kind: CanonicalIssueKind, point: usize) -> CanonicalIssue {
+            CanonicalIssue { kind, point }
+        }
+
+        #[test]
+        fn should_return_no_issues_for_canonical_input() {
+            let input = b"d1:ai-1e1:bl1:xu1:yi0enee";
+            let (value, issues) = decode_lenient(input).unwrap();
+            assert_eq!(Vec::<CanonicalIssue>::new(), issues);
+            assert_eq!(input.to_vec().decode().unwrap(), value);
+        }
+
+        #[test]
+        fn should_report_unordered_and_duplicated_keys() {
+            let (value, issues) = decode_lenient(b"d1:bi1e1:ai2e1:bi3ee").unwrap();
+            assert_eq!(
+                vec![
+                    issue(CanonicalIssueKind::UnorderedKey, 7),
+                    issue(CanonicalIssueKind::DuplicatedKey, 13),
+                ],
+                issues
+            );
+            let mut expected = BencodexDictionary::new();
+            expected.insert(b"a".to_vec().into(), 2.into());
+            expected.insert(b"b".to_vec().into(), 3.into());
+            assert_eq!(BencodexValue::Dictionary(expected), value);
+        }
+
+        #[test]
+        fn should_report_leading_zeros() {
+            let (value, issues) = decode_lenient(b"l001:au02:bci00ei-0ei-01ee").unwrap();
+            assert_eq!(
+                vec![
+                    issue(CanonicalIssueKind::LeadingZeroLength, 1),
+                    issue(CanonicalIssueKind::LeadingZeroLength, 7),
+                    issue(CanonicalIssueKind::NonCanonicalNumber, 13),
+                    issue(CanonicalIssueKind::NonCanonicalNumber, 17),
+                    issue(CanonicalIssueKind::NonCanonicalNumber, 21),
+                ],
+                issues
+            );
+            assert_eq!(
+                BencodexValue::List(vec![
+                    b"a".to_vec().into(),
+                    "bc".into(),
+                    0.into(),
+                    0.into(),
+                    (-1).into(),
+                ]),
+                value
+            );
+        }
+
+        #[test]
+        fn should_report_trailing_data() {
+            let (value, issues) = decode_lenient(b"i1ei2e").unwrap();
+            assert_eq!(vec![issue(CanonicalIssueKind::TrailingData, 3)], issues);
+            assert_eq!(BencodexValue::from(1), value);
+        }
+
+        #[test]
+        fn should_fail_on_invalid_input() {
+            assert!(decode_lenient(b"d1:a").is_err());
+            assert!(decode_lenient(b"di1ei2ee").is_err());
+        }
+    }
+
+    mod canonicalize {
+        use super::super::*;
+
+        #[test]
+        fn should_keep_canonical_input() {
+            let input = b"d1:ai-1e1:bl1:xu1:yi0enee";
+            let (canonical, issues) = canonicalize(input).unwrap();
+            assert!(issues.is_empty());
+            assert_eq!(input.to_vec(), canonical);
+        }
+
+        #[test]
+        fn should_rewrite_non_canonical_input() {
+            let (canonical, issues) = canonicalize(b"d1:bi-00e01:al01:xe1:bnex").unwrap();
+            assert_eq!(6, issues.len());
+            assert_eq!(b"d1:al1:xe1:bne".to_vec(), canonical);
+        }
+    }
+
+    mod decode {
+        use crate::codec::decode::{Decode, DecodeError};
+        use alloc::vec;
+
+        #[test]
+        fn should_reject_duplicated_keys() {
+            assert_eq!(
+                Err(DecodeError::InvalidBencodexValueError),
+                vec![b'd', b'1', b':', b'a', b'n', b'1', b':', b'a', b'n', b'e'].decode()
+            );
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use super::canonical::CanonicalIssueKind;
+use super::dictionary::DictionaryBuilder;
 use super::types::*;
 use crate::prelude::*;
 use core::error::Error;
@@ -5,7 +7,6 @@ use core::fmt;
 use core::result::Result;
 use core::str;
 use core::str::FromStr;
-use num_bigint::BigInt;
 
 /// The error type which is returned from decoding a Bencodex value through [`Decode::decode`].
 #[derive(Debug, PartialEq)]
@@ -110,22 +111,46 @@ impl Expect<u8> for u8 {
     }
 }
 
+/// Receives the non-canonical parts of the input while decoding, and decides whether to accept
+/// them.
+pub(crate) trait Observer {
+    /// Whether to check the order of dictionary keys, which costs a comparison per key.
+    const CHECK_KEY_ORDER: bool;
+
+    /// Observe an issue at `point`. Returning [`Err`] stops decoding.
+    fn observe(&mut self, kind: CanonicalIssueKind, point: usize) -> Result<(), DecodeError>;
+}
+
+/// The default decoding, which accepts non-canonical input except duplicated keys.
+impl Observer for () {
+    const CHECK_KEY_ORDER: bool = false;
+
+    #[inline(always)]
+    fn observe(&mut self, kind: CanonicalIssueKind, _point: usize) -> Result<(), DecodeError> {
+        match kind {
+            CanonicalIssueKind::DuplicatedKey => Err(DecodeError::InvalidBencodexValueError),
+            _ => Ok(()),
+        }
+    }
+}
+
 /// When `BORROW` is true, Binary/Text values borrow from the input slice (zero-copy).
 /// When `BORROW` is false, Binary/Text values are owned copies (for `Decode for Vec<u8>`).
-fn decode_impl<'a, const BORROW: bool>(
+pub(super) fn decode_impl<'a, const BORROW: bool, O: Observer>(
     vector: &'a [u8],
     start: usize,
+    observer: &mut O,
 ) -> Result<(BencodexValue<'a>, usize), DecodeError> {
     if start >= vector.len() {
         return Err(DecodeError::InvalidBencodexValueError);
     }
 
     match vector[start] {
-        b'd' => decode_dict_impl::<BORROW>(vector, start),
-        b'l' => decode_list_impl::<BORROW>(vector, start),
-        b'u' => decode_unicode_string_impl::<BORROW>(vector, start),
-        b'i' => decode_number_impl(vector, start),
-        b'0'..=b'9' => decode_byte_string_impl::<BORROW>(vector, start),
+        b'd' => decode_dict_impl::<BORROW, O>(vector, start, observer),
+        b'l' => decode_list_impl::<BORROW, O>(vector, start, observer),
+        b'u' => decode_unicode_string_impl::<BORROW, O>(vector, start, observer),
+        b'i' => decode_number_impl(vector, start, observer),
+        b'0'..=b'9' => decode_byte_string_impl::<BORROW, O>(vector, start, observer),
         b't' => Ok((BencodexValue::Boolean(true), 1)),
         b'f' => Ok((BencodexValue::Boolean(false), 1)),
         b'n' => Ok((BencodexValue::Null, 1)),
@@ -155,9 +180,10 @@ fn cow_text<'a, const BORROW: bool>(text: &'a str) -> Cow<'a, str> {
 }
 
 // start must be on 'd'
-fn decode_dict_impl<'a, const BORROW: bool>(
+fn decode_dict_impl<'a, const BORROW: bool, O: Observer>(
     vector: &'a [u8],
     start: usize,
+    observer: &mut O,
 ) -> Result<(BencodexValue<'a>, usize), DecodeError> {
     vector
         .get(start)
@@ -166,22 +192,25 @@ fn decode_dict_impl<'a, const BORROW: bool>(
 
     let mut tsize: usize = 1;
     let mut index = start + tsize;
-    let mut map = BTreeMap::new();
+    let mut map = DictionaryBuilder::default();
     while vector.get(index).should_not_be_none()? != b'e' {
-        let (value, size) = decode_impl::<BORROW>(vector, index)?;
+        let key_point = index;
+        let (value, size) = decode_impl::<BORROW, O>(vector, index, observer)?;
         let key = match value {
             BencodexValue::Text(s) => BencodexKey::Text(s),
             BencodexValue::Binary(b) => BencodexKey::Binary(b),
             _ => return Err(DecodeError::InvalidBencodexValueError),
         };
+        if O::CHECK_KEY_ORDER && map.max_key().is_some_and(|max| key < *max) {
+            observer.observe(CanonicalIssueKind::UnorderedKey, key_point)?;
+        }
         tsize += size;
         index = start + tsize;
-        let (value, size) = decode_impl::<BORROW>(vector, index)?;
+        let (value, size) = decode_impl::<BORROW, O>(vector, index, observer)?;
 
-        match map.insert(key, value) {
-            None => (),
-            Some(_) => todo!(),
-        };
+        if let Some(point) = map.push(key, value, key_point) {
+            observer.observe(CanonicalIssueKind::DuplicatedKey, point)?;
+        }
         tsize += size;
         index = start + tsize;
     }
@@ -192,13 +221,18 @@ fn decode_dict_impl<'a, const BORROW: bool>(
         .expect(b'e', index)?;
     tsize += 1;
 
+    let (map, duplicates) = map.finish();
+    for point in duplicates {
+        observer.observe(CanonicalIssueKind::DuplicatedKey, point)?;
+    }
     Ok((BencodexValue::Dictionary(map), tsize))
 }
 
 // start must be on 'l'
-fn decode_list_impl<'a, const BORROW: bool>(
+fn decode_list_impl<'a, const BORROW: bool, O: Observer>(
     vector: &'a [u8],
     start: usize,
+    observer: &mut O,
 ) -> Result<(BencodexValue<'a>, usize), DecodeError> {
     vector
         .get(start)
@@ -209,7 +243,7 @@ fn decode_list_impl<'a, const BORROW: bool>(
     let mut list = Vec::new();
     let mut index = start + tsize;
     while vector.get(index).should_not_be_none()? != b'e' {
-        let (value, size) = decode_impl::<BORROW>(vector, index)?;
+        let (value, size) = decode_impl::<BORROW, O>(vector, index, observer)?;
         list.push(value);
         tsize += size;
         index = start + tsize
@@ -225,15 +259,19 @@ fn decode_list_impl<'a, const BORROW: bool>(
     Ok((BencodexValue::List(list), tsize))
 }
 
-fn decode_byte_string_impl<'a, const BORROW: bool>(
+fn decode_byte_string_impl<'a, const BORROW: bool, O: Observer>(
     vector: &'a [u8],
     start: usize,
+    observer: &mut O,
 ) -> Result<(BencodexValue<'a>, usize), DecodeError> {
     let mut tsize: usize = 0;
     let (length, size) = match read_length(&vector[start + tsize..]) {
         None => return Err(DecodeError::InvalidBencodexValueError),
         Some(v) => v,
     };
+    if size > 1 && vector[start + tsize] == b'0' {
+        observer.observe(CanonicalIssueKind::LeadingZeroLength, start + tsize)?;
+    }
     tsize += size;
 
     let index = start + tsize;
@@ -254,9 +292,10 @@ fn decode_byte_string_impl<'a, const BORROW: bool>(
 }
 
 // start must be on 'u'
-fn decode_unicode_string_impl<'a, const BORROW: bool>(
+fn decode_unicode_string_impl<'a, const BORROW: bool, O: Observer>(
     vector: &'a [u8],
     start: usize,
+    observer: &mut O,
 ) -> Result<(BencodexValue<'a>, usize), DecodeError> {
     vector
         .get(start)
@@ -276,6 +315,9 @@ fn decode_unicode_string_impl<'a, const BORROW: bool>(
         }
         Some(v) => v,
     };
+    if size > 1 && vector[start + tsize] == b'0' {
+        observer.observe(CanonicalIssueKind::LeadingZeroLength, start + tsize)?;
+    }
     tsize += size;
 
     let index = start + tsize;
@@ -297,9 +339,10 @@ fn decode_unicode_string_impl<'a, const BORROW: bool>(
 }
 
 // start must be on 'i'
-fn decode_number_impl<'a>(
+fn decode_number_impl<'a, O: Observer>(
     vector: &'a [u8],
     start: usize,
+    observer: &mut O,
 ) -> Result<(BencodexValue<'a>, usize), DecodeError> {
     let mut tsize: usize = 1;
     if vector.len() < start + tsize + 1 {
@@ -314,6 +357,13 @@ fn decode_number_impl<'a>(
         }
         Some(v) => v,
     };
+    let digits = &vector[start + tsize..start + tsize + size];
+    let unsigned = digits.strip_prefix(b"-").unwrap_or(digits);
+    if unsigned.len() > 1 && unsigned[0] == b'0'
+        || unsigned.len() < digits.len() && number.is_zero()
+    {
+        observer.observe(CanonicalIssueKind::NonCanonicalNumber, start + tsize)?;
+    }
     tsize += size;
 
     let index = start + tsize;
@@ -329,7 +379,7 @@ fn decode_number_impl<'a>(
 /// Returns `(value, bytes_consumed)` or `None` if the input is empty,
 /// starts with `-`, or contains no digits.
 #[inline]
-fn read_length(s: &[u8]) -> Option<(usize, usize)> {
+pub(super) fn read_length(s: &[u8]) -> Option<(usize, usize)> {
     if s.is_empty() || s[0] == b'-' {
         return None;
     }
@@ -351,7 +401,7 @@ fn read_length(s: &[u8]) -> Option<(usize, usize)> {
     if size == 0 { None } else { Some((value, size)) }
 }
 
-fn read_number(s: &[u8]) -> Option<(BigInt, usize)> {
+pub(super) fn read_number(s: &[u8]) -> Option<(BencodexNumber, usize)> {
     if s.is_empty() {
         return None;
     }
@@ -375,20 +425,24 @@ fn read_number(s: &[u8]) -> Option<(BigInt, usize)> {
     if is_negative && size == 1 || size == 0 {
         None
     } else {
-        // Fast-path: small numbers that fit in i64 (up to 19 digits)
+        // Fast-path: small numbers that fit in i64 (up to 18 digits) never touch BigInt.
         // i64 max is 9,223,372,036,854,775,807 (19 digits)
         let digit_count = if is_negative { size - 1 } else { size };
+        if digit_count <= 18 {
+            let mut value: i64 = 0;
+            for &b in &s[is_negative as usize..size] {
+                value = value * 10 + (b - b'0') as i64;
+            }
+            return Some((
+                BencodexNumber::from(if is_negative { -value } else { value }),
+                size,
+            ));
+        }
         // SAFETY: The loop above only advances `size` for bytes matching b'0'..=b'9',
         // and s[0] is checked for b'-'. These are all single-byte ASCII, which is valid UTF-8.
         let str_slice = unsafe { core::str::from_utf8_unchecked(&s[..size]) };
-        if digit_count <= 18
-            && let Ok(n) = str_slice.parse::<i64>()
-        {
-            // Safe to parse as i64 (18 digits is always within i64 range)
-            return Some((BigInt::from(n), size));
-        }
-        // Large numbers or parsing edge cases: use BigInt directly
-        Some((BigInt::from_str(str_slice).unwrap(), size))
+        // 19 digits or more may still fit in i64; BencodexNumber only falls back to BigInt if not
+        Some((BencodexNumber::from_str(str_slice).unwrap(), size))
     }
 }
 
@@ -406,21 +460,79 @@ fn read_number(s: &[u8]) -> Option<(BigInt, usize)> {
 /// assert_eq!(value, BencodexValue::Text("hello".into()));
 /// ```
 pub fn decode_borrowed(input: &[u8]) -> Result<BencodexValue<'_>, DecodeError> {
-    Ok(decode_impl::<true>(input, 0)?.0)
+    Ok(decode_impl::<true, _>(input, 0, &mut ())?.0)
+}
+
+/// Decode the Bencodex values concatenated in `input`, with zero-copy borrowing like
+/// [`decode_borrowed`].
+///
+/// The iterator yields each value in turn. After yielding an error, it yields nothing more
+/// because the start of the next value is unknown.
+///
+/// # Examples
+/// ```
+/// use bencodex::{BencodexValue, decode_iter};
+///
+/// let values: Vec<_> = decode_iter(b"i1eu1:an").collect();
+/// assert_eq!(values, vec![Ok(1.into()), Ok("a".into()), Ok(BencodexValue::Null)]);
+///
+/// let mut values = decode_iter(b"i1ex");
+/// assert_eq!(values.next(), Some(Ok(1.into())));
+/// assert!(values.next().unwrap().is_err());
+/// assert_eq!(values.next(), None);
+/// ```
+pub fn decode_iter(input: &[u8]) -> DecodeIter<'_> {
+    DecodeIter { input, position: 0 }
+}
+
+/// An iterator over concatenated Bencodex values, returned by [`decode_iter`].
+#[derive(Debug, Clone)]
+pub struct DecodeIter<'a> {
+    input: &'a [u8],
+    position: usize,
 }
 
+impl DecodeIter<'_> {
+    /// Get the position in bytes where the next value starts.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl<'a> Iterator for DecodeIter<'a> {
+    type Item = Result<BencodexValue<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.input.len() {
+            return None;
+        }
+
+        match decode_impl::<true, _>(self.input, self.position, &mut ()) {
+            Ok((value, size)) => {
+                self.position += size;
+                Some(Ok(value))
+            }
+            Err(e) => {
+                self.position = self.input.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for DecodeIter<'_> {}
+
 impl Decode for Vec<u8> {
     /// ```
-    /// use bencodex::{ Decode, BencodexValue };
-    /// use std::collections::BTreeMap;
+    /// use bencodex::{ Decode, BencodexDictionary, BencodexValue };
     ///
     /// let buf = b"de".to_vec();
     /// let dictionary = buf.decode().ok().unwrap();
     ///
-    /// assert_eq!(dictionary, BencodexValue::Dictionary(BTreeMap::new()));
+    /// assert_eq!(dictionary, BencodexValue::Dictionary(BencodexDictionary::new()));
     /// ```
     fn decode(self) -> Result<BencodexValue<'static>, DecodeError> {
-        let value = decode_impl::<false>(&self, 0)?.0;
+        let value = decode_impl::<false, _>(&self, 0, &mut ())?.0;
         // SAFETY: With BORROW=false, all Cow values in the tree are Cow::Owned,
         // so no references with the local lifetime exist. The lifetime parameter
         // is purely phantom, making the transmute to 'static sound.
@@ -441,9 +553,18 @@ mod tests {
         #[test]
         fn should_return_error_with_overflowed_start() {
             let expected_error = DecodeError::InvalidBencodexValueError;
-            assert_eq!(expected_error, decode_impl::<true>(&[], 1).unwrap_err());
-            assert_eq!(expected_error, decode_impl::<true>(b"12", 2).unwrap_err());
-            assert_eq!(expected_error, decode_impl::<true>(b"12", 20).unwrap_err());
+            assert_eq!(
+                expected_error,
+                decode_impl::<true, _>(&[], 1, &mut ()).unwrap_err()
+            );
+            assert_eq!(
+                expected_error,
+                decode_impl::<true, _>(b"12", 2, &mut ()).unwrap_err()
+            );
+            assert_eq!(
+                expected_error,
+                decode_impl::<true, _>(b"12", 20, &mut ()).unwrap_err()
+            );
         }
 
         #[test]
@@ -453,14 +574,14 @@ mod tests {
                     token: b'x',
                     point: 0,
                 },
-                decode_impl::<true>(b"x", 0).unwrap_err()
+                decode_impl::<true, _>(b"x", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 DecodeError::UnexpectedTokenError {
                     token: b'k',
                     point: 4,
                 },
-                decode_impl::<true>(b"xyzok", 4).unwrap_err()
+                decode_impl::<true, _>(b"xyzok", 4, &mut ()).unwrap_err()
             );
         }
     }
@@ -473,15 +594,15 @@ mod tests {
             let expected_error = DecodeError::InvalidBencodexValueError;
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(b"d", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"d", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(b"d", 2).unwrap_err()
+                decode_dict_impl::<true, _>(b"d", 2, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(&[], 0).unwrap_err()
+                decode_dict_impl::<true, _>(&[], 0, &mut ()).unwrap_err()
             );
         }
 
@@ -491,27 +612,27 @@ mod tests {
             // { 0: null }
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(b"di0ene", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"di0ene", 0, &mut ()).unwrap_err()
             );
             // { null: null }
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(b"dnne", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"dnne", 0, &mut ()).unwrap_err()
             );
             // { list: null }
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(b"dlene", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"dlene", 0, &mut ()).unwrap_err()
             );
             // { dictionary: null }
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(b"ddene", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"ddene", 0, &mut ()).unwrap_err()
             );
             // { boolean: null }
             assert_eq!(
                 expected_error,
-                decode_dict_impl::<true>(b"dtene", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"dtene", 0, &mut ()).unwrap_err()
             );
         }
 
@@ -522,14 +643,14 @@ mod tests {
                     token: b'k',
                     point: 1,
                 },
-                decode_dict_impl::<true>(b"dkne", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"dkne", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 DecodeError::UnexpectedTokenError {
                     token: b'k',
                     point: 4,
                 },
-                decode_dict_impl::<true>(b"d1:ake", 0).unwrap_err()
+                decode_dict_impl::<true, _>(b"d1:ake", 0, &mut ()).unwrap_err()
             );
         }
     }
@@ -542,15 +663,15 @@ mod tests {
             let expected_error = DecodeError::InvalidBencodexValueError;
             assert_eq!(
                 expected_error,
-                decode_list_impl::<true>(b"l", 0).unwrap_err()
+                decode_list_impl::<true, _>(b"l", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_list_impl::<true>(b"l", 2).unwrap_err()
+                decode_list_impl::<true, _>(b"l", 2, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_list_impl::<true>(&[], 0).unwrap_err()
+                decode_list_impl::<true, _>(&[], 0, &mut ()).unwrap_err()
             );
         }
 
@@ -561,7 +682,7 @@ mod tests {
                     token: b'k',
                     point: 1,
                 },
-                decode_list_impl::<true>(b"lke", 0).unwrap_err()
+                decode_list_impl::<true, _>(b"lke", 0, &mut ()).unwrap_err()
             );
         }
     }
@@ -574,19 +695,19 @@ mod tests {
             let expected_error = DecodeError::InvalidBencodexValueError;
             assert_eq!(
                 expected_error,
-                decode_byte_string_impl::<true>(b"1", 0).unwrap_err()
+                decode_byte_string_impl::<true, _>(b"1", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_byte_string_impl::<true>(b"1:", 0).unwrap_err()
+                decode_byte_string_impl::<true, _>(b"1:", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_byte_string_impl::<true>(b"2:a", 0).unwrap_err()
+                decode_byte_string_impl::<true, _>(b"2:a", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_byte_string_impl::<true>(&[], 0).unwrap_err()
+                decode_byte_string_impl::<true, _>(&[], 0, &mut ()).unwrap_err()
             );
         }
 
@@ -597,7 +718,7 @@ mod tests {
                     token: b'k',
                     point: 1,
                 },
-                decode_byte_string_impl::<true>(b"1ka", 0).unwrap_err()
+                decode_byte_string_impl::<true, _>(b"1ka", 0, &mut ()).unwrap_err()
             );
         }
     }
@@ -610,26 +731,26 @@ mod tests {
             let expected_error = DecodeError::InvalidBencodexValueError;
             assert_eq!(
                 expected_error,
-                decode_unicode_string_impl::<true>(b"u", 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(b"u", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_unicode_string_impl::<true>(b"u1", 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(b"u1", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_unicode_string_impl::<true>(b"u2:a", 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(b"u2:a", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 DecodeError::UnexpectedTokenError {
                     token: b'k',
                     point: 1,
                 },
-                decode_unicode_string_impl::<true>(b"uk", 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(b"uk", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 expected_error,
-                decode_unicode_string_impl::<true>(&[], 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(&[], 0, &mut ()).unwrap_err()
             );
         }
 
@@ -640,7 +761,7 @@ mod tests {
                     token: b'k',
                     point: 2
                 },
-                decode_unicode_string_impl::<true>(b"u1ka", 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(b"u1ka", 0, &mut ()).unwrap_err()
             );
         }
 
@@ -651,7 +772,7 @@ mod tests {
                     token: b'-',
                     point: 1,
                 },
-                decode_unicode_string_impl::<true>(b"u-1:a", 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(b"u-1:a", 0, &mut ()).unwrap_err()
             );
         }
 
@@ -659,7 +780,8 @@ mod tests {
         fn should_return_error_with_invalid_source_having_invalid_unicode_string() {
             assert_eq!(
                 DecodeError::InvalidBencodexValueError,
-                decode_unicode_string_impl::<true>(&[b'u', b'1', b':', 0x90], 0).unwrap_err()
+                decode_unicode_string_impl::<true, _>(&[b'u', b'1', b':', 0x90], 0, &mut ())
+                    .unwrap_err()
             );
         }
     }
@@ -670,10 +792,22 @@ mod tests {
         #[test]
         fn should_return_error_with_insufficient_length_source() {
             let expected_error = DecodeError::InvalidBencodexValueError;
-            assert_eq!(expected_error, decode_number_impl(b"i", 0).unwrap_err());
-            assert_eq!(expected_error, decode_number_impl(b"i2", 0).unwrap_err());
-            assert_eq!(expected_error, decode_number_impl(b"i-2", 0).unwrap_err());
-            assert_eq!(expected_error, decode_number_impl(&[], 0).unwrap_err());
+            assert_eq!(
+                expected_error,
+                decode_number_impl(b"i", 0, &mut ()).unwrap_err()
+            );
+            assert_eq!(
+                expected_error,
+                decode_number_impl(b"i2", 0, &mut ()).unwrap_err()
+            );
+            assert_eq!(
+                expected_error,
+                decode_number_impl(b"i-2", 0, &mut ()).unwrap_err()
+            );
+            assert_eq!(
+                expected_error,
+                decode_number_impl(&[], 0, &mut ()).unwrap_err()
+            );
         }
 
         #[test]
@@ -683,14 +817,14 @@ mod tests {
                     token: b'a',
                     point: 1,
                 },
-                decode_number_impl(b"iaa", 0).unwrap_err()
+                decode_number_impl(b"iaa", 0, &mut ()).unwrap_err()
             );
             assert_eq!(
                 DecodeError::UnexpectedTokenError {
                     token: b'a',
                     point: 2,
                 },
-                decode_number_impl(b"i1a", 0).unwrap_err()
+                decode_number_impl(b"i1a", 0, &mut ()).unwrap_err()
             );
         }
     }
@@ -766,14 +900,14 @@ mod tests {
 
         #[test]
         fn should_return_ok_with_positive() {
-            assert_eq!(Some((BigInt::from(1), 1)), read_number(b"1"));
-            assert_eq!(Some((BigInt::from(326), 3)), read_number(b"326"));
+            assert_eq!(Some((BencodexNumber::from(1), 1)), read_number(b"1"));
+            assert_eq!(Some((BencodexNumber::from(326), 3)), read_number(b"326"));
         }
 
         #[test]
         fn should_return_ok_with_negative() {
-            assert_eq!(Some((BigInt::from(-1), 2)), read_number(b"-1"));
-            assert_eq!(Some((BigInt::from(-845), 4)), read_number(b"-845"));
+            assert_eq!(Some((BencodexNumber::from(-1), 2)), read_number(b"-1"));
+            assert_eq!(Some((BencodexNumber::from(-845), 4)), read_number(b"-845"));
         }
 
         #[test]
@@ -816,4 +950,38 @@ mod tests {
             assert_eq!(borrowed, owned);
         }
     }
+    mod decode_iter {
+        use super::super::*;
+        use alloc::vec;
+
+        #[test]
+        fn should_decode_concatenated_values() {
+            let mut values = decode_iter(b"li1ee1:\x12tdu1:anen");
+            assert_eq!(Some(Ok(BencodexValue::List(vec![1.into()]))), values.next());
+            assert_eq!(5, values.position());
+            assert_eq!(Some(Ok(vec![0x12u8].into())), values.next());
+            assert_eq!(Some(Ok(true.into())), values.next());
+            assert!(matches!(
+                values.next(),
+                Some(Ok(BencodexValue::Dictionary(_)))
+            ));
+            assert_eq!(Some(Ok(BencodexValue::Null)), values.next());
+            assert_eq!(None, values.next());
+            assert_eq!(0, decode_iter(b"").count());
+        }
+
+        #[test]
+        fn should_stop_after_error() {
+            let mut values = decode_iter(b"nxn");
+            assert_eq!(Some(Ok(BencodexValue::Null)), values.next());
+            assert_eq!(
+                Some(Err(DecodeError::UnexpectedTokenError {
+                    token: b'x',
+                    point: 1
+                })),
+                values.next()
+            );
+            assert_eq!(None, values.next());
+        }
+    }
 }
@@ -0,0 +1,698 @@
+//! A dictionary backed by a sorted [`Vec`].
+
+use super::types::*;
+use crate::prelude::*;
+use core::borrow::Borrow;
+use core::error::Error;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::slice;
+
+/// A dictionary which keeps its entries in a [`Vec`] sorted by their keys.
+///
+/// Decoded dictionaries are already sorted when the input is canonical, so inserting them one by
+/// one only appends to the [`Vec`] and looking them up is a binary search. Its API follows
+/// [`BTreeMap`], and it becomes [`BencodexDictionary`] when the `sorted-dict` feature is enabled.
+///
+/// ```
+/// use bencodex::{ BencodexKey, BencodexValue };
+/// use bencodex::dictionary::SortedDictionary;
+///
+/// let mut dict = SortedDictionary::new();
+/// dict.insert("b".into(), 2.into());
+/// dict.insert("a".into(), 1.into());
+///
+/// assert_eq!(dict.get(&BencodexKey::from("a")), Some(&BencodexValue::from(1)));
+/// assert_eq!(
+///     dict.keys().collect::<Vec<_>>(),
+///     vec![&BencodexKey::from("a"), &BencodexKey::from("b")]
+/// );
+/// ```
+#[derive(PartialEq, Clone, Default)]
+pub struct SortedDictionary<'a> {
+    entries: Vec<(BencodexKey<'a>, BencodexValue<'a>)>,
+}
+
+/// The error type which is returned when a key breaks the canonical order of a
+/// [`SortedDictionary`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DictionaryOrderError {
+    /// The key at `index` is less than the key before it.
+    UnorderedKeyError { index: usize },
+    /// The key at `index` is equal to the key before it.
+    DuplicatedKeyError { index: usize },
+}
+
+impl fmt::Display for DictionaryOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for DictionaryOrderError {}
+
+impl<'a> SortedDictionary<'a> {
+    /// Create an empty dictionary.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Create an empty dictionary which can hold `capacity` entries without reallocation.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Create a dictionary from entries which are already in the canonical order, i.e., strictly
+    /// ascending by their keys.
+    pub fn from_sorted_vec(
+        entries: Vec<(BencodexKey<'a>, BencodexValue<'a>)>,
+    ) -> Result<Self, DictionaryOrderError> {
+        for (i, pair) in entries.windows(2).enumerate() {
+            check_order(&pair[0].0, &pair[1].0, i + 1)?;
+        }
+        Ok(Self { entries })
+    }
+
+    /// Get the entries as a slice sorted by their keys.
+    pub fn as_slice(&self) -> &[(BencodexKey<'a>, BencodexValue<'a>)] {
+        &self.entries
+    }
+
+    /// Take the entries sorted by their keys.
+    pub fn into_vec(self) -> Vec<(BencodexKey<'a>, BencodexValue<'a>)> {
+        self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        BencodexKey<'a>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&BencodexValue<'a>>
+    where
+        BencodexKey<'a>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).ok().map(|i| &self.entries[i].1)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&BencodexKey<'a>, &BencodexValue<'a>)>
+    where
+        BencodexKey<'a>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).ok().map(|i| {
+            let (k, v) = &self.entries[i];
+            (k, v)
+        })
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut BencodexValue<'a>>
+    where
+        BencodexKey<'a>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).ok().map(|i| &mut self.entries[i].1)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        BencodexKey<'a>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).is_ok()
+    }
+
+    /// Append an entry whose key is greater than every key in this dictionary.
+    ///
+    /// This is how a canonical dictionary is built without searching. If `key` breaks the order,
+    /// the dictionary is left unchanged and [`Err`] is returned.
+    pub fn push(
+        &mut self,
+        key: BencodexKey<'a>,
+        value: BencodexValue<'a>,
+    ) -> Result<(), DictionaryOrderError> {
+        if let Some((last, _)) = self.entries.last() {
+            check_order(last, &key, self.entries.len())?;
+        }
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    /// Insert an entry, returning the previous value of `key` if it existed.
+    ///
+    /// Keys greater than the last key are appended without searching, so inserting the entries of
+    /// a canonical dictionary in order takes linear time in total.
+    pub fn insert(
+        &mut self,
+        key: BencodexKey<'a>,
+        value: BencodexValue<'a>,
+    ) -> Option<BencodexValue<'a>> {
+        match self.entries.last() {
+            Some((last, _)) if *last >= key => match self.search(&key) {
+                Ok(i) => Some(core::mem::replace(&mut self.entries[i].1, value)),
+                Err(i) => {
+                    self.entries.insert(i, (key, value));
+                    None
+                }
+            },
+            _ => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<BencodexValue<'a>>
+    where
+        BencodexKey<'a>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(BencodexKey<'a>, BencodexValue<'a>)>
+    where
+        BencodexKey<'a>: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).ok().map(|i| self.entries.remove(i))
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&BencodexKey<'a>, &mut BencodexValue<'a>) -> bool,
+    {
+        self.entries.retain_mut(|(k, v)| f(k, v));
+    }
+
+    pub fn first_key_value(&self) -> Option<(&BencodexKey<'a>, &BencodexValue<'a>)> {
+        self.entries.first().map(|(k, v)| (k, v))
+    }
+
+    pub fn last_key_value(&self) -> Option<(&BencodexKey<'a>, &BencodexValue<'a>)> {
+        self.entries.last().map(|(k, v)| (k, v))
+    }
+
+    pub fn iter(&self) -> Iter<'_, 'a> {
+        Iter(self.entries.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, 'a> {
+        IterMut(self.entries.iter_mut())
+    }
+
+    pub fn keys(&self) -> Keys<'_, 'a> {
+        Keys(self.entries.iter())
+    }
+
+    pub fn values(&self) -> Values<'_, 'a> {
+        Values(self.entries.iter())
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, 'a> {
+        ValuesMut(self.entries.iter_mut())
+    }
+
+    pub fn into_keys(self) -> impl Iterator<Item = BencodexKey<'a>> {
+        self.entries.into_iter().map(|(k, _)| k)
+    }
+
+    pub fn into_values(self) -> impl Iterator<Item = BencodexValue<'a>> {
+        self.entries.into_iter().map(|(_, v)| v)
+    }
+}
+
+#[inline]
+fn check_order(
+    prev: &BencodexKey<'_>,
+    key: &BencodexKey<'_>,
+    index: usize,
+) -> Result<(), DictionaryOrderError> {
+    match prev.cmp(key) {
+        core::cmp::Ordering::Less => Ok(()),
+        core::cmp::Ordering::Equal => Err(DictionaryOrderError::DuplicatedKeyError { index }),
+        core::cmp::Ordering::Greater => Err(DictionaryOrderError::UnorderedKeyError { index }),
+    }
+}
+
+/// Builds a [`SortedDictionary`] from entries in the order they are decoded.
+///
+/// Entries in the canonical order are appended like [`SortedDictionary::push`]. From the first key
+/// out of order, the rest are collected and merged into the dictionary once by
+/// [`finish`](SortedDictionaryBuilder::finish), so building takes O(n log n) time whatever the
+/// order, instead of shifting the entries on every insertion.
+///
+/// Each entry comes with a `point`, e.g., its offset in the input, which is returned for every
+/// key appearing more than once. The last value of such a key is kept.
+pub(crate) struct SortedDictionaryBuilder<'a, P> {
+    dict: SortedDictionary<'a>,
+    /// The entries from the first one out of order, with their points.
+    unordered: Vec<(BencodexKey<'a>, BencodexValue<'a>, P)>,
+    /// The index of the greatest key in `unordered` if it is greater than every key in `dict`.
+    unordered_max: Option<usize>,
+}
+
+impl<P> Default for SortedDictionaryBuilder<'_, P> {
+    fn default() -> Self {
+        Self {
+            dict: SortedDictionary::new(),
+            unordered: Vec::new(),
+            unordered_max: None,
+        }
+    }
+}
+
+impl<'a, P: Ord> SortedDictionaryBuilder<'a, P> {
+    /// Start from the entries of `dict`.
+    pub(crate) fn from_dictionary(dict: SortedDictionary<'a>) -> Self {
+        Self {
+            dict,
+            unordered: Vec::new(),
+            unordered_max: None,
+        }
+    }
+
+    /// Get the greatest key pushed so far.
+    pub(crate) fn max_key(&self) -> Option<&BencodexKey<'a>> {
+        match self.unordered_max {
+            Some(i) => Some(&self.unordered[i].0),
+            None => self.dict.entries.last().map(|(k, _)| k),
+        }
+    }
+
+    /// Add an entry, and return `point` if its key is found to be duplicated at once, i.e., it is
+    /// equal to the key before it. Other duplicated keys are found by
+    /// [`finish`](SortedDictionaryBuilder::finish).
+    pub(crate) fn push(
+        &mut self,
+        key: BencodexKey<'a>,
+        value: BencodexValue<'a>,
+        point: P,
+    ) -> Option<P> {
+        if self.unordered.is_empty() {
+            let order = match self.dict.entries.last() {
+                Some((last, _)) => check_order(last, &key, self.dict.entries.len()),
+                None => Ok(()),
+            };
+            match order {
+                Ok(()) => self.dict.entries.push((key, value)),
+                Err(DictionaryOrderError::DuplicatedKeyError { .. }) => {
+                    self.dict.entries.last_mut().unwrap().1 = value;
+                    return Some(point);
+                }
+                Err(DictionaryOrderError::UnorderedKeyError { .. }) => {
+                    self.unordered.push((key, value, point));
+                }
+            }
+            return None;
+        }
+
+        if self.max_key().is_some_and(|max| key > *max) {
+            self.unordered_max = Some(self.unordered.len());
+        }
+        self.unordered.push((key, value, point));
+        None
+    }
+
+    /// Take the dictionary with the points of the duplicated keys not returned by
+    /// [`push`](SortedDictionaryBuilder::push), in ascending order.
+    pub(crate) fn finish(self) -> (SortedDictionary<'a>, Vec<P>) {
+        let Self {
+            dict,
+            mut unordered,
+            ..
+        } = self;
+        let mut duplicates = Vec::new();
+        if unordered.is_empty() {
+            return (dict, duplicates);
+        }
+
+        // Stable, so the entries of each key stay in the order they are pushed
+        unordered.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+        let mut entries = Vec::with_capacity(dict.entries.len() + unordered.len());
+        let mut ordered = dict.entries.into_iter().peekable();
+        for (key, value, point) in unordered {
+            while let Some(entry) = ordered.next_if(|(k, _)| *k < key) {
+                entries.push(entry);
+            }
+            if ordered.next_if(|(k, _)| *k == key).is_some() {
+                duplicates.push(point);
+                entries.push((key, value));
+                continue;
+            }
+            match entries.last_mut() {
+                Some((last, last_value)) if *last == key => {
+                    duplicates.push(point);
+                    *last_value = value;
+                }
+                _ => entries.push((key, value)),
+            }
+        }
+        entries.extend(ordered);
+        duplicates.sort_unstable();
+        (SortedDictionary { entries }, duplicates)
+    }
+}
+
+/// Builds a [`BencodexDictionary`] from entries in the order they are decoded, with the API of
+/// [`SortedDictionaryBuilder`].
+#[cfg(feature = "sorted-dict")]
+pub(crate) type DictionaryBuilder<'a, P> = SortedDictionaryBuilder<'a, P>;
+
+/// Builds a [`BencodexDictionary`] from entries in the order they are decoded, with the API of
+/// [`SortedDictionaryBuilder`].
+#[cfg(not(feature = "sorted-dict"))]
+#[derive(Default)]
+pub(crate) struct DictionaryBuilder<'a, P> {
+    dict: BencodexDictionary<'a>,
+    _point: core::marker::PhantomData<P>,
+}
+
+#[cfg(not(feature = "sorted-dict"))]
+impl<'a, P: Ord> DictionaryBuilder<'a, P> {
+    pub(crate) fn max_key(&self) -> Option<&BencodexKey<'a>> {
+        self.dict.last_key_value().map(|(k, _)| k)
+    }
+
+    /// Insert an entry, and return `point` if its key is duplicated. A `BTreeMap` finds every
+    /// duplicated key at once.
+    pub(crate) fn push(
+        &mut self,
+        key: BencodexKey<'a>,
+        value: BencodexValue<'a>,
+        point: P,
+    ) -> Option<P> {
+        self.dict.insert(key, value).map(|_| point)
+    }
+
+    pub(crate) fn finish(self) -> (BencodexDictionary<'a>, Vec<P>) {
+        (self.dict, Vec::new())
+    }
+}
+
+impl fmt::Debug for SortedDictionary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> FromIterator<(BencodexKey<'a>, BencodexValue<'a>)> for SortedDictionary<'a> {
+    fn from_iter<T: IntoIterator<Item = (BencodexKey<'a>, BencodexValue<'a>)>>(iter: T) -> Self {
+        let mut entries: Vec<_> = iter.into_iter().collect();
+        // Stable, so the last one wins among the duplicated keys like `BTreeMap`.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut deduped: Vec<(BencodexKey<'a>, BencodexValue<'a>)> =
+            Vec::with_capacity(entries.len());
+        for (key, value) in entries {
+            match deduped.last_mut() {
+                Some((last, last_value)) if *last == key => *last_value = value,
+                _ => deduped.push((key, value)),
+            }
+        }
+        Self { entries: deduped }
+    }
+}
+
+impl<'a, const N: usize> From<[(BencodexKey<'a>, BencodexValue<'a>); N]> for SortedDictionary<'a> {
+    fn from(entries: [(BencodexKey<'a>, BencodexValue<'a>); N]) -> Self {
+        Self::from_iter(entries)
+    }
+}
+
+impl<'a> Extend<(BencodexKey<'a>, BencodexValue<'a>)> for SortedDictionary<'a> {
+    fn extend<T: IntoIterator<Item = (BencodexKey<'a>, BencodexValue<'a>)>>(&mut self, iter: T) {
+        let mut builder = SortedDictionaryBuilder::from_dictionary(core::mem::take(self));
+        for (key, value) in iter {
+            builder.push(key, value, ());
+        }
+        *self = builder.finish().0;
+    }
+}
+
+impl<'a> IntoIterator for SortedDictionary<'a> {
+    type Item = (BencodexKey<'a>, BencodexValue<'a>);
+    type IntoIter = alloc::vec::IntoIter<(BencodexKey<'a>, BencodexValue<'a>)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s SortedDictionary<'a> {
+    type Item = (&'s BencodexKey<'a>, &'s BencodexValue<'a>);
+    type IntoIter = Iter<'s, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s mut SortedDictionary<'a> {
+    type Item = (&'s BencodexKey<'a>, &'s mut BencodexValue<'a>);
+    type IntoIter = IterMut<'s, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+macro_rules! sorted_dictionary_iter_impl {
+    ($name:ident, $iter:ident, $item:ty, $map:expr) => {
+        pub struct $name<'s, 'a>(slice::$iter<'s, (BencodexKey<'a>, BencodexValue<'a>)>);
+
+        impl<'s, 'a> Iterator for $name<'s, 'a> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next().map($map)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl DoubleEndedIterator for $name<'_, '_> {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.0.next_back().map($map)
+            }
+        }
+
+        impl ExactSizeIterator for $name<'_, '_> {}
+
+        impl FusedIterator for $name<'_, '_> {}
+    };
+}
+
+sorted_dictionary_iter_impl!(
+    Iter,
+    Iter,
+    (&'s BencodexKey<'a>, &'s BencodexValue<'a>),
+    |(k, v)| (k, v)
+);
+sorted_dictionary_iter_impl!(
+    IterMut,
+    IterMut,
+    (&'s BencodexKey<'a>, &'s mut BencodexValue<'a>),
+    |(k, v)| (&*k, v)
+);
+sorted_dictionary_iter_impl!(Keys, Iter, &'s BencodexKey<'a>, |(k, _)| k);
+sorted_dictionary_iter_impl!(Values, Iter, &'s BencodexValue<'a>, |(_, v)| v);
+sorted_dictionary_iter_impl!(ValuesMut, IterMut, &'s mut BencodexValue<'a>, |(_, v)| v);
+
+#[cfg(test)]
+mod tests {
+    mod sorted_dictionary {
+        use super::super::*;
+        use alloc::vec;
+
+        fn key(s: &str) -> BencodexKey<'static> {
+            BencodexKey::Text(Cow::Owned(s.to_string()))
+        }
+
+        #[test]
+        fn insert_keeps_order() {
+            let mut dict = SortedDictionary::new();
+            assert_eq!(None, dict.insert(key("b"), 1.into()));
+            assert_eq!(None, dict.insert(key("c"), 2.into()));
+            assert_eq!(None, dict.insert(key("a"), 3.into()));
+            assert_eq!(None, dict.insert(BencodexKey::from(vec![0u8]), 4.into()));
+            assert_eq!(
+                Some(BencodexValue::from(1)),
+                dict.insert(key("b"), 5.into())
+            );
+
+            assert_eq!(
+                vec![
+                    (&BencodexKey::from(vec![0u8]), &BencodexValue::from(4)),
+                    (&key("a"), &BencodexValue::from(3)),
+                    (&key("b"), &BencodexValue::from(5)),
+                    (&key("c"), &BencodexValue::from(2)),
+                ],
+                dict.iter().collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn lookup_and_remove() {
+            let mut dict = SortedDictionary::from([(key("a"), 1.into()), (key("b"), 2.into())]);
+            assert!(dict.contains_key(&key("a")));
+            assert_eq!(Some(&BencodexValue::from(2)), dict.get(&key("b")));
+            assert_eq!(None, dict.get(&key("c")));
+
+            *dict.get_mut(&key("a")).unwrap() = 3.into();
+            assert_eq!(Some(BencodexValue::from(3)), dict.remove(&key("a")));
+            assert_eq!(None, dict.remove(&key("a")));
+            assert_eq!(1, dict.len());
+        }
+
+        #[test]
+        fn push_checks_order() {
+            let mut dict = SortedDictionary::new();
+            assert_eq!(Ok(()), dict.push(key("a"), 1.into()));
+            assert_eq!(Ok(()), dict.push(key("b"), 2.into()));
+            assert_eq!(
+                Err(DictionaryOrderError::DuplicatedKeyError { index: 2 }),
+                dict.push(key("b"), 3.into())
+            );
+            assert_eq!(
+                Err(DictionaryOrderError::UnorderedKeyError { index: 2 }),
+                dict.push(key("a"), 3.into())
+            );
+            assert_eq!(2, dict.len());
+        }
+
+        #[test]
+        fn from_sorted_vec() {
+            assert!(
+                SortedDictionary::from_sorted_vec(vec![
+                    (key("a"), BencodexValue::Null),
+                    (key("b"), BencodexValue::Null),
+                ])
+                .is_ok()
+            );
+            assert_eq!(
+                Err(DictionaryOrderError::UnorderedKeyError { index: 1 }),
+                SortedDictionary::from_sorted_vec(vec![
+                    (key("b"), BencodexValue::Null),
+                    (key("a"), BencodexValue::Null),
+                ])
+            );
+        }
+
+        #[test]
+        fn from_iter_last_wins() {
+            let dict: SortedDictionary = [
+                (key("b"), BencodexValue::from(1)),
+                (key("a"), BencodexValue::from(2)),
+                (key("b"), BencodexValue::from(3)),
+            ]
+            .into_iter()
+            .collect();
+            assert_eq!(
+                vec![
+                    (key("a"), BencodexValue::from(2)),
+                    (key("b"), BencodexValue::from(3)),
+                ],
+                dict.into_vec()
+            );
+        }
+    }
+
+    mod sorted_dictionary_builder {
+        use super::super::*;
+        use alloc::{format, vec};
+
+        fn key(s: &str) -> BencodexKey<'static> {
+            BencodexKey::Text(Cow::Owned(s.to_string()))
+        }
+
+        fn build(keys: &[&str]) -> (Vec<BencodexKey<'static>>, Vec<usize>, Vec<usize>) {
+            let mut builder = SortedDictionaryBuilder::default();
+            let mut at_once = vec![];
+            for (point, k) in keys.iter().enumerate() {
+                assert!(builder.max_key() <= keys[..point].iter().map(|k| key(k)).max().as_ref());
+                at_once.extend(builder.push(key(k), BencodexValue::from(point as i64), point));
+            }
+            let (dict, later) = builder.finish();
+            // The last value of each key is kept
+            for (k, value) in dict.iter() {
+                let last = keys.iter().rposition(|other| key(other) == *k).unwrap();
+                assert_eq!(*value, BencodexValue::from(last as i64));
+            }
+            (dict.into_keys().collect(), at_once, later)
+        }
+
+        #[test]
+        fn ordered() {
+            let (keys, at_once, later) = build(&["a", "b", "b", "c"]);
+            assert_eq!(keys, vec![key("a"), key("b"), key("c")]);
+            assert_eq!(at_once, vec![2]);
+            assert!(later.is_empty());
+        }
+
+        #[test]
+        fn unordered() {
+            let (keys, at_once, later) = build(&["b", "d", "a", "d", "c", "a", "e", "b"]);
+            assert_eq!(keys, vec![key("a"), key("b"), key("c"), key("d"), key("e")]);
+            assert!(at_once.is_empty());
+            assert_eq!(later, vec![3, 5, 7]);
+        }
+
+        #[test]
+        fn reversed_in_linearithmic_time() {
+            // Inserting each key at the front would shift the whole Vec every time
+            let keys: Vec<_> = (0..200_000).rev().map(|i| format!("{:08}", i)).collect();
+            let mut builder = SortedDictionaryBuilder::default();
+            for k in &keys {
+                builder.push(key(k), BencodexValue::Null, ());
+            }
+            let (dict, duplicates) = builder.finish();
+            assert!(duplicates.is_empty());
+            assert_eq!(dict.len(), keys.len());
+            assert_eq!(dict.first_key_value().unwrap().0, &key("00000000"));
+        }
+
+        #[test]
+        fn extend() {
+            let mut dict = SortedDictionary::from([(key("b"), 1.into()), (key("d"), 2.into())]);
+            dict.extend([
+                (key("c"), 3.into()),
+                (key("a"), 4.into()),
+                (key("b"), 5.into()),
+            ]);
+            assert_eq!(
+                dict.into_vec(),
+                vec![
+                    (key("a"), 4.into()),
+                    (key("b"), 5.into()),
+                    (key("c"), 3.into()),
+                    (key("d"), 2.into()),
+                ]
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::dictionary::SortedDictionary;
 use super::types::*;
 use crate::io::{Error as IoError, Write};
 use crate::prelude::*;
@@ -124,10 +125,25 @@ impl Encode for BigInt {
     }
 }
 
+impl Encode for BencodexNumber {
+    /// ```
+    /// use bencodex::{ BencodexNumber, Encode };
+    ///
+    /// let mut buf = vec![];
+    /// BencodexNumber::from(-42).encode(&mut buf);
+    /// assert_eq!(buf, b"i-42e");
+    /// ```
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<(), IoError> {
+        match self.as_i64() {
+            Some(n) => n.encode(writer),
+            None => self.to_bigint().encode(writer),
+        }
+    }
+}
+
 impl Encode for Vec<BencodexValue<'_>> {
     /// ```
     /// use bencodex::{ Encode, BencodexValue };
-    /// use num_bigint::BigInt;
     ///
     /// let list: Vec<BencodexValue> = vec![0.into(), BencodexValue::Null];
     /// let mut buf = vec![];
@@ -183,6 +199,207 @@ impl Encode for BencodexValue<'_> {
     }
 }
 
+impl BencodexValue<'_> {
+    /// Compute the length in bytes of the encoded value, without encoding it.
+    ///
+    /// ```
+    /// use bencodex::{ BencodexValue, Encode };
+    ///
+    /// let value = BencodexValue::List(vec!["foo".into(), (-42).into()]);
+    /// let mut buf = vec![];
+    /// value.encode(&mut buf);
+    /// assert_eq!(value.encoded_len(), buf.len());
+    /// ```
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            BencodexValue::Binary(x) => binary_encoded_len(x),
+            BencodexValue::Text(x) => text_encoded_len(x),
+            BencodexValue::Dictionary(x) => {
+                2 + x
+                    .iter()
+                    .map(|(key, value)| key.encoded_len() + value.encoded_len())
+                    .sum::<usize>()
+            }
+            BencodexValue::List(x) => 2 + x.iter().map(|el| el.encoded_len()).sum::<usize>(),
+            BencodexValue::Boolean(_) | BencodexValue::Null => 1,
+            BencodexValue::Number(x) => {
+                2 + match x.as_i64() {
+                    Some(n) => itoa::Buffer::new().format(n).len(),
+                    None => x.to_bigint().to_str_radix(10).len(),
+                }
+            }
+        }
+    }
+}
+
+impl<'a> BencodexValue<'a> {
+    /// Encode the value into a new buffer sized by [`encoded_len`](BencodexValue::encoded_len)
+    /// beforehand, so it is never reallocated and no I/O error has to be handled.
+    ///
+    /// ```
+    /// use bencodex::{ BencodexValue, Encode };
+    ///
+    /// let value = BencodexValue::List(vec!["foo".into(), (-42).into()]);
+    /// let mut buf = vec![];
+    /// value.encode(&mut buf);
+    /// assert_eq!(value.encode_to_vec(), buf);
+    /// ```
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut encoder = BufferEncoder {
+            buf: Vec::with_capacity(self.encoded_len()),
+            payloads: None,
+        };
+        encoder.value(self);
+        encoder.buf
+    }
+
+    /// Encode the value with [`write_vectored`](std::io::Write::write_vectored), writing binary
+    /// and text payloads of at least [`VECTORED_MIN_PAYLOAD_LEN`] bytes from the value itself
+    /// instead of copying them. Everything between them is encoded into a buffer first.
+    ///
+    /// ```
+    /// use bencodex::BencodexValue;
+    ///
+    /// let value = BencodexValue::List(vec![vec![0u8; 10000].into(), "foo".into()]);
+    /// let mut buf = vec![];
+    /// value.encode_vectored(&mut buf).unwrap();
+    /// assert_eq!(buf, value.encode_to_vec());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn encode_vectored<W: std::io::Write>(&self, writer: &mut W) -> Result<(), IoError> {
+        use std::io::{ErrorKind, IoSlice};
+
+        let mut encoder = BufferEncoder {
+            buf: Vec::new(),
+            payloads: Some(Vec::new()),
+        };
+        encoder.value(self);
+        let payloads = encoder.payloads.unwrap_or_default();
+
+        let mut slices = Vec::with_capacity(payloads.len() * 2 + 1);
+        let mut start = 0;
+        for &(end, payload) in &payloads {
+            if start < end {
+                slices.push(IoSlice::new(&encoder.buf[start..end]));
+            }
+            slices.push(IoSlice::new(payload));
+            start = end;
+        }
+        if start < encoder.buf.len() {
+            slices.push(IoSlice::new(&encoder.buf[start..]));
+        }
+
+        // Like the unstable `Write::write_all_vectored`
+        let mut slices = &mut slices[..];
+        while !slices.is_empty() {
+            match writer.write_vectored(slices) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Binary and text payloads shorter than this are copied into the buffer by
+/// [`BencodexValue::encode_vectored`], because a separate write costs more than copying them.
+pub const VECTORED_MIN_PAYLOAD_LEN: usize = 4096;
+
+/// Encoder writing straight into a `Vec<u8>`, used by [`BencodexValue::encode_to_vec`] and
+/// [`BencodexValue::encode_vectored`].
+struct BufferEncoder<'v> {
+    buf: Vec<u8>,
+    /// Payloads of at least [`VECTORED_MIN_PAYLOAD_LEN`] bytes with the length of `buf` at which
+    /// each of them is written, or `None` to copy all payloads into `buf`.
+    payloads: Option<Vec<(usize, &'v [u8])>>,
+}
+
+impl<'v> BufferEncoder<'v> {
+    fn value(&mut self, value: &'v BencodexValue<'_>) {
+        match value {
+            BencodexValue::Binary(x) => self.binary(x),
+            BencodexValue::Text(x) => self.text(x),
+            BencodexValue::Dictionary(x) => {
+                self.buf.push(b'd');
+                for (key, value) in x.iter() {
+                    match key {
+                        BencodexKey::Binary(x) => self.binary(x),
+                        BencodexKey::Text(x) => self.text(x),
+                    }
+                    self.value(value);
+                }
+                self.buf.push(b'e');
+            }
+            BencodexValue::List(x) => {
+                self.buf.push(b'l');
+                for el in x {
+                    self.value(el);
+                }
+                self.buf.push(b'e');
+            }
+            BencodexValue::Boolean(x) => self.buf.push(if *x { b't' } else { b'f' }),
+            BencodexValue::Null => self.buf.push(b'n'),
+            BencodexValue::Number(x) => {
+                self.buf.push(b'i');
+                match x.as_i64() {
+                    Some(n) => self
+                        .buf
+                        .extend_from_slice(itoa::Buffer::new().format(n).as_bytes()),
+                    None => self
+                        .buf
+                        .extend_from_slice(x.to_bigint().to_str_radix(10).as_bytes()),
+                }
+                self.buf.push(b'e');
+            }
+        }
+    }
+
+    fn binary(&mut self, data: &'v [u8]) {
+        self.buf
+            .extend_from_slice(itoa::Buffer::new().format(data.len()).as_bytes());
+        self.buf.push(b':');
+        self.payload(data);
+    }
+
+    fn text(&mut self, text: &'v str) {
+        self.buf.push(b'u');
+        self.binary(text.as_bytes());
+    }
+
+    fn payload(&mut self, data: &'v [u8]) {
+        match &mut self.payloads {
+            Some(payloads) if data.len() >= VECTORED_MIN_PAYLOAD_LEN => {
+                payloads.push((self.buf.len(), data));
+            }
+            _ => self.buf.extend_from_slice(data),
+        }
+    }
+}
+
+impl BencodexKey<'_> {
+    /// Compute the length in bytes of the encoded key, without encoding it.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            BencodexKey::Binary(x) => binary_encoded_len(x),
+            BencodexKey::Text(x) => text_encoded_len(x),
+        }
+    }
+}
+
+fn decimal_len(n: usize) -> usize {
+    n.checked_ilog10().map_or(1, |digits| digits as usize + 1)
+}
+
+fn binary_encoded_len(data: &[u8]) -> usize {
+    decimal_len(data.len()) + 1 + data.len()
+}
+
+fn text_encoded_len(text: &str) -> usize {
+    1 + binary_encoded_len(text.as_bytes())
+}
+
 impl Encode for BTreeMap<BencodexKey<'_>, BencodexValue<'_>> {
     /// ```
     /// use bencodex::{ Encode, BencodexKey, BencodexValue };
@@ -197,18 +414,44 @@ impl Encode for BTreeMap<BencodexKey<'_>, BencodexValue<'_>> {
     /// assert_eq!(buf, b"du0:u0:e")
     /// ```
     fn encode<W: Write>(&self, writer: &mut W) -> Result<(), IoError> {
-        writer.write_all(b"d")?;
-        for (key, value) in self {
-            match key {
-                BencodexKey::Binary(x) => encode_binary(writer, x)?,
-                BencodexKey::Text(x) => encode_text(writer, x)?,
-            }
-            value.encode(writer)?;
-        }
-        writer.write_all(b"e")?;
+        encode_dictionary(writer, self)
+    }
+}
 
-        Ok(())
+impl Encode for SortedDictionary<'_> {
+    /// ```
+    /// use bencodex::Encode;
+    /// use bencodex::dictionary::SortedDictionary;
+    ///
+    /// let mut dict = SortedDictionary::new();
+    /// dict.insert("b".into(), "".into());
+    /// dict.insert("a".into(), "".into());
+    ///
+    /// let mut buf = vec![];
+    /// dict.encode(&mut buf);
+    ///
+    /// assert_eq!(buf, b"du1:au0:u1:bu0:e")
+    /// ```
+    fn encode<W: Write>(&self, writer: &mut W) -> Result<(), IoError> {
+        encode_dictionary(writer, self)
+    }
+}
+
+fn encode_dictionary<'b, 'a: 'b, W: Write>(
+    writer: &mut W,
+    entries: impl IntoIterator<Item = (&'b BencodexKey<'a>, &'b BencodexValue<'a>)>,
+) -> Result<(), IoError> {
+    writer.write_all(b"d")?;
+    for (key, value) in entries {
+        match key {
+            BencodexKey::Binary(x) => encode_binary(writer, x)?,
+            BencodexKey::Text(x) => encode_text(writer, x)?,
+        }
+        value.encode(writer)?;
     }
+    writer.write_all(b"e")?;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -245,6 +488,146 @@ mod tests {
         }
     }
 
+    mod encoded_len {
+        use super::super::*;
+        use alloc::vec;
+
+        #[test]
+        fn should_equal_encoded_length() {
+            let mut dict = BencodexDictionary::new();
+            dict.insert(vec![0u8; 10].into(), "\u{AC00}".into());
+            dict.insert("key".into(), BencodexValue::Null);
+            let values: Vec<BencodexValue> = vec![
+                BencodexValue::Null,
+                true.into(),
+                0.into(),
+                (-9).into(),
+                i64::MIN.into(),
+                "123456789012345678901234567890"
+                    .parse::<BigInt>()
+                    .unwrap()
+                    .into(),
+                vec![0u8; 0].into(),
+                vec![0u8; 100].into(),
+                "".into(),
+                BencodexValue::List(vec![BencodexValue::List(vec![]), 1.into()]),
+                BencodexValue::Dictionary(dict),
+            ];
+            for value in values {
+                let mut buf = Vec::new();
+                value.encode(&mut buf).unwrap();
+                assert_eq!(buf.len(), value.encoded_len(), "{:?}", value);
+            }
+        }
+    }
+
+    mod encode_to_vec {
+        use super::super::*;
+        use alloc::vec;
+
+        pub(super) fn values() -> Vec<BencodexValue<'static>> {
+            let mut dict = BencodexDictionary::new();
+            dict.insert(vec![0u8; 5000].into(), "\u{AC00}".repeat(2000).into());
+            dict.insert("key".into(), vec![1u8; 10].into());
+            vec![
+                BencodexValue::Null,
+                false.into(),
+                i64::MIN.into(),
+                "123456789012345678901234567890"
+                    .parse::<BigInt>()
+                    .unwrap()
+                    .into(),
+                vec![0u8; 0].into(),
+                vec![2u8; VECTORED_MIN_PAYLOAD_LEN].into(),
+                "".into(),
+                BencodexValue::List(vec![
+                    vec![3u8; VECTORED_MIN_PAYLOAD_LEN - 1].into(),
+                    vec![4u8; VECTORED_MIN_PAYLOAD_LEN + 1].into(),
+                    BencodexValue::List(vec![]),
+                ]),
+                BencodexValue::Dictionary(dict),
+            ]
+        }
+
+        #[test]
+        fn should_equal_encode() {
+            for value in values() {
+                let mut buf = Vec::new();
+                value.encode(&mut buf).unwrap();
+                let encoded = value.encode_to_vec();
+                assert_eq!(buf, encoded, "{:?}", value);
+                assert_eq!(encoded.len(), encoded.capacity());
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    mod encode_vectored {
+        use super::super::*;
+        use super::encode_to_vec::values;
+        use std::io::IoSlice;
+
+        /// Writes at most `limit` bytes per call, and fails with `Interrupted` every other call.
+        struct ShortWriter {
+            buf: Vec<u8>,
+            limit: usize,
+            interrupt: bool,
+        }
+
+        #[cfg(not(tarpaulin_include))]
+        impl std::io::Write for ShortWriter {
+            fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+                self.write_vectored(&[IoSlice::new(bytes)])
+            }
+
+            fn write_vectored(&mut self, slices: &[IoSlice<'_>]) -> std::io::Result<usize> {
+                self.interrupt = !self.interrupt;
+                if self.interrupt {
+                    return Err(std::io::ErrorKind::Interrupted.into());
+                }
+                let mut written = 0;
+                for slice in slices {
+                    let len = slice.len().min(self.limit - written);
+                    self.buf.extend_from_slice(&slice[..len]);
+                    written += len;
+                }
+                Ok(written)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn should_equal_encode() {
+            for value in values() {
+                let mut buf = Vec::new();
+                value.encode_vectored(&mut buf).unwrap();
+                assert_eq!(buf, value.encode_to_vec(), "{:?}", value);
+
+                for limit in [1, 7, VECTORED_MIN_PAYLOAD_LEN] {
+                    let mut writer = ShortWriter {
+                        buf: Vec::new(),
+                        limit,
+                        interrupt: false,
+                    };
+                    value.encode_vectored(&mut writer).unwrap();
+                    assert_eq!(writer.buf, buf, "limit: {}", limit);
+                }
+            }
+        }
+
+        #[test]
+        fn should_fail_on_write_zero() {
+            let mut slice = &mut [0u8; 3][..];
+            let err = BencodexValue::from("foo")
+                .encode_vectored(&mut slice)
+                .unwrap_err();
+            assert_eq!(std::io::ErrorKind::WriteZero, err.kind());
+        }
+    }
+
     #[cfg(feature = "std")]
     mod encode_std {
         struct ConditionFailWriter {
@@ -0,0 +1,274 @@
+use super::types::*;
+use crate::prelude::*;
+use core::error::Error;
+use core::fmt;
+use core::result::Result;
+
+/// The error type which is returned from [`merge`].
+#[derive(Debug, PartialEq)]
+pub enum MergeError {
+    /// This should be used when both sides have different values on the same key and
+    /// [`MergeStrategy::ErrorOnConflict`] is given.
+    ///
+    /// `path` is the list of keys from the root dictionary to the conflicted value.
+    ConflictError { path: Vec<BencodexKey<'static>> },
+    /// This should be used when the resolver of [`MergeStrategy::Custom`] failed to resolve a conflict.
+    ResolveError {
+        path: Vec<BencodexKey<'static>>,
+        reason: String,
+    },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for MergeError {}
+
+/// The signature of a conflict resolver used by [`MergeStrategy::Custom`].
+///
+/// It receives the path of the conflicted key, the base value and the overlay value, and returns
+/// the value to keep or the reason why it cannot be resolved.
+pub type MergeResolver<'a> = dyn Fn(
+        &[BencodexKey<'a>],
+        BencodexValue<'a>,
+        BencodexValue<'a>,
+    ) -> Result<BencodexValue<'a>, String>
+    + 'a;
+
+/// A strategy to choose the value when both dictionaries given to [`merge`] have the same key.
+///
+/// Dictionaries on both sides are always merged recursively, so the strategy is only consulted
+/// when at least one of the values is not a dictionary and the values are different.
+pub enum MergeStrategy<'a> {
+    /// Keep the value of the overlay.
+    OverlayWins,
+    /// Keep the value of the base.
+    BaseWins,
+    /// Fail with [`MergeError::ConflictError`].
+    ErrorOnConflict,
+    /// Append the overlay list to the base list when both values are lists.
+    /// Otherwise, keep the value of the overlay.
+    ConcatLists,
+    /// Resolve the conflict with the given closure.
+    Custom(Box<MergeResolver<'a>>),
+}
+
+/// Merges `overlay` into `base` recursively with the given [`MergeStrategy`].
+///
+/// # Examples
+/// ```
+/// use bencodex::{ BencodexDictionary, BencodexValue, MergeStrategy, merge };
+///
+/// let mut base = BencodexDictionary::new();
+/// base.insert("name".into(), "base".into());
+/// base.insert("level".into(), 1.into());
+///
+/// let mut overlay = BencodexDictionary::new();
+/// overlay.insert("level".into(), 2.into());
+///
+/// let merged = merge(base, overlay, &MergeStrategy::OverlayWins).unwrap();
+/// assert_eq!(merged.get(&"name".into()), Some(&"base".into()));
+/// assert_eq!(merged.get(&"level".into()), Some(&BencodexValue::from(2)));
+/// ```
+pub fn merge<'a>(
+    base: BencodexDictionary<'a>,
+    overlay: BencodexDictionary<'a>,
+    strategy: &MergeStrategy<'a>,
+) -> Result<BencodexDictionary<'a>, MergeError> {
+    let mut path = Vec::new();
+    merge_dict_impl(base, overlay, strategy, &mut path)
+}
+
+fn merge_dict_impl<'a>(
+    mut base: BencodexDictionary<'a>,
+    overlay: BencodexDictionary<'a>,
+    strategy: &MergeStrategy<'a>,
+    path: &mut Vec<BencodexKey<'a>>,
+) -> Result<BencodexDictionary<'a>, MergeError> {
+    for (key, overlay_value) in overlay {
+        match base.remove(&key) {
+            None => {
+                base.insert(key, overlay_value);
+            }
+            Some(base_value) => {
+                path.push(key);
+                let value = merge_value_impl(base_value, overlay_value, strategy, path)?;
+                let key = path
+                    .pop()
+                    .expect("the key pushed above should be on the path");
+                base.insert(key, value);
+            }
+        }
+    }
+
+    Ok(base)
+}
+
+fn merge_value_impl<'a>(
+    base: BencodexValue<'a>,
+    overlay: BencodexValue<'a>,
+    strategy: &MergeStrategy<'a>,
+    path: &mut Vec<BencodexKey<'a>>,
+) -> Result<BencodexValue<'a>, MergeError> {
+    match (base, overlay) {
+        (BencodexValue::Dictionary(base), BencodexValue::Dictionary(overlay)) => Ok(
+            BencodexValue::Dictionary(merge_dict_impl(base, overlay, strategy, path)?),
+        ),
+        (base, overlay) if base == overlay => Ok(overlay),
+        (base, overlay) => match strategy {
+            MergeStrategy::OverlayWins => Ok(overlay),
+            MergeStrategy::BaseWins => Ok(base),
+            MergeStrategy::ErrorOnConflict => Err(MergeError::ConflictError {
+                path: owned_path(path),
+            }),
+            MergeStrategy::ConcatLists => match (base, overlay) {
+                (BencodexValue::List(mut base), BencodexValue::List(overlay)) => {
+                    base.extend(overlay);
+                    Ok(BencodexValue::List(base))
+                }
+                (_, overlay) => Ok(overlay),
+            },
+            MergeStrategy::Custom(resolve) => {
+                resolve(path, base, overlay).map_err(|reason| MergeError::ResolveError {
+                    path: owned_path(path),
+                    reason,
+                })
+            }
+        },
+    }
+}
+
+fn owned_path(path: &[BencodexKey<'_>]) -> Vec<BencodexKey<'static>> {
+    path.iter().map(|key| key.clone().into_owned()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn dict<'a>(entries: Vec<(&'a str, BencodexValue<'a>)>) -> BencodexDictionary<'a> {
+        entries.into_iter().map(|(k, v)| (k.into(), v)).collect()
+    }
+
+    mod merge {
+        use super::*;
+
+        #[test]
+        fn should_keep_keys_from_both_sides() {
+            let base = dict(vec![("a", 1.into())]);
+            let overlay = dict(vec![("b", 2.into())]);
+            let merged = merge(base, overlay, &MergeStrategy::ErrorOnConflict).unwrap();
+            assert_eq!(merged, dict(vec![("a", 1.into()), ("b", 2.into())]));
+        }
+
+        #[test]
+        fn should_merge_nested_dictionaries() {
+            let base = dict(vec![(
+                "config",
+                BencodexValue::Dictionary(dict(vec![("a", 1.into()), ("b", 1.into())])),
+            )]);
+            let overlay = dict(vec![(
+                "config",
+                BencodexValue::Dictionary(dict(vec![("b", 2.into()), ("c", 3.into())])),
+            )]);
+            let merged = merge(base, overlay, &MergeStrategy::OverlayWins).unwrap();
+            assert_eq!(
+                merged,
+                dict(vec![(
+                    "config",
+                    BencodexValue::Dictionary(dict(vec![
+                        ("a", 1.into()),
+                        ("b", 2.into()),
+                        ("c", 3.into()),
+                    ])),
+                )])
+            );
+        }
+
+        #[test]
+        fn should_follow_overlay_wins() {
+            let base = dict(vec![("a", 1.into())]);
+            let overlay = dict(vec![("a", "x".into())]);
+            let merged = merge(base, overlay, &MergeStrategy::OverlayWins).unwrap();
+            assert_eq!(merged, dict(vec![("a", "x".into())]));
+        }
+
+        #[test]
+        fn should_follow_base_wins() {
+            let base = dict(vec![("a", 1.into())]);
+            let overlay = dict(vec![("a", "x".into())]);
+            let merged = merge(base, overlay, &MergeStrategy::BaseWins).unwrap();
+            assert_eq!(merged, dict(vec![("a", 1.into())]));
+        }
+
+        #[test]
+        fn should_return_conflict_error_with_path() {
+            let base = dict(vec![(
+                "outer",
+                BencodexValue::Dictionary(dict(vec![("inner", 1.into())])),
+            )]);
+            let overlay = dict(vec![(
+                "outer",
+                BencodexValue::Dictionary(dict(vec![("inner", 2.into())])),
+            )]);
+            assert_eq!(
+                MergeError::ConflictError {
+                    path: vec!["outer".to_string().into(), "inner".to_string().into()],
+                },
+                merge(base, overlay, &MergeStrategy::ErrorOnConflict).unwrap_err()
+            );
+        }
+
+        #[test]
+        fn should_not_treat_equal_values_as_conflict() {
+            let base = dict(vec![("a", 1.into())]);
+            let overlay = dict(vec![("a", 1.into())]);
+            let merged = merge(base, overlay, &MergeStrategy::ErrorOnConflict).unwrap();
+            assert_eq!(merged, dict(vec![("a", 1.into())]));
+        }
+
+        #[test]
+        fn should_concat_lists() {
+            let base = dict(vec![("a", vec![1, 2].into()), ("b", 1.into())]);
+            let overlay = dict(vec![("a", vec![3].into()), ("b", 2.into())]);
+            let merged = merge(base, overlay, &MergeStrategy::ConcatLists).unwrap();
+            assert_eq!(
+                merged,
+                dict(vec![("a", vec![1, 2, 3].into()), ("b", 2.into())])
+            );
+        }
+
+        #[test]
+        fn should_use_custom_resolver() {
+            let strategy = MergeStrategy::Custom(Box::new(|path, base, overlay| {
+                match (path.last(), base, overlay) {
+                    (
+                        Some(BencodexKey::Text(key)),
+                        BencodexValue::Number(a),
+                        BencodexValue::Number(b),
+                    ) if key == "sum" => Ok(BencodexValue::Number(a + b)),
+                    _ => Err("unsupported".to_string()),
+                }
+            }));
+
+            let base = dict(vec![("sum", 1.into())]);
+            let overlay = dict(vec![("sum", 2.into())]);
+            let merged = merge(base, overlay, &strategy).unwrap();
+            assert_eq!(merged, dict(vec![("sum", 3.into())]));
+
+            let base = dict(vec![("other", 1.into())]);
+            let overlay = dict(vec![("other", 2.into())]);
+            assert_eq!(
+                MergeError::ResolveError {
+                    path: vec!["other".to_string().into()],
+                    reason: "unsupported".to_string(),
+                },
+                merge(base, overlay, &strategy).unwrap_err()
+            );
+        }
+    }
+}
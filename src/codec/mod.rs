@@ -1,6 +1,13 @@
+pub mod canonical;
 pub mod decode;
+pub mod dictionary;
 pub mod encode;
+pub mod merge;
+pub mod number;
 pub mod types;
 
+#[cfg(feature = "arena")]
+pub mod arena;
+
 #[cfg(feature = "simd")]
 pub mod simd;
@@ -0,0 +1,487 @@
+use crate::prelude::*;
+use core::cmp::Ordering;
+use core::error::Error;
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+use core::str::FromStr;
+use num_bigint::{BigInt, Sign};
+use num_traits::{ToPrimitive, Zero};
+
+/// The number type of [`BencodexValue::Number`](super::types::BencodexValue::Number).
+///
+/// Bencodex numbers are arbitrary-precision integers, but most of them fit in [`i64`]. So this
+/// type keeps them as [`i64`] and only allocates a [`BigInt`] for the numbers out of its range.
+///
+/// ```
+/// use bencodex::BencodexNumber;
+/// use num_bigint::BigInt;
+///
+/// let small = BencodexNumber::from(42);
+/// assert_eq!(small.as_i64(), Some(42));
+///
+/// let big: BencodexNumber = "18446744073709551616".parse().unwrap();
+/// assert_eq!(big.as_i64(), None);
+/// assert_eq!(big.to_bigint(), BigInt::from(u64::MAX) + 1);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BencodexNumber(Repr);
+
+/// The internal representation of [`BencodexNumber`].
+///
+/// `Big` is only used for numbers which don't fit in [`i64`], so each number has exactly one
+/// representation and the derived `PartialEq`, `Eq` and `Hash` are sound.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    Small(i64),
+    Big(BigInt),
+}
+
+impl BencodexNumber {
+    /// Get the number as [`i64`] if it fits, without allocation.
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match &self.0 {
+            Repr::Small(n) => Some(*n),
+            Repr::Big(_) => None,
+        }
+    }
+
+    /// Get the number as [`BigInt`] if it doesn't fit in [`i64`].
+    #[inline]
+    pub fn as_bigint(&self) -> Option<&BigInt> {
+        match &self.0 {
+            Repr::Small(_) => None,
+            Repr::Big(n) => Some(n),
+        }
+    }
+
+    /// Convert the number into [`BigInt`].
+    pub fn to_bigint(&self) -> BigInt {
+        match &self.0 {
+            Repr::Small(n) => BigInt::from(*n),
+            Repr::Big(n) => n.clone(),
+        }
+    }
+
+    /// Convert the number into [`BigInt`], reusing the allocation if it has one.
+    pub fn into_bigint(self) -> BigInt {
+        match self.0 {
+            Repr::Small(n) => BigInt::from(n),
+            Repr::Big(n) => n,
+        }
+    }
+
+    /// Check if the number fits in [`i64`].
+    #[inline]
+    pub fn is_small(&self) -> bool {
+        matches!(self.0, Repr::Small(_))
+    }
+
+    /// Check if the number is zero.
+    #[inline]
+    pub fn is_zero(&self) -> bool {
+        matches!(self.0, Repr::Small(0))
+    }
+
+    /// Check if the number is less than zero.
+    #[inline]
+    pub fn is_negative(&self) -> bool {
+        match &self.0 {
+            Repr::Small(n) => *n < 0,
+            Repr::Big(n) => n.sign() == Sign::Minus,
+        }
+    }
+}
+
+impl Default for BencodexNumber {
+    fn default() -> Self {
+        BencodexNumber(Repr::Small(0))
+    }
+}
+
+impl From<BigInt> for BencodexNumber {
+    fn from(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(small) => BencodexNumber(Repr::Small(small)),
+            None => BencodexNumber(Repr::Big(n)),
+        }
+    }
+}
+
+impl From<&BigInt> for BencodexNumber {
+    fn from(n: &BigInt) -> Self {
+        match n.to_i64() {
+            Some(small) => BencodexNumber(Repr::Small(small)),
+            None => BencodexNumber(Repr::Big(n.clone())),
+        }
+    }
+}
+
+impl From<BencodexNumber> for BigInt {
+    fn from(n: BencodexNumber) -> Self {
+        n.into_bigint()
+    }
+}
+
+impl From<&BencodexNumber> for BigInt {
+    fn from(n: &BencodexNumber) -> Self {
+        n.to_bigint()
+    }
+}
+
+macro_rules! bencodex_number_from_small_impl {
+    ($($x:ty),*) => {
+        $(
+            impl From<$x> for BencodexNumber {
+                #[inline]
+                fn from(val: $x) -> Self {
+                    BencodexNumber(Repr::Small(val.into()))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! bencodex_number_from_large_impl {
+    ($($x:ty),*) => {
+        $(
+            impl From<$x> for BencodexNumber {
+                #[inline]
+                fn from(val: $x) -> Self {
+                    match i64::try_from(val) {
+                        Ok(small) => BencodexNumber(Repr::Small(small)),
+                        Err(_) => BencodexNumber(Repr::Big(BigInt::from(val))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+bencodex_number_from_small_impl!(i8, i16, i32, i64, u8, u16, u32);
+bencodex_number_from_large_impl!(isize, i128, u64, usize, u128);
+
+/// The error type which is returned when a [`BencodexNumber`] doesn't fit in the target type.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TryFromNumberError;
+
+impl fmt::Display for TryFromNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "out of range number conversion attempted")
+    }
+}
+
+impl Error for TryFromNumberError {}
+
+macro_rules! bencodex_number_try_into_impl {
+    ($($x:ty => $to:ident),*) => {
+        $(
+            impl TryFrom<&BencodexNumber> for $x {
+                type Error = TryFromNumberError;
+
+                #[inline]
+                fn try_from(val: &BencodexNumber) -> Result<Self, Self::Error> {
+                    match &val.0 {
+                        Repr::Small(n) => <$x>::try_from(*n).map_err(|_| TryFromNumberError),
+                        Repr::Big(n) => n.$to().ok_or(TryFromNumberError),
+                    }
+                }
+            }
+
+            impl TryFrom<BencodexNumber> for $x {
+                type Error = TryFromNumberError;
+
+                #[inline]
+                fn try_from(val: BencodexNumber) -> Result<Self, Self::Error> {
+                    <$x>::try_from(&val)
+                }
+            }
+        )*
+    };
+}
+
+bencodex_number_try_into_impl!(
+    i8 => to_i8,
+    i16 => to_i16,
+    i32 => to_i32,
+    i64 => to_i64,
+    i128 => to_i128,
+    isize => to_isize,
+    u8 => to_u8,
+    u16 => to_u16,
+    u32 => to_u32,
+    u64 => to_u64,
+    u128 => to_u128,
+    usize => to_usize
+);
+
+/// The error type which is returned from parsing a [`BencodexNumber`] with [`FromStr`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ParseNumberError;
+
+impl fmt::Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid number literal")
+    }
+}
+
+impl Error for ParseNumberError {}
+
+impl FromStr for BencodexNumber {
+    type Err = ParseNumberError;
+
+    /// Parse a decimal integer with an optional leading `-`, e.g., `-123`.
+    ///
+    /// Unlike [`BigInt`], a leading `+` and `_` separators are not allowed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('-').unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseNumberError);
+        }
+
+        // The digits are validated above, so parsing as i64 only fails on overflow.
+        match s.parse::<i64>() {
+            Ok(n) => Ok(Self::from(n)),
+            Err(_) => BigInt::from_str(s)
+                .map(Self::from)
+                .map_err(|_| ParseNumberError),
+        }
+    }
+}
+
+impl fmt::Display for BencodexNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Small(n) => fmt::Display::fmt(n, f),
+            Repr::Big(n) => fmt::Display::fmt(n, f),
+        }
+    }
+}
+
+impl fmt::Debug for BencodexNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "BencodexNumber({})", self)
+    }
+}
+
+impl Ord for BencodexNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.0, &other.0) {
+            (Repr::Small(a), Repr::Small(b)) => a.cmp(b),
+            (Repr::Big(a), Repr::Big(b)) => a.cmp(b),
+            // A big number is always out of the i64 range, so its sign decides the order.
+            (Repr::Small(_), Repr::Big(b)) => {
+                if b.sign() == Sign::Minus {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                }
+            }
+            (Repr::Big(a), Repr::Small(_)) => {
+                if a.sign() == Sign::Minus {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            }
+        }
+    }
+}
+
+impl PartialOrd for BencodexNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq<i64> for BencodexNumber {
+    fn eq(&self, other: &i64) -> bool {
+        self.as_i64() == Some(*other)
+    }
+}
+
+macro_rules! bencodex_number_binop_impl {
+    ($trait:ident, $method:ident, $checked:ident) => {
+        impl $trait for BencodexNumber {
+            type Output = BencodexNumber;
+
+            fn $method(self, rhs: BencodexNumber) -> BencodexNumber {
+                match (&self.0, &rhs.0) {
+                    (Repr::Small(a), Repr::Small(b)) => match a.$checked(*b) {
+                        Some(n) => BencodexNumber(Repr::Small(n)),
+                        None => BigInt::from(*a).$method(BigInt::from(*b)).into(),
+                    },
+                    _ => self.into_bigint().$method(rhs.into_bigint()).into(),
+                }
+            }
+        }
+
+        impl $trait for &BencodexNumber {
+            type Output = BencodexNumber;
+
+            fn $method(self, rhs: &BencodexNumber) -> BencodexNumber {
+                self.clone().$method(rhs.clone())
+            }
+        }
+    };
+}
+
+bencodex_number_binop_impl!(Add, add, checked_add);
+bencodex_number_binop_impl!(Sub, sub, checked_sub);
+bencodex_number_binop_impl!(Mul, mul, checked_mul);
+
+impl Neg for BencodexNumber {
+    type Output = BencodexNumber;
+
+    fn neg(self) -> BencodexNumber {
+        match self.0 {
+            Repr::Small(n) => match n.checked_neg() {
+                Some(n) => BencodexNumber(Repr::Small(n)),
+                None => (-BigInt::from(n)).into(),
+            },
+            Repr::Big(n) => (-n).into(),
+        }
+    }
+}
+
+impl Zero for BencodexNumber {
+    fn zero() -> Self {
+        Self::default()
+    }
+
+    fn is_zero(&self) -> bool {
+        BencodexNumber::is_zero(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    mod from_impl {
+        use super::*;
+
+        #[test]
+        fn should_keep_small_numbers_without_bigint() {
+            assert!(BencodexNumber::from(i64::MAX).is_small());
+            assert!(BencodexNumber::from(i64::MIN).is_small());
+            assert!(BencodexNumber::from(i64::MAX as u64).is_small());
+            assert!(BencodexNumber::from(BigInt::from(42)).is_small());
+        }
+
+        #[test]
+        fn should_use_bigint_for_large_numbers() {
+            assert!(!BencodexNumber::from(u64::MAX).is_small());
+            assert!(!BencodexNumber::from(i128::MIN).is_small());
+            assert_eq!(
+                BencodexNumber::from(u64::MAX).to_bigint(),
+                BigInt::from(u64::MAX)
+            );
+        }
+    }
+
+    mod try_from_impl {
+        use super::*;
+
+        #[test]
+        fn should_convert_in_range() {
+            assert_eq!(u8::try_from(&BencodexNumber::from(255)), Ok(255u8));
+            assert_eq!(u64::try_from(BencodexNumber::from(u64::MAX)), Ok(u64::MAX));
+            assert_eq!(
+                i128::try_from(BencodexNumber::from(i128::MIN)),
+                Ok(i128::MIN)
+            );
+        }
+
+        #[test]
+        fn should_return_error_out_of_range() {
+            assert_eq!(
+                u8::try_from(&BencodexNumber::from(256)),
+                Err(TryFromNumberError)
+            );
+            assert_eq!(
+                usize::try_from(&BencodexNumber::from(-1)),
+                Err(TryFromNumberError)
+            );
+            assert_eq!(
+                i64::try_from(BencodexNumber::from(u64::MAX)),
+                Err(TryFromNumberError)
+            );
+        }
+    }
+
+    mod from_str_impl {
+        use super::*;
+
+        #[test]
+        fn should_parse() {
+            assert_eq!("0".parse(), Ok(BencodexNumber::from(0)));
+            assert_eq!("-845".parse(), Ok(BencodexNumber::from(-845)));
+            assert_eq!(
+                "-9223372036854775808".parse(),
+                Ok(BencodexNumber::from(i64::MIN))
+            );
+            assert_eq!(
+                "9223372036854775808".parse(),
+                Ok(BencodexNumber::from(i64::MAX as u64 + 1))
+            );
+        }
+
+        #[test]
+        fn should_return_error_with_invalid_literal() {
+            for s in ["", "-", "+1", "1_000", "1a", "--1", " 1"] {
+                assert_eq!(s.parse::<BencodexNumber>(), Err(ParseNumberError));
+            }
+        }
+    }
+
+    mod ord_impl {
+        use super::*;
+
+        #[test]
+        fn should_order_numerically() {
+            let mut numbers = vec![
+                BencodexNumber::from(u64::MAX),
+                BencodexNumber::from(0),
+                BencodexNumber::from(i128::MIN),
+                BencodexNumber::from(-1),
+                BencodexNumber::from(i64::MAX),
+            ];
+            numbers.sort();
+            assert_eq!(
+                numbers,
+                vec![
+                    BencodexNumber::from(i128::MIN),
+                    BencodexNumber::from(-1),
+                    BencodexNumber::from(0),
+                    BencodexNumber::from(i64::MAX),
+                    BencodexNumber::from(u64::MAX),
+                ]
+            );
+        }
+    }
+
+    mod ops_impl {
+        use super::*;
+
+        #[test]
+        fn should_promote_on_overflow() {
+            let sum = BencodexNumber::from(i64::MAX) + BencodexNumber::from(1);
+            assert_eq!(sum, BencodexNumber::from(i64::MAX as u64 + 1));
+
+            let back = sum - BencodexNumber::from(1);
+            assert!(back.is_small());
+            assert_eq!(back, i64::MAX);
+
+            assert_eq!(
+                -BencodexNumber::from(i64::MIN),
+                BencodexNumber::from(i64::MAX as u64 + 1)
+            );
+            assert_eq!(
+                BencodexNumber::from(-3) * BencodexNumber::from(4),
+                BencodexNumber::from(-12)
+            );
+        }
+    }
+}
@@ -4,6 +4,14 @@
 use core::arch::aarch64::*;
 
 use super::SimdBackend;
+#[cfg(target_arch = "aarch64")]
+use super::candidate_mask;
+#[cfg(target_arch = "aarch64")]
+use crate::codec::simd::stage1::scan_structural_with;
+#[cfg(target_arch = "aarch64")]
+use crate::codec::simd::structural::{Offset, StructuralIndex};
+#[cfg(target_arch = "aarch64")]
+use crate::codec::simd::utf8::validate_utf8_with;
 
 /// NEON backend (128-bit vectors)
 pub struct Neon;
@@ -32,11 +40,56 @@ impl SimdBackend for Neon {
     }
 
     #[inline]
-    unsafe fn movemask_epi8(a: Self::Vector) -> u32 {
+    unsafe fn movemask_epi8(a: Self::Vector) -> u64 {
         // NEON doesn't have a direct movemask equivalent
         // We need to emulate it by extracting the high bit of each byte
         unsafe { neon_movemask(a) }
     }
+
+    #[inline]
+    unsafe fn splat(b: u8) -> Self::Vector {
+        unsafe { vdupq_n_u8(b) }
+    }
+
+    #[inline]
+    unsafe fn and(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { vandq_u8(a, b) }
+    }
+
+    #[inline]
+    unsafe fn xor(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { veorq_u8(a, b) }
+    }
+
+    #[inline]
+    unsafe fn saturating_sub(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { vqsubq_u8(a, b) }
+    }
+
+    #[inline]
+    unsafe fn shr4(a: Self::Vector) -> Self::Vector {
+        unsafe { vshrq_n_u8::<4>(a) }
+    }
+
+    #[inline]
+    unsafe fn lookup16(table: &[u8; 16], indices: Self::Vector) -> Self::Vector {
+        unsafe { vqtbl1q_u8(vld1q_u8(table.as_ptr()), indices) }
+    }
+
+    #[inline]
+    unsafe fn prev1(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { vextq_u8::<15>(previous, current) }
+    }
+
+    #[inline]
+    unsafe fn prev2(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { vextq_u8::<14>(previous, current) }
+    }
+
+    #[inline]
+    unsafe fn prev3(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { vextq_u8::<13>(previous, current) }
+    }
 }
 
 /// Emulate x86 movemask for NEON
@@ -45,7 +98,7 @@ impl SimdBackend for Neon {
 /// and packs them into a 16-bit result.
 #[cfg(target_arch = "aarch64")]
 #[inline]
-unsafe fn neon_movemask(v: uint8x16_t) -> u32 {
+unsafe fn neon_movemask(v: uint8x16_t) -> u64 {
     unsafe {
         // Shift each byte right by 7 to get the MSB in bit 0 (0x01 for match, 0x00 for no match)
         let shifted = vshrq_n_u8::<7>(v);
@@ -63,8 +116,8 @@ unsafe fn neon_movemask(v: uint8x16_t) -> u32 {
         let high = vget_high_u8(masked);
 
         // Sum each 8-byte half
-        let low_sum = vaddv_u8(low) as u32;
-        let high_sum = vaddv_u8(high) as u32;
+        let low_sum = vaddv_u8(low) as u64;
+        let high_sum = vaddv_u8(high) as u64;
 
         low_sum | (high_sum << 8)
     }
@@ -75,90 +128,20 @@ unsafe fn neon_movemask(v: uint8x16_t) -> u32 {
 /// # Safety
 /// - Only call on AArch64 platforms (NEON is always available on AArch64)
 #[cfg(target_arch = "aarch64")]
-pub unsafe fn scan_structural_neon(input: &[u8], indices: &mut Vec<u32>) {
-    unsafe {
-        scan_structural_generic::<Neon>(input, indices);
-    }
+pub unsafe fn scan_structural_neon<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    scan_structural_with(
+        input,
+        index,
+        |block| unsafe { candidate_mask::<Neon>(block) },
+        |text| unsafe { validate_utf8_with::<Neon>(text) },
+    )
 }
 
-/// Generic structural scanner using any SimdBackend
+/// Check if `input` is valid UTF-8 using NEON
+///
+/// # Safety
+/// - Only call on AArch64 platforms (NEON is always available on AArch64)
 #[cfg(target_arch = "aarch64")]
-#[inline]
-unsafe fn scan_structural_generic<B: SimdBackend>(input: &[u8], indices: &mut Vec<u32>) {
-    let len = input.len();
-    let mut pos = 0;
-
-    // Process full SIMD chunks
-    while pos + B::LANE_WIDTH <= len {
-        unsafe {
-            let chunk = B::load_unaligned(input.as_ptr().add(pos));
-
-            // Check for structural characters: n, t, f, i, l, d, u, :, e, 0-9
-            let mask_n = B::cmpeq_epi8(chunk, b'n');
-            let mask_t = B::cmpeq_epi8(chunk, b't');
-            let mask_f = B::cmpeq_epi8(chunk, b'f');
-            let mask_i = B::cmpeq_epi8(chunk, b'i');
-            let mask_l = B::cmpeq_epi8(chunk, b'l');
-            let mask_d = B::cmpeq_epi8(chunk, b'd');
-            let mask_u = B::cmpeq_epi8(chunk, b'u');
-            let mask_colon = B::cmpeq_epi8(chunk, b':');
-            let mask_e = B::cmpeq_epi8(chunk, b'e');
-
-            // Digits 0-9
-            let mask_0 = B::cmpeq_epi8(chunk, b'0');
-            let mask_1 = B::cmpeq_epi8(chunk, b'1');
-            let mask_2 = B::cmpeq_epi8(chunk, b'2');
-            let mask_3 = B::cmpeq_epi8(chunk, b'3');
-            let mask_4 = B::cmpeq_epi8(chunk, b'4');
-            let mask_5 = B::cmpeq_epi8(chunk, b'5');
-            let mask_6 = B::cmpeq_epi8(chunk, b'6');
-            let mask_7 = B::cmpeq_epi8(chunk, b'7');
-            let mask_8 = B::cmpeq_epi8(chunk, b'8');
-            let mask_9 = B::cmpeq_epi8(chunk, b'9');
-
-            // Combine all masks
-            let combined = B::or(
-                B::or(
-                    B::or(B::or(mask_n, mask_t), B::or(mask_f, mask_i)),
-                    B::or(B::or(mask_l, mask_d), B::or(mask_u, mask_colon)),
-                ),
-                B::or(
-                    B::or(
-                        B::or(B::or(mask_e, mask_0), B::or(mask_1, mask_2)),
-                        B::or(B::or(mask_3, mask_4), B::or(mask_5, mask_6)),
-                    ),
-                    B::or(B::or(mask_7, mask_8), mask_9),
-                ),
-            );
-
-            let mut bits = B::movemask_epi8(combined);
-
-            // Extract positions from bitmask
-            while bits != 0 {
-                let bit_pos = bits.trailing_zeros();
-                indices.push((pos + bit_pos as usize) as u32);
-                bits &= bits - 1; // Clear lowest set bit
-            }
-        }
-
-        pos += B::LANE_WIDTH;
-    }
-
-    // Process remaining bytes with scalar code
-    while pos < len {
-        let byte = input[pos];
-        if is_structural_char(byte) {
-            indices.push(pos as u32);
-        }
-        pos += 1;
-    }
-}
-
-/// Check if a byte is a structural character
-#[inline]
-fn is_structural_char(b: u8) -> bool {
-    matches!(
-        b,
-        b'n' | b't' | b'f' | b'i' | b'l' | b'd' | b'u' | b':' | b'e' | b'0'..=b'9'
-    )
+pub unsafe fn validate_utf8_neon(input: &[u8]) -> bool {
+    unsafe { validate_utf8_with::<Neon>(input) }
 }
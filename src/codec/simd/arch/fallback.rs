@@ -1,20 +1,34 @@
 //! Scalar fallback implementation for platforms without SIMD support.
 
+use crate::codec::simd::stage1::scan_structural_with;
+use crate::codec::simd::structural::{Offset, StructuralIndex};
+
 /// Scan for structural characters using scalar code
 ///
-/// This is used as a fallback when SIMD is not available or for
-/// processing remainder bytes that don't fill a full SIMD lane.
-pub fn scan_structural_scalar(input: &[u8], indices: &mut Vec<u32>) {
-    for (pos, &byte) in input.iter().enumerate() {
-        if is_structural_char(byte) {
-            indices.push(pos as u32);
-        }
-    }
+/// This is used as a fallback when SIMD is not available.
+pub fn scan_structural_scalar<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    scan_structural_with(input, index, candidate_mask_scalar, validate_utf8_scalar);
+}
+
+/// Check if `input` is valid UTF-8 using scalar code
+#[inline]
+pub fn validate_utf8_scalar(input: &[u8]) -> bool {
+    core::str::from_utf8(input).is_ok()
 }
 
-/// Check if a byte is a structural character in Bencodex.
+/// Find the bytes in a 64-byte block which may be structural, like
+/// [`candidate_mask`](super::candidate_mask) does with SIMD.
+#[inline]
+pub fn candidate_mask_scalar(block: &[u8; 64]) -> u64 {
+    block.iter().enumerate().fold(0, |mask, (offset, &byte)| {
+        mask | ((is_structural_char(byte) as u64) << offset)
+    })
+}
+
+/// Check if a byte may be a structural character in Bencodex.
 ///
-/// Structural characters are:
+/// The same bytes also appear inside binary and text payloads, so they are only candidates until
+/// [`scan_structural_with`] excludes the payloads. Structural characters are:
 /// - `n`: null
 /// - `t`: true
 /// - `f`: false
@@ -24,11 +38,13 @@ pub fn scan_structural_scalar(input: &[u8], indices: &mut Vec<u32>) {
 /// - `u`: unicode string prefix
 /// - `:`: separator (after length in strings)
 /// - `e`: end marker (for integers, lists, dictionaries)
-/// - `0-9`: digits (string length prefix or integer digits)
+///
+/// Digits are structural too, but they are not candidates because a binary always starts right
+/// after the previous token.
 #[inline]
 pub fn is_structural_char(b: u8) -> bool {
     matches!(
         b,
-        b'n' | b't' | b'f' | b'i' | b'l' | b'd' | b'u' | b':' | b'e' | b'0'..=b'9'
+        b'n' | b't' | b'f' | b'i' | b'l' | b'd' | b'u' | b':' | b'e'
     )
 }
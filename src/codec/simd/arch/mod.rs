@@ -6,14 +6,17 @@ pub mod x86_64;
 #[cfg(target_arch = "aarch64")]
 pub mod aarch64;
 
+#[cfg(target_arch = "wasm32")]
+pub mod wasm32;
+
 pub mod fallback;
 
 /// Trait defining SIMD backend operations.
 ///
-/// Each architecture (SSE4.2, AVX2, NEON) implements this trait to provide
-/// consistent vector operations for structural character detection.
+/// Each architecture (SSE4.2, AVX2, AVX-512BW, NEON, SIMD128) implements this trait to provide
+/// consistent vector operations for structural character detection and UTF-8 validation.
 pub trait SimdBackend {
-    /// Width of vector lane in bytes (16 for SSE/NEON, 32 for AVX2)
+    /// Width of vector lane in bytes (16 for SSE/NEON/SIMD128, 32 for AVX2, 64 for AVX-512)
     const LANE_WIDTH: usize;
 
     /// Vector type for this backend
@@ -43,5 +46,102 @@ pub trait SimdBackend {
     ///
     /// # Safety
     /// - Requires the appropriate SIMD feature to be available
-    unsafe fn movemask_epi8(a: Self::Vector) -> u32;
+    unsafe fn movemask_epi8(a: Self::Vector) -> u64;
+
+    /// Broadcast byte `b` to every byte of a vector
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn splat(b: u8) -> Self::Vector;
+
+    /// Bitwise AND of two vectors
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn and(a: Self::Vector, b: Self::Vector) -> Self::Vector;
+
+    /// Bitwise XOR of two vectors
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn xor(a: Self::Vector, b: Self::Vector) -> Self::Vector;
+
+    /// Subtract each unsigned byte of `b` from `a`, saturating at 0
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn saturating_sub(a: Self::Vector, b: Self::Vector) -> Self::Vector;
+
+    /// Shift each byte right by 4 bits, leaving its high nibble
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn shr4(a: Self::Vector) -> Self::Vector;
+
+    /// Replace each byte of `indices`, which must be less than 16, with `table[index]`
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn lookup16(table: &[u8; 16], indices: Self::Vector) -> Self::Vector;
+
+    /// Shift `current` by one byte towards the end, filling with the last byte of `previous`,
+    /// i.e., the byte before each byte of `current`
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn prev1(current: Self::Vector, previous: Self::Vector) -> Self::Vector;
+
+    /// Like [`prev1`](SimdBackend::prev1), but the second byte before each byte
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn prev2(current: Self::Vector, previous: Self::Vector) -> Self::Vector;
+
+    /// Like [`prev1`](SimdBackend::prev1), but the third byte before each byte
+    ///
+    /// # Safety
+    /// - Requires the appropriate SIMD feature to be available
+    unsafe fn prev3(current: Self::Vector, previous: Self::Vector) -> Self::Vector;
+}
+
+/// Find the bytes in a 64-byte block which may be structural, i.e., the bytes matching
+/// [`is_structural_char`](fallback::is_structural_char), and return them as a bitmask whose
+/// bit N is set for byte N.
+///
+/// # Safety
+/// - Requires the backend's SIMD features to be available
+#[inline(always)]
+pub unsafe fn candidate_mask<B: SimdBackend>(block: &[u8; 64]) -> u64 {
+    let mut mask = 0u64;
+    let mut offset = 0;
+    while offset < 64 {
+        // SAFETY: offset + LANE_WIDTH <= 64, and the caller guarantees the SIMD features.
+        unsafe {
+            let chunk = B::load_unaligned(block.as_ptr().add(offset));
+
+            // Check for structural characters: n, t, f, i, l, d, u, :, e
+            let mask_n = B::cmpeq_epi8(chunk, b'n');
+            let mask_t = B::cmpeq_epi8(chunk, b't');
+            let mask_f = B::cmpeq_epi8(chunk, b'f');
+            let mask_i = B::cmpeq_epi8(chunk, b'i');
+            let mask_l = B::cmpeq_epi8(chunk, b'l');
+            let mask_d = B::cmpeq_epi8(chunk, b'd');
+            let mask_u = B::cmpeq_epi8(chunk, b'u');
+            let mask_colon = B::cmpeq_epi8(chunk, b':');
+            let mask_e = B::cmpeq_epi8(chunk, b'e');
+
+            // Combine all masks
+            let combined = B::or(
+                B::or(
+                    B::or(B::or(mask_n, mask_t), B::or(mask_f, mask_i)),
+                    B::or(B::or(mask_l, mask_d), B::or(mask_u, mask_colon)),
+                ),
+                mask_e,
+            );
+
+            mask |= B::movemask_epi8(combined) << offset;
+        }
+        offset += B::LANE_WIDTH;
+    }
+    mask
 }
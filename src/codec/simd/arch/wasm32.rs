@@ -0,0 +1,132 @@
+//! WebAssembly SIMD backend (SIMD128)
+
+#![allow(unused_unsafe)]
+
+use core::arch::wasm32::*;
+
+use super::{SimdBackend, candidate_mask};
+use crate::codec::simd::stage1::scan_structural_with;
+use crate::codec::simd::structural::{Offset, StructuralIndex};
+use crate::codec::simd::utf8::validate_utf8_with;
+
+/// SIMD128 backend (128-bit vectors)
+pub struct Simd128;
+
+impl SimdBackend for Simd128 {
+    const LANE_WIDTH: usize = 16;
+    type Vector = v128;
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn load_unaligned(ptr: *const u8) -> Self::Vector {
+        unsafe { v128_load(ptr as *const v128) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn cmpeq_epi8(a: Self::Vector, b: u8) -> Self::Vector {
+        unsafe { u8x16_eq(a, u8x16_splat(b)) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn or(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { v128_or(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn movemask_epi8(a: Self::Vector) -> u64 {
+        unsafe { u8x16_bitmask(a) as u64 }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn splat(b: u8) -> Self::Vector {
+        unsafe { u8x16_splat(b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn and(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { v128_and(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn xor(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { v128_xor(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn saturating_sub(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { u8x16_sub_sat(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn shr4(a: Self::Vector) -> Self::Vector {
+        unsafe { u8x16_shr(a, 4) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn lookup16(table: &[u8; 16], indices: Self::Vector) -> Self::Vector {
+        unsafe { u8x16_swizzle(v128_load(table.as_ptr() as *const v128), indices) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn prev1(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe {
+            u8x16_shuffle::<15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30>(
+                previous, current,
+            )
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn prev2(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe {
+            u8x16_shuffle::<14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29>(
+                previous, current,
+            )
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "simd128")]
+    unsafe fn prev3(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe {
+            u8x16_shuffle::<13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28>(
+                previous, current,
+            )
+        }
+    }
+}
+
+/// Scan for structural characters using SIMD128
+///
+/// # Safety
+/// - Requires the `simd128` target feature. WebAssembly has no runtime detection, so the module
+///   fails to validate on a runtime without SIMD128 instead.
+#[target_feature(enable = "simd128")]
+pub unsafe fn scan_structural_simd128<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    scan_structural_with(
+        input,
+        index,
+        |block| unsafe { candidate_mask::<Simd128>(block) },
+        |text| unsafe { validate_utf8_with::<Simd128>(text) },
+    )
+}
+
+/// Check if `input` is valid UTF-8 using SIMD128
+///
+/// # Safety
+/// - Requires the `simd128` target feature, like [`scan_structural_simd128`]
+#[target_feature(enable = "simd128")]
+pub unsafe fn validate_utf8_simd128(input: &[u8]) -> bool {
+    unsafe { validate_utf8_with::<Simd128>(input) }
+}
@@ -1,11 +1,14 @@
-//! x86_64 SIMD backends (SSE4.2 and AVX2)
+//! x86_64 SIMD backends (SSE4.2, AVX2 and AVX-512BW)
 
 #![allow(unused_unsafe)]
 
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::*;
 
-use super::SimdBackend;
+use super::{SimdBackend, candidate_mask};
+use crate::codec::simd::stage1::scan_structural_with;
+use crate::codec::simd::structural::{Offset, StructuralIndex};
+use crate::codec::simd::utf8::validate_utf8_with;
 
 /// SSE4.2 backend (128-bit vectors)
 pub struct Sse42;
@@ -37,8 +40,62 @@ impl SimdBackend for Sse42 {
 
     #[inline]
     #[target_feature(enable = "sse4.2")]
-    unsafe fn movemask_epi8(a: Self::Vector) -> u32 {
-        unsafe { _mm_movemask_epi8(a) as u32 }
+    unsafe fn movemask_epi8(a: Self::Vector) -> u64 {
+        unsafe { _mm_movemask_epi8(a) as u16 as u64 }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn splat(b: u8) -> Self::Vector {
+        unsafe { _mm_set1_epi8(b as i8) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn and(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm_and_si128(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn xor(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm_xor_si128(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn saturating_sub(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm_subs_epu8(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn shr4(a: Self::Vector) -> Self::Vector {
+        unsafe { _mm_and_si128(_mm_srli_epi16::<4>(a), _mm_set1_epi8(0x0F)) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn lookup16(table: &[u8; 16], indices: Self::Vector) -> Self::Vector {
+        unsafe { _mm_shuffle_epi8(_mm_loadu_si128(table.as_ptr() as *const __m128i), indices) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn prev1(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { _mm_alignr_epi8::<15>(current, previous) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn prev2(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { _mm_alignr_epi8::<14>(current, previous) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn prev3(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { _mm_alignr_epi8::<13>(current, previous) }
     }
 }
 
@@ -72,8 +129,180 @@ impl SimdBackend for Avx2 {
 
     #[inline]
     #[target_feature(enable = "avx2")]
-    unsafe fn movemask_epi8(a: Self::Vector) -> u32 {
-        unsafe { _mm256_movemask_epi8(a) as u32 }
+    unsafe fn movemask_epi8(a: Self::Vector) -> u64 {
+        unsafe { _mm256_movemask_epi8(a) as u32 as u64 }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn splat(b: u8) -> Self::Vector {
+        unsafe { _mm256_set1_epi8(b as i8) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn and(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm256_and_si256(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn xor(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm256_xor_si256(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn saturating_sub(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm256_subs_epu8(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn shr4(a: Self::Vector) -> Self::Vector {
+        unsafe { _mm256_and_si256(_mm256_srli_epi16::<4>(a), _mm256_set1_epi8(0x0F)) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn lookup16(table: &[u8; 16], indices: Self::Vector) -> Self::Vector {
+        // vpshufb looks up each 128-bit lane separately, so repeat the table in both lanes.
+        unsafe {
+            let table =
+                _mm256_broadcastsi128_si256(_mm_loadu_si128(table.as_ptr() as *const __m128i));
+            _mm256_shuffle_epi8(table, indices)
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn prev1(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        // vpalignr also works on each 128-bit lane, so pair each lane with the lane before it.
+        unsafe {
+            _mm256_alignr_epi8::<15>(
+                current,
+                _mm256_permute2x128_si256::<0x21>(previous, current),
+            )
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn prev2(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe {
+            _mm256_alignr_epi8::<14>(
+                current,
+                _mm256_permute2x128_si256::<0x21>(previous, current),
+            )
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn prev3(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe {
+            _mm256_alignr_epi8::<13>(
+                current,
+                _mm256_permute2x128_si256::<0x21>(previous, current),
+            )
+        }
+    }
+}
+
+/// AVX-512BW backend (512-bit vectors)
+///
+/// AVX-512 compares into mask registers instead of vectors, so [`SimdBackend::cmpeq_epi8`]
+/// expands the mask back to a vector to keep the same interface as the other backends.
+pub struct Avx512;
+
+impl SimdBackend for Avx512 {
+    const LANE_WIDTH: usize = 64;
+    type Vector = __m512i;
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn load_unaligned(ptr: *const u8) -> Self::Vector {
+        unsafe { _mm512_loadu_si512(ptr as *const __m512i) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn cmpeq_epi8(a: Self::Vector, b: u8) -> Self::Vector {
+        unsafe {
+            let broadcast = _mm512_set1_epi8(b as i8);
+            _mm512_movm_epi8(_mm512_cmpeq_epi8_mask(a, broadcast))
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn or(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm512_or_si512(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn movemask_epi8(a: Self::Vector) -> u64 {
+        unsafe { _mm512_movepi8_mask(a) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn splat(b: u8) -> Self::Vector {
+        unsafe { _mm512_set1_epi8(b as i8) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn and(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm512_and_si512(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn xor(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm512_xor_si512(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn saturating_sub(a: Self::Vector, b: Self::Vector) -> Self::Vector {
+        unsafe { _mm512_subs_epu8(a, b) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn shr4(a: Self::Vector) -> Self::Vector {
+        unsafe { _mm512_and_si512(_mm512_srli_epi16::<4>(a), _mm512_set1_epi8(0x0F)) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn lookup16(table: &[u8; 16], indices: Self::Vector) -> Self::Vector {
+        // vpshufb looks up each 128-bit lane separately, so repeat the table in every lane.
+        unsafe {
+            let table = _mm512_broadcast_i32x4(_mm_loadu_si128(table.as_ptr() as *const __m128i));
+            _mm512_shuffle_epi8(table, indices)
+        }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn prev1(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        // vpalignr also works on each 128-bit lane, so pair each lane with the lane before it.
+        unsafe { _mm512_alignr_epi8::<15>(current, _mm512_alignr_epi64::<6>(current, previous)) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn prev2(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { _mm512_alignr_epi8::<14>(current, _mm512_alignr_epi64::<6>(current, previous)) }
+    }
+
+    #[inline]
+    #[target_feature(enable = "avx512bw")]
+    unsafe fn prev3(current: Self::Vector, previous: Self::Vector) -> Self::Vector {
+        unsafe { _mm512_alignr_epi8::<13>(current, _mm512_alignr_epi64::<6>(current, previous)) }
     }
 }
 
@@ -82,8 +311,13 @@ impl SimdBackend for Avx2 {
 /// # Safety
 /// - Requires SSE4.2 support (caller must verify with `is_x86_feature_detected!("sse4.2")`)
 #[target_feature(enable = "sse4.2")]
-pub unsafe fn scan_structural_sse42(input: &[u8], indices: &mut Vec<u32>) {
-    unsafe { scan_structural_generic::<Sse42>(input, indices) }
+pub unsafe fn scan_structural_sse42<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    scan_structural_with(
+        input,
+        index,
+        |block| unsafe { candidate_mask::<Sse42>(block) },
+        |text| unsafe { validate_utf8_with::<Sse42>(text) },
+    )
 }
 
 /// Scan for structural characters using AVX2
@@ -91,94 +325,52 @@ pub unsafe fn scan_structural_sse42(input: &[u8], indices: &mut Vec<u32>) {
 /// # Safety
 /// - Requires AVX2 support (caller must verify with `is_x86_feature_detected!("avx2")`)
 #[target_feature(enable = "avx2")]
-pub unsafe fn scan_structural_avx2(input: &[u8], indices: &mut Vec<u32>) {
-    unsafe { scan_structural_generic::<Avx2>(input, indices) }
+pub unsafe fn scan_structural_avx2<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    scan_structural_with(
+        input,
+        index,
+        |block| unsafe { candidate_mask::<Avx2>(block) },
+        |text| unsafe { validate_utf8_with::<Avx2>(text) },
+    )
 }
 
-/// Generic structural scanner using any SimdBackend
+/// Scan for structural characters using AVX-512BW
 ///
 /// # Safety
-/// - Requires the backend's SIMD features to be available
-#[inline]
-unsafe fn scan_structural_generic<B: SimdBackend>(input: &[u8], indices: &mut Vec<u32>) {
-    let len = input.len();
-    let mut pos = 0;
-
-    // Process full SIMD chunks
-    while pos + B::LANE_WIDTH <= len {
-        // SAFETY: We've verified pos + LANE_WIDTH <= len, so pointer arithmetic is valid.
-        // The caller must ensure the required SIMD features are available.
-        let chunk = unsafe { B::load_unaligned(input.as_ptr().add(pos)) };
-
-        // Check for structural characters: n, t, f, i, l, d, u, :, e, 0-9
-        // SAFETY: These operations use the SIMD features guaranteed by the caller.
-        let mask_n = unsafe { B::cmpeq_epi8(chunk, b'n') };
-        let mask_t = unsafe { B::cmpeq_epi8(chunk, b't') };
-        let mask_f = unsafe { B::cmpeq_epi8(chunk, b'f') };
-        let mask_i = unsafe { B::cmpeq_epi8(chunk, b'i') };
-        let mask_l = unsafe { B::cmpeq_epi8(chunk, b'l') };
-        let mask_d = unsafe { B::cmpeq_epi8(chunk, b'd') };
-        let mask_u = unsafe { B::cmpeq_epi8(chunk, b'u') };
-        let mask_colon = unsafe { B::cmpeq_epi8(chunk, b':') };
-        let mask_e = unsafe { B::cmpeq_epi8(chunk, b'e') };
-
-        // Digits 0-9
-        let mask_0 = unsafe { B::cmpeq_epi8(chunk, b'0') };
-        let mask_1 = unsafe { B::cmpeq_epi8(chunk, b'1') };
-        let mask_2 = unsafe { B::cmpeq_epi8(chunk, b'2') };
-        let mask_3 = unsafe { B::cmpeq_epi8(chunk, b'3') };
-        let mask_4 = unsafe { B::cmpeq_epi8(chunk, b'4') };
-        let mask_5 = unsafe { B::cmpeq_epi8(chunk, b'5') };
-        let mask_6 = unsafe { B::cmpeq_epi8(chunk, b'6') };
-        let mask_7 = unsafe { B::cmpeq_epi8(chunk, b'7') };
-        let mask_8 = unsafe { B::cmpeq_epi8(chunk, b'8') };
-        let mask_9 = unsafe { B::cmpeq_epi8(chunk, b'9') };
-
-        // Combine all masks
-        // SAFETY: These operations use the SIMD features guaranteed by the caller.
-        let combined = unsafe {
-            B::or(
-                B::or(
-                    B::or(B::or(mask_n, mask_t), B::or(mask_f, mask_i)),
-                    B::or(B::or(mask_l, mask_d), B::or(mask_u, mask_colon)),
-                ),
-                B::or(
-                    B::or(
-                        B::or(B::or(mask_e, mask_0), B::or(mask_1, mask_2)),
-                        B::or(B::or(mask_3, mask_4), B::or(mask_5, mask_6)),
-                    ),
-                    B::or(B::or(mask_7, mask_8), mask_9),
-                ),
-            )
-        };
-
-        let mut bits = unsafe { B::movemask_epi8(combined) };
-
-        // Extract positions from bitmask
-        while bits != 0 {
-            let bit_pos = bits.trailing_zeros();
-            indices.push((pos + bit_pos as usize) as u32);
-            bits &= bits - 1; // Clear lowest set bit
-        }
+/// - Requires AVX-512BW support (caller must verify with `is_x86_feature_detected!("avx512bw")`)
+#[target_feature(enable = "avx512bw")]
+pub unsafe fn scan_structural_avx512<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    scan_structural_with(
+        input,
+        index,
+        |block| unsafe { candidate_mask::<Avx512>(block) },
+        |text| unsafe { validate_utf8_with::<Avx512>(text) },
+    )
+}
 
-        pos += B::LANE_WIDTH;
-    }
+/// Check if `input` is valid UTF-8 using SSE4.2
+///
+/// # Safety
+/// - Requires SSE4.2 support (caller must verify with `is_x86_feature_detected!("sse4.2")`)
+#[target_feature(enable = "sse4.2")]
+pub unsafe fn validate_utf8_sse42(input: &[u8]) -> bool {
+    unsafe { validate_utf8_with::<Sse42>(input) }
+}
 
-    // Process remaining bytes with scalar code
-    while pos < len {
-        let byte = input[pos];
-        if is_structural_char(byte) {
-            indices.push(pos as u32);
-        }
-        pos += 1;
-    }
+/// Check if `input` is valid UTF-8 using AVX2
+///
+/// # Safety
+/// - Requires AVX2 support (caller must verify with `is_x86_feature_detected!("avx2")`)
+#[target_feature(enable = "avx2")]
+pub unsafe fn validate_utf8_avx2(input: &[u8]) -> bool {
+    unsafe { validate_utf8_with::<Avx2>(input) }
 }
 
-/// Check if a byte is a structural character
-#[inline]
-fn is_structural_char(b: u8) -> bool {
-    matches!(
-        b,
-        b'n' | b't' | b'f' | b'i' | b'l' | b'd' | b'u' | b':' | b'e' | b'0'..=b'9'
-    )
+/// Check if `input` is valid UTF-8 using AVX-512BW
+///
+/// # Safety
+/// - Requires AVX-512BW support (caller must verify with `is_x86_feature_detected!("avx512bw")`)
+#[target_feature(enable = "avx512bw")]
+pub unsafe fn validate_utf8_avx512(input: &[u8]) -> bool {
+    unsafe { validate_utf8_with::<Avx512>(input) }
 }
@@ -0,0 +1,247 @@
+//! Selection of the SIMD backend.
+//!
+//! Stage 1 and [`validate_utf8`](super::utf8::validate_utf8) use the fastest [`Backend`] the CPU
+//! supports, detected at runtime. [`force_backend`] overrides the choice for the whole process,
+//! e.g., to benchmark a specific backend or to avoid one on a CPU with errata, and
+//! [`decode_simd_with_backend`](super::decode_simd_with_backend) uses a backend for a single call,
+//! e.g., to compare every backend on the same machine.
+//!
+//! ```
+//! use bencodex::simd::{Backend, available_backends, force_backend, selected_backend};
+//!
+//! assert!(available_backends().contains(&Backend::Scalar));
+//!
+//! force_backend(Some(Backend::Scalar)).unwrap();
+//! assert_eq!(selected_backend(), Backend::Scalar);
+//!
+//! // Back to the detected one
+//! force_backend(None).unwrap();
+//! ```
+
+use core::error::Error;
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use super::arch::fallback::{scan_structural_scalar, validate_utf8_scalar};
+use super::structural::{Offset, StructuralIndex};
+use crate::prelude::*;
+
+/// An implementation of the structural scan and UTF-8 validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// Portable scalar code, available everywhere.
+    Scalar,
+    /// SSE4.2 on x86_64.
+    Sse42,
+    /// AVX2 on x86_64.
+    Avx2,
+    /// AVX-512BW on x86_64.
+    Avx512,
+    /// NEON on AArch64.
+    Neon,
+    /// SIMD128 on wasm32, if compiled with the `simd128` target feature.
+    Simd128,
+}
+
+impl Backend {
+    /// Every backend, from the most preferred to the least.
+    pub const ALL: [Backend; 6] = [
+        Backend::Avx512,
+        Backend::Avx2,
+        Backend::Sse42,
+        Backend::Neon,
+        Backend::Simd128,
+        Backend::Scalar,
+    ];
+
+    /// Get the name of the backend, e.g., `avx2`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Scalar => "scalar",
+            Backend::Sse42 => "sse4.2",
+            Backend::Avx2 => "avx2",
+            Backend::Avx512 => "avx512bw",
+            Backend::Neon => "neon",
+            Backend::Simd128 => "simd128",
+        }
+    }
+
+    /// Check if the backend can run on this machine.
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Backend::Sse42 => is_x86_feature_detected!("sse4.2"),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Backend::Avx512 => is_x86_feature_detected!("avx512bw"),
+            #[cfg(target_arch = "aarch64")]
+            Backend::Neon => true,
+            #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+            Backend::Simd128 => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Build the structural index of `input` into `index` with this backend.
+    ///
+    /// # Safety
+    /// - The backend must be [available](Backend::is_available)
+    pub(super) unsafe fn scan_structural<O: Offset>(
+        self,
+        input: &[u8],
+        index: &mut StructuralIndex<O>,
+    ) {
+        // SAFETY: The caller guarantees the backend is available
+        unsafe {
+            match self {
+                #[cfg(target_arch = "x86_64")]
+                Backend::Sse42 => super::arch::x86_64::scan_structural_sse42(input, index),
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx2 => super::arch::x86_64::scan_structural_avx2(input, index),
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx512 => super::arch::x86_64::scan_structural_avx512(input, index),
+                #[cfg(target_arch = "aarch64")]
+                Backend::Neon => super::arch::aarch64::scan_structural_neon(input, index),
+                #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+                Backend::Simd128 => super::arch::wasm32::scan_structural_simd128(input, index),
+                _ => scan_structural_scalar(input, index),
+            }
+        }
+    }
+
+    /// Check if `input` is valid UTF-8 with this backend.
+    ///
+    /// # Safety
+    /// - The backend must be [available](Backend::is_available)
+    pub(super) unsafe fn validate_utf8(self, input: &[u8]) -> bool {
+        // SAFETY: The caller guarantees the backend is available
+        unsafe {
+            match self {
+                #[cfg(target_arch = "x86_64")]
+                Backend::Sse42 => super::arch::x86_64::validate_utf8_sse42(input),
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx2 => super::arch::x86_64::validate_utf8_avx2(input),
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx512 => super::arch::x86_64::validate_utf8_avx512(input),
+                #[cfg(target_arch = "aarch64")]
+                Backend::Neon => super::arch::aarch64::validate_utf8_neon(input),
+                #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+                Backend::Simd128 => super::arch::wasm32::validate_utf8_simd128(input),
+                _ => validate_utf8_scalar(input),
+            }
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The error type which is returned from [`force_backend`] with a backend this machine lacks.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct UnavailableBackendError(pub Backend);
+
+impl fmt::Display for UnavailableBackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the {} backend is not available on this machine", self.0)
+    }
+}
+
+impl Error for UnavailableBackendError {}
+
+/// The backend set by [`force_backend`], as its index in [`Backend::ALL`] plus one, or 0 if none.
+static FORCED_BACKEND: AtomicU8 = AtomicU8::new(0);
+
+/// Get the backends which can run on this machine, from the most preferred to the least.
+///
+/// [`Backend::Scalar`] is always the last one.
+pub fn available_backends() -> Vec<Backend> {
+    Backend::ALL
+        .into_iter()
+        .filter(|backend| backend.is_available())
+        .collect()
+}
+
+/// Get the backend which decoding uses: the one set by [`force_backend`], or the most preferred
+/// available one.
+pub fn selected_backend() -> Backend {
+    match FORCED_BACKEND.load(Ordering::Relaxed) {
+        0 => detected_backend(),
+        forced => Backend::ALL[forced as usize - 1],
+    }
+}
+
+/// The most preferred available backend.
+fn detected_backend() -> Backend {
+    Backend::ALL
+        .into_iter()
+        .find(|backend| backend.is_available())
+        .unwrap_or(Backend::Scalar)
+}
+
+/// Make every following decoding in the process use `backend`, or the detected one with `None`.
+///
+/// Returns an error and keeps the current choice if `backend` is not available on this machine.
+pub fn force_backend(backend: Option<Backend>) -> Result<(), UnavailableBackendError> {
+    let value = match backend {
+        None => 0,
+        Some(backend) if backend.is_available() => {
+            Backend::ALL.iter().position(|&b| b == backend).unwrap() as u8 + 1
+        }
+        Some(backend) => return Err(UnavailableBackendError(backend)),
+    };
+    FORCED_BACKEND.store(value, Ordering::Relaxed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_available_backends() {
+        let backends = available_backends();
+        assert_eq!(backends.last(), Some(&Backend::Scalar));
+        assert!(backends.iter().all(|backend| backend.is_available()));
+        assert_eq!(backends[0], detected_backend());
+
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(
+            backends.contains(&Backend::Avx2),
+            is_x86_feature_detected!("avx2")
+        );
+        #[cfg(not(target_arch = "aarch64"))]
+        assert!(!backends.contains(&Backend::Neon));
+    }
+
+    #[test]
+    fn test_force_unavailable_backend() {
+        for backend in Backend::ALL {
+            if !backend.is_available() {
+                assert_eq!(
+                    force_backend(Some(backend)),
+                    Err(UnavailableBackendError(backend))
+                );
+            }
+        }
+        // Forcing the detected backend changes nothing for the other tests in the process
+        let detected = detected_backend();
+        force_backend(Some(detected)).unwrap();
+        assert_eq!(selected_backend(), detected);
+        force_backend(None).unwrap();
+    }
+
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(Backend::Sse42.to_string(), "sse4.2");
+        assert_eq!(
+            UnavailableBackendError(Backend::Neon).to_string(),
+            "the neon backend is not available on this machine"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Reusable SIMD decoder state.
+//!
+//! [`decode_simd`](super::decode_simd) allocates a new structural index on every call, which
+//! dominates the time to decode small values. [`SimdDecoder`] keeps the index between calls, and
+//! decodes small inputs with the scalar decoder, which needs no index at all.
+//!
+//! ```
+//! use bencodex::BencodexValue;
+//! use bencodex::simd::SimdDecoder;
+//!
+//! let mut decoder = SimdDecoder::new();
+//! for input in [&b"i1e"[..], b"u5:hello"] {
+//!     decoder.decode(input).unwrap();
+//! }
+//!
+//! // Or the decoder of the current thread
+//! let value = SimdDecoder::with_thread_local(|decoder| decoder.decode(b"li1ee")).unwrap();
+//! assert_eq!(value, BencodexValue::List(vec![1.into()]));
+//! ```
+
+use core::cell::RefCell;
+
+use super::stage1::build_structural_index_into;
+use super::stage2::SimdParser;
+use super::structural::{Offset, StructuralIndex};
+use super::tape::Tape;
+use crate::codec::decode::{DecodeError, DecodeIter, decode_borrowed, decode_iter};
+use crate::codec::types::BencodexValue;
+
+/// The default of [`SimdDecoder::scalar_threshold`].
+///
+/// Inputs this short have no long payloads for the SIMD scan to skip, so the fixed cost of
+/// building the structural index makes them slower to decode than with the scalar decoder.
+/// Run `cargo bench --features simd --bench decode -- decode_small` to compare on this machine.
+pub const DEFAULT_SCALAR_THRESHOLD: usize = 256;
+
+/// The most positions a [`SimdDecoder`] keeps room for between calls, so decoding a huge input
+/// once does not hold its index forever.
+const MAX_RETAINED_CAPACITY: usize = 1 << 20;
+
+std::thread_local! {
+    static THREAD_DECODER: RefCell<SimdDecoder> = RefCell::new(SimdDecoder::new());
+}
+
+/// A SIMD decoder which reuses its structural index across calls.
+///
+/// A decoder is `Send`, so it can be kept in a pool and moved between threads, or the one of the
+/// current thread can be used by [`SimdDecoder::with_thread_local`].
+#[derive(Debug, Clone)]
+pub struct SimdDecoder {
+    /// Inputs shorter than this are decoded by [`decode_borrowed`] instead.
+    pub scalar_threshold: usize,
+    index: StructuralIndex,
+}
+
+impl SimdDecoder {
+    /// Create a decoder with [`DEFAULT_SCALAR_THRESHOLD`].
+    pub fn new() -> Self {
+        Self::with_scalar_threshold(DEFAULT_SCALAR_THRESHOLD)
+    }
+
+    /// Create a decoder which decodes inputs shorter than `scalar_threshold` by
+    /// [`decode_borrowed`]. With 0, every input is decoded with SIMD.
+    pub fn with_scalar_threshold(scalar_threshold: usize) -> Self {
+        Self {
+            scalar_threshold,
+            index: StructuralIndex::new(),
+        }
+    }
+
+    /// Decode `input` like [`decode_simd`](super::decode_simd), reusing the structural index of
+    /// the previous calls.
+    ///
+    /// The decoded value is the same as [`decode_simd`](super::decode_simd) returns, but the
+    /// error for invalid input may differ when it is decoded by the scalar decoder.
+    pub fn decode<'a>(&mut self, input: &'a [u8]) -> Result<BencodexValue<'a>, DecodeError> {
+        if input.len() < self.scalar_threshold {
+            return decode_borrowed(input);
+        }
+        if input.len() > u32::MAX_INPUT_LEN {
+            // Rare enough not to keep a u64 index around
+            return super::decode_simd(input);
+        }
+
+        build_structural_index_into(input, &mut self.index);
+        let result = SimdParser::new(input, &self.index).parse();
+        self.shrink();
+        result
+    }
+
+    /// Decode `input` into a [`Tape`] like [`decode_tape`](super::tape::decode_tape), reusing the
+    /// structural index of the previous calls. Tapes are always built with SIMD regardless of
+    /// [`scalar_threshold`](SimdDecoder::scalar_threshold).
+    pub fn decode_tape<'a>(&mut self, input: &'a [u8]) -> Result<Tape<'a>, DecodeError> {
+        if input.len() > u32::MAX_INPUT_LEN {
+            return super::tape::decode_tape(input);
+        }
+
+        build_structural_index_into(input, &mut self.index);
+        let result = SimdParser::new(input, &self.index).parse_tape();
+        self.shrink();
+        result
+    }
+
+    /// Decode concatenated values in `input` like [`decode_iter`], building the structural index
+    /// of the whole input once.
+    ///
+    /// Like [`decode`](SimdDecoder::decode), inputs shorter than
+    /// [`scalar_threshold`](SimdDecoder::scalar_threshold) are decoded by the scalar decoder.
+    ///
+    /// ```
+    /// use bencodex::BencodexValue;
+    /// use bencodex::simd::SimdDecoder;
+    ///
+    /// let mut decoder = SimdDecoder::with_scalar_threshold(0);
+    /// let values: Vec<_> = decoder.decode_iter(b"i1eu1:an").collect();
+    /// assert_eq!(values, vec![Ok(1.into()), Ok("a".into()), Ok(BencodexValue::Null)]);
+    /// ```
+    pub fn decode_iter<'d, 'a>(&'d mut self, input: &'a [u8]) -> SimdDecodeIter<'d, 'a> {
+        if input.len() < self.scalar_threshold || input.len() > u32::MAX_INPUT_LEN {
+            return SimdDecodeIter(IterState::Scalar(decode_iter(input)));
+        }
+
+        // The iterator borrows the index, so release the room left by the previous call instead
+        self.shrink();
+        build_structural_index_into(input, &mut self.index);
+        SimdDecodeIter(IterState::Simd {
+            parser: SimdParser::new(input, &self.index),
+            len: input.len(),
+        })
+    }
+
+    /// Release the room of the index beyond [`MAX_RETAINED_CAPACITY`].
+    fn shrink(&mut self) {
+        if self.index.indices.capacity() > MAX_RETAINED_CAPACITY {
+            self.index.indices.shrink_to(MAX_RETAINED_CAPACITY);
+        }
+        if self.index.texts.capacity() > MAX_RETAINED_CAPACITY {
+            self.index.texts.shrink_to(MAX_RETAINED_CAPACITY);
+        }
+    }
+
+    /// Call `f` with the decoder of the current thread.
+    ///
+    /// If `f` calls this again, the inner call gets a new decoder instead of the busy one.
+    pub fn with_thread_local<R>(f: impl FnOnce(&mut SimdDecoder) -> R) -> R {
+        let mut f = Some(f);
+        let result = THREAD_DECODER.try_with(|decoder| {
+            decoder
+                .try_borrow_mut()
+                .ok()
+                .map(|mut decoder| (f.take().unwrap())(&mut decoder))
+        });
+        match result {
+            Ok(Some(result)) => result,
+            // Borrowed by an outer call, or the thread is being destroyed
+            _ => (f.take().unwrap())(&mut SimdDecoder::new()),
+        }
+    }
+}
+
+impl Default for SimdDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator over concatenated Bencodex values, returned by [`SimdDecoder::decode_iter`].
+///
+/// After yielding an error, it yields nothing more because the start of the next value is
+/// unknown.
+pub struct SimdDecodeIter<'d, 'a>(IterState<'d, 'a>);
+
+enum IterState<'d, 'a> {
+    /// Decoding with the structural index of the decoder.
+    Simd {
+        parser: SimdParser<'a, 'd>,
+        len: usize,
+    },
+    /// Decoding short or huge inputs with the scalar decoder.
+    Scalar(DecodeIter<'a>),
+}
+
+impl<'a> Iterator for SimdDecodeIter<'_, 'a> {
+    type Item = Result<BencodexValue<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IterState::Simd { parser, len } => {
+                if parser.position() >= *len {
+                    return None;
+                }
+                let result = parser.parse();
+                if result.is_err() {
+                    self.0 = IterState::Scalar(decode_iter(&[]));
+                }
+                Some(result)
+            }
+            IterState::Scalar(iter) => iter.next(),
+        }
+    }
+}
+
+impl core::iter::FusedIterator for SimdDecodeIter<'_, '_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::simd::decode_simd;
+    use alloc::vec;
+
+    const DATA: [&[u8]; 2] = [
+        include_bytes!("../../../_data/ncavatar_1.bin"),
+        include_bytes!("../../../_data/ncinventory_1.bin"),
+    ];
+
+    #[test]
+    fn test_simd_decoder_same_as_decode_simd() {
+        for threshold in [0, DEFAULT_SCALAR_THRESHOLD, usize::MAX] {
+            let mut decoder = SimdDecoder::with_scalar_threshold(threshold);
+            for input in [
+                &b"i1e"[..],
+                b"u5:hello",
+                DATA[0],
+                b"le",
+                DATA[1],
+                b"d1:ai1ee",
+            ] {
+                assert_eq!(decode_simd(input), decoder.decode(input));
+            }
+            assert!(decoder.decode(b"i1").is_err());
+            assert!(decoder.decode(b"d1:ai1e1:ai1ee").is_err());
+        }
+    }
+
+    #[test]
+    fn test_simd_decoder_decode_tape() {
+        let mut decoder = SimdDecoder::new();
+        for input in [&b"i1e"[..], DATA[0], DATA[1]] {
+            assert_eq!(
+                decode_simd(input).unwrap(),
+                decoder.decode_tape(input).unwrap().to_value()
+            );
+        }
+    }
+
+    #[test]
+    fn test_simd_decoder_reuses_index() {
+        let mut decoder = SimdDecoder::with_scalar_threshold(0);
+        decoder.decode(DATA[1]).unwrap();
+        let buffer = decoder.index.indices.as_ptr();
+        decoder.decode(DATA[0]).unwrap();
+        decoder.decode(DATA[1]).unwrap();
+        assert_eq!(buffer, decoder.index.indices.as_ptr());
+    }
+
+    #[test]
+    fn test_simd_decoder_scalar_threshold() {
+        let mut decoder = SimdDecoder::new();
+        decoder.decode(b"li1ei2ee").unwrap();
+        assert_eq!(0, decoder.index.indices.capacity());
+    }
+
+    #[test]
+    fn test_simd_decoder_decode_iter() {
+        let mut input = DATA.concat();
+        input.extend_from_slice(b"i1eu1:an");
+        for threshold in [0, usize::MAX] {
+            let mut decoder = SimdDecoder::with_scalar_threshold(threshold);
+            assert_eq!(
+                crate::decode_iter(&input).collect::<Vec<_>>(),
+                decoder.decode_iter(&input).collect::<Vec<_>>()
+            );
+
+            let mut values = decoder.decode_iter(b"i1eli2ex");
+            assert_eq!(values.next(), Some(Ok(1.into())));
+            assert!(values.next().unwrap().is_err());
+            assert_eq!(values.next(), None);
+            assert_eq!(decoder.decode_iter(b"").next(), None);
+        }
+    }
+
+    #[test]
+    fn test_simd_decoder_with_thread_local() {
+        let value = SimdDecoder::with_thread_local(|decoder| decoder.decode(DATA[0]));
+        assert_eq!(decode_simd(DATA[0]), value);
+
+        // Nested calls get their own decoder
+        let (outer, inner) = SimdDecoder::with_thread_local(|outer| {
+            let inner = SimdDecoder::with_thread_local(|inner| inner.decode(b"li1ee"));
+            (outer.decode(b"li2ee"), inner)
+        });
+        assert_eq!(Ok(BencodexValue::List(vec![2.into()])), outer);
+        assert_eq!(Ok(BencodexValue::List(vec![1.into()])), inner);
+    }
+}
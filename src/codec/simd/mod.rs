@@ -4,7 +4,8 @@
 //! strategy inspired by simdjson:
 //!
 //! 1. **Stage 1 (Structural Scanning)**: Uses SIMD instructions to scan the input
-//!    and identify all structural characters (type markers, delimiters, digits).
+//!    and identify all structural characters (type markers, delimiters, lengths).
+//!    Length prefixes are followed to exclude the bytes inside string payloads.
 //!    This builds a "structural index" of positions.
 //!
 //! 2. **Stage 2 (Value Extraction)**: Uses the structural index to parse values
@@ -12,12 +13,15 @@
 //!
 //! ## Supported Architectures
 //!
-//! - **x86_64**: SSE4.2 and AVX2 (runtime detection)
+//! - **x86_64**: SSE4.2, AVX2 and AVX-512BW (runtime detection)
 //! - **AArch64**: NEON (always available)
+//! - **wasm32**: SIMD128 (when compiled with `-C target-feature=+simd128`)
 //! - **Other**: Falls back to scalar implementation
 //!
 //! ## Usage
 //!
+//! To decode many values, [`decoder::SimdDecoder`] reuses its buffers across calls.
+//!
 //! ```ignore
 //! use bencodex::simd::decode_simd;
 //!
@@ -26,24 +30,35 @@
 //! ```
 
 pub mod arch;
+pub mod backend;
+pub mod decoder;
 pub mod number;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod stage1;
 pub mod stage2;
 pub mod structural;
+pub mod tape;
+pub mod utf8;
 
+use crate::codec::canonical::{CanonicalIssue, CanonicalIssueKind};
 use crate::codec::decode::DecodeError;
 use crate::codec::types::BencodexValue;
+use crate::prelude::*;
 
-use stage1::build_structural_index;
+use backend::Backend;
+use stage1::{build_structural_index_as, build_structural_index_with_backend};
 use stage2::SimdParser;
+use structural::Offset;
 
 /// Decode a Bencodex value using SIMD-accelerated parsing.
 ///
 /// This function automatically selects the best available SIMD implementation
 /// based on runtime CPU feature detection:
 ///
-/// - On x86_64: Uses AVX2 if available, otherwise SSE4.2
+/// - On x86_64: Uses AVX-512BW if available, otherwise AVX2, otherwise SSE4.2
 /// - On AArch64: Uses NEON (always available)
+/// - On wasm32: Uses SIMD128 if the `simd128` target feature is enabled
 /// - On other platforms: Falls back to scalar implementation
 ///
 /// # Arguments
@@ -55,6 +70,9 @@ use stage2::SimdParser;
 /// * `Ok(BencodexValue)` - The decoded value
 /// * `Err(DecodeError)` - If the input is not valid Bencodex
 ///
+/// Inputs over 4 GiB are indexed with `u64` positions instead of `u32`, so they are decoded
+/// correctly at the cost of a twice larger structural index.
+///
 /// # Example
 ///
 /// ```ignore
@@ -67,20 +85,93 @@ use stage2::SimdParser;
 /// let value = decode_simd(b"li1ei2ei3ee")?;
 /// ```
 pub fn decode_simd<'a>(input: &'a [u8]) -> Result<BencodexValue<'a>, DecodeError> {
+    // Positions of inputs over 4 GiB do not fit in the default u32 index
+    if input.len() > u32::MAX_INPUT_LEN {
+        return decode_simd_as::<u64>(input);
+    }
+    decode_simd_as::<u32>(input)
+}
+
+/// Decode `input` like [`decode_simd`], with `backend` instead of the
+/// [selected one](backend::selected_backend), e.g., to compare the backends on this machine.
+///
+/// # Panics
+///
+/// Panics if `backend` is not [available](Backend::is_available) on this machine.
+///
+/// # Example
+///
+/// ```ignore
+/// use bencodex::simd::{available_backends, decode_simd, decode_simd_with_backend};
+///
+/// for backend in available_backends() {
+///     assert_eq!(decode_simd_with_backend(b"li1ee", backend), decode_simd(b"li1ee"));
+/// }
+/// ```
+pub fn decode_simd_with_backend(
+    input: &[u8],
+    backend: Backend,
+) -> Result<BencodexValue<'_>, DecodeError> {
+    if input.len() > u32::MAX_INPUT_LEN {
+        let structural_index = build_structural_index_with_backend::<u64>(input, backend);
+        return SimdParser::new(input, &structural_index).parse();
+    }
+    let structural_index = build_structural_index_with_backend::<u32>(input, backend);
+    SimdParser::new(input, &structural_index).parse()
+}
+
+fn decode_simd_as<O: Offset>(input: &[u8]) -> Result<BencodexValue<'_>, DecodeError> {
     // Stage 1: Build structural index using SIMD
-    let structural_index = build_structural_index(input);
+    let structural_index = build_structural_index_as::<O>(input);
 
     // Stage 2: Parse using the structural index
     let mut parser = SimdParser::new(input, &structural_index);
     parser.parse()
 }
 
+/// Decode `input` like [`decode_lenient`](crate::canonical::decode_lenient) using SIMD, and
+/// return the value with the same issues in the same order.
+///
+/// # Example
+///
+/// ```ignore
+/// use bencodex::canonical::{CanonicalIssue, CanonicalIssueKind};
+/// use bencodex::simd::decode_simd_lenient;
+///
+/// let (value, issues) = decode_simd_lenient(b"i01e")?;
+/// assert_eq!(issues, vec![CanonicalIssue { kind: CanonicalIssueKind::NonCanonicalNumber, point: 1 }]);
+/// ```
+pub fn decode_simd_lenient(
+    input: &[u8],
+) -> Result<(BencodexValue<'_>, Vec<CanonicalIssue>), DecodeError> {
+    if input.len() > u32::MAX_INPUT_LEN {
+        return decode_simd_lenient_as::<u64>(input);
+    }
+    decode_simd_lenient_as::<u32>(input)
+}
+
+fn decode_simd_lenient_as<O: Offset>(
+    input: &[u8],
+) -> Result<(BencodexValue<'_>, Vec<CanonicalIssue>), DecodeError> {
+    let structural_index = build_structural_index_as::<O>(input);
+    let mut parser = SimdParser::with_observer(input, &structural_index, Vec::new());
+    let value = parser.parse()?;
+    let size = parser.position();
+    let mut issues = parser.into_observer();
+    if size < input.len() {
+        issues.push(CanonicalIssue {
+            kind: CanonicalIssueKind::TrailingData,
+            point: size,
+        });
+    }
+    Ok((value, issues))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::codec::types::{BencodexDictionary, BencodexKey};
     use alloc::borrow::Cow;
-    use num_bigint::BigInt;
 
     #[test]
     fn test_decode_simd_null() {
@@ -97,15 +188,15 @@ mod tests {
     fn test_decode_simd_integer() {
         assert_eq!(
             decode_simd(b"i0e").unwrap(),
-            BencodexValue::Number(BigInt::from(0))
+            BencodexValue::Number(0.into())
         );
         assert_eq!(
             decode_simd(b"i42e").unwrap(),
-            BencodexValue::Number(BigInt::from(42))
+            BencodexValue::Number(42.into())
         );
         assert_eq!(
             decode_simd(b"i-42e").unwrap(),
-            BencodexValue::Number(BigInt::from(-42))
+            BencodexValue::Number((-42).into())
         );
     }
 
@@ -142,9 +233,9 @@ mod tests {
         assert_eq!(
             decode_simd(b"li1ei2ei3ee").unwrap(),
             BencodexValue::List(alloc::vec![
-                BencodexValue::Number(BigInt::from(1)),
-                BencodexValue::Number(BigInt::from(2)),
-                BencodexValue::Number(BigInt::from(3)),
+                BencodexValue::Number(1.into()),
+                BencodexValue::Number(2.into()),
+                BencodexValue::Number(3.into()),
             ])
         );
     }
@@ -160,13 +251,97 @@ mod tests {
         if let BencodexValue::Dictionary(map) = result {
             assert_eq!(
                 map.get(&BencodexKey::Text(Cow::Borrowed("a"))),
-                Some(&BencodexValue::Number(BigInt::from(42)))
+                Some(&BencodexValue::Number(42.into()))
             );
         } else {
             panic!("Expected dictionary");
         }
     }
 
+    #[test]
+    fn test_decode_simd_wide_offsets() {
+        for data in [
+            &include_bytes!("../../../_data/ncavatar_1.bin")[..],
+            &include_bytes!("../../../_data/ncinventory_1.bin")[..],
+        ] {
+            assert_eq!(decode_simd(data), decode_simd_as::<u64>(data));
+        }
+    }
+
+    #[test]
+    fn test_decode_simd_with_backend() {
+        for data in [
+            &include_bytes!("../../../_data/ncavatar_1.bin")[..],
+            &include_bytes!("../../../_data/ncinventory_1.bin")[..],
+            b"lu2:\xff\xffe",
+        ] {
+            for backend in backend::available_backends() {
+                assert_eq!(
+                    decode_simd(data),
+                    decode_simd_with_backend(data, backend),
+                    "{}",
+                    backend
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "allocates more than 4 GiB"]
+    fn test_decode_simd_over_4gib() {
+        // A list whose second item starts after the first 4 GiB
+        let payload_len = u32::MAX as usize + 16;
+        let header = alloc::format!("l{}:", payload_len);
+        let mut input = alloc::vec::Vec::with_capacity(header.len() + payload_len + 6);
+        input.extend_from_slice(header.as_bytes());
+        input.resize(header.len() + payload_len, b'e');
+        input.extend_from_slice(b"u2:abe");
+        let value = decode_simd(&input).unwrap();
+        let BencodexValue::List(items) = value else {
+            panic!("Expected list");
+        };
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1], BencodexValue::Text(Cow::Borrowed("ab")));
+    }
+
+    #[test]
+    fn test_decode_simd_lenient() {
+        use crate::canonical::decode_lenient;
+
+        let issue = |kind, point| CanonicalIssue { kind, point };
+        for (data, kind, point) in [
+            (&b"i01e"[..], CanonicalIssueKind::NonCanonicalNumber, 1),
+            (b"i-0e", CanonicalIssueKind::NonCanonicalNumber, 1),
+            (b"i00e", CanonicalIssueKind::NonCanonicalNumber, 1),
+            (b"01:a", CanonicalIssueKind::LeadingZeroLength, 0),
+            (b"u01:a", CanonicalIssueKind::LeadingZeroLength, 1),
+        ] {
+            // The default decoding accepts them silently
+            let (value, issues) = decode_simd_lenient(data).unwrap();
+            assert_eq!(decode_simd(data), Ok(value));
+            assert_eq!(issues, alloc::vec![issue(kind, point)], "{:?}", data);
+        }
+
+        for data in [
+            &b"i-000000000000000000000001e"[..],
+            b"i1ei2e",
+            b"d1:bi01e1:a01:xe",
+            b"du1:bnu1:antu1:anu1:bfe",
+            b"d1:b1:x1:a1:y1:b1:z1:a1:we",
+            b"l001:ai-0ed02:abi1eee",
+            b"d1:ai1ee",
+            b"u2:\xff\xfe",
+            &include_bytes!("../../../_data/ncavatar_1.bin")[..],
+        ] {
+            assert_eq!(
+                decode_simd_lenient(data),
+                decode_lenient(data),
+                "{:?}",
+                data
+            );
+        }
+    }
+
     #[test]
     fn test_decode_simd_error() {
         assert!(decode_simd(b"").is_err());
@@ -1,12 +1,152 @@
-//! SIMD-accelerated number parsing utilities.
+//! Fast decimal parsing for lengths and integers.
 //!
-//! Note: Currently uses scalar parsing as Bencodex integers can be
-//! arbitrarily large (BigInt). SIMD optimization would be limited to
-//! the digit extraction phase for fixed-size integers.
+//! Up to 19 digits, which always fit in `u64`, are parsed eight at a time with SWAR (SIMD
+//! within a register) arithmetic on a `u64`, which also checks they are all digits. Longer
+//! integers are rare in Bencodex and fall back to [`BigInt`].
+//!
+//! The parsers accept leading zeros and `-0` like the scalar decoder, and report whether the
+//! number is canonical as a by-product of the same pass.
 
+use crate::codec::types::BencodexNumber;
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
 
+/// The most digits which always fit in `u64`.
+const MAX_FAST_DIGITS: usize = 19;
+
+/// An integer parsed by [`parse_integer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedInteger {
+    /// The integer, or `None` if it does not fit in `i64` and has to be parsed as a [`BigInt`].
+    pub value: Option<i64>,
+    /// Whether the integer has no leading zeros and is not `-0`, as in the canonical form.
+    pub canonical: bool,
+}
+
+/// A length parsed by [`parse_length`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedLength {
+    pub value: usize,
+    /// Whether the length has no leading zeros, as in the canonical form.
+    pub canonical: bool,
+}
+
+/// Check if each byte of `chunk` is an ASCII digit.
+#[inline]
+fn is_8_digits(chunk: u64) -> bool {
+    // The high nibble of each byte must be 3, and adding 6 must not carry into it
+    const HIGH: u64 = 0xF0F0_F0F0_F0F0_F0F0;
+    const THREES: u64 = 0x3030_3030_3030_3030;
+    chunk & HIGH == THREES && chunk.wrapping_add(0x0606_0606_0606_0606) & HIGH == THREES
+}
+
+/// Parse 8 ASCII digits loaded in little-endian order, so the first digit is the lowest byte.
+#[inline]
+fn parse_8_digits(chunk: u64) -> u64 {
+    // Combine adjacent digits into pairs, pairs into quads, and quads into the result
+    let chunk = chunk - 0x3030_3030_3030_3030;
+    let chunk = (chunk * 10 + (chunk >> 8)) & 0x00FF_00FF_00FF_00FF;
+    let chunk = (chunk * 100 + (chunk >> 16)) & 0x0000_FFFF_0000_FFFF;
+    (chunk * 10000 + (chunk >> 32)) & 0xFFFF_FFFF
+}
+
+/// Parse at most [`MAX_FAST_DIGITS`] ASCII digits, or return `None` if any byte is not a digit.
+#[inline]
+fn parse_digits(digits: &[u8]) -> Option<u64> {
+    debug_assert!(digits.len() <= MAX_FAST_DIGITS);
+    let (head, chunks) = digits.split_at(digits.len() % 8);
+
+    let mut value = 0u64;
+    for &b in head {
+        let digit = b.wrapping_sub(b'0');
+        if digit > 9 {
+            return None;
+        }
+        value = value * 10 + digit as u64;
+    }
+    for chunk in chunks.chunks_exact(8) {
+        let chunk = u64::from_le_bytes(chunk.try_into().unwrap());
+        if !is_8_digits(chunk) {
+            return None;
+        }
+        value = value * 100_000_000 + parse_8_digits(chunk);
+    }
+    Some(value)
+}
+
+/// Parse a decimal integer with an optional leading `-`.
+///
+/// Returns `None` if `bytes` is not an integer. Integers out of the range of `i64` are only
+/// validated, and their [`value`](ParsedInteger::value) is `None`.
+#[inline]
+pub fn parse_integer(bytes: &[u8]) -> Option<ParsedInteger> {
+    let digits = bytes.strip_prefix(b"-").unwrap_or(bytes);
+    let negative = digits.len() < bytes.len();
+    if digits.is_empty() {
+        return None;
+    }
+    let leading_zero = digits.len() > 1 && digits[0] == b'0';
+
+    if digits.len() > MAX_FAST_DIGITS {
+        // Out of the range of i64 unless it has leading zeros, which leave it to BigInt as well
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        return Some(ParsedInteger {
+            value: None,
+            canonical: !leading_zero,
+        });
+    }
+
+    let unsigned = parse_digits(digits)?;
+    let value = if negative {
+        0i64.checked_sub_unsigned(unsigned)
+    } else {
+        i64::try_from(unsigned).ok()
+    };
+    Some(ParsedInteger {
+        value,
+        canonical: !(leading_zero || negative && unsigned == 0),
+    })
+}
+
+/// Parse the length of a binary or a text.
+///
+/// Returns `None` if `bytes` is not a non-negative decimal integer, or it is too large to be the
+/// length of any input.
+#[inline]
+pub fn parse_length(bytes: &[u8]) -> Option<ParsedLength> {
+    let canonical = match bytes {
+        [] => return None,
+        [b'0', _, ..] => false,
+        _ => true,
+    };
+
+    let mut digits = bytes;
+    if digits.len() > MAX_FAST_DIGITS {
+        // Only leading zeros can make such a length fit
+        let zeros = digits.iter().take_while(|&&b| b == b'0').count();
+        digits = &digits[zeros.min(digits.len() - 1)..];
+        if digits.len() > MAX_FAST_DIGITS {
+            return None;
+        }
+    }
+
+    let value = usize::try_from(parse_digits(digits)?).ok()?;
+    Some(ParsedLength { value, canonical })
+}
+
+/// Parse a decimal integer of any size with [`parse_integer`], and only fall back to [`BigInt`]
+/// if it does not fit in `i64`.
+#[inline]
+pub fn parse_number(bytes: &[u8]) -> Option<BencodexNumber> {
+    match parse_integer(bytes)?.value {
+        Some(n) => Some(n.into()),
+        // The digits are validated, so BigInt accepting `+` and `_` does not matter
+        None => BigInt::parse_bytes(bytes, 10).map(BencodexNumber::from),
+    }
+}
+
 /// Fast path for parsing small positive integers (up to 20 digits).
 ///
 /// Returns `None` if the number is too large or negative, in which case
@@ -37,38 +177,7 @@ pub fn try_parse_small_positive(bytes: &[u8]) -> Option<u64> {
 /// Fast path for parsing small integers (positive or negative, up to i64 range).
 #[inline]
 pub fn try_parse_small_integer(bytes: &[u8]) -> Option<i64> {
-    if bytes.is_empty() {
-        return None;
-    }
-
-    let is_negative = bytes[0] == b'-';
-    let digits = if is_negative { &bytes[1..] } else { bytes };
-
-    // i64::MAX has 19 digits (9223372036854775807)
-    // i64::MIN has 19 digits after the sign (-9223372036854775808)
-    if digits.is_empty() || digits.len() > 19 {
-        return None;
-    }
-
-    let unsigned = try_parse_small_positive(digits)?;
-
-    if is_negative {
-        // Handle i64::MIN specially to avoid overflow when negating
-        // i64::MIN = -9223372036854775808, which is -(i64::MAX + 1)
-        let min_abs = (i64::MAX as u64) + 1; // 9223372036854775808
-        if unsigned > min_abs {
-            return None;
-        }
-        if unsigned == min_abs {
-            return Some(i64::MIN);
-        }
-        Some(-(unsigned as i64))
-    } else {
-        if unsigned > i64::MAX as u64 {
-            return None;
-        }
-        Some(unsigned as i64)
-    }
+    parse_integer(bytes)?.value
 }
 
 /// Convert a BigInt to u64 if it fits, otherwise return None.
@@ -117,6 +226,112 @@ mod tests {
         assert_eq!(try_parse_small_positive(b"12a3"), None);
     }
 
+    #[test]
+    fn test_parse_digits() {
+        let digits = b"1234567890123456789";
+        for len in 1..=MAX_FAST_DIGITS {
+            let expected: u64 = core::str::from_utf8(&digits[..len])
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(parse_digits(&digits[..len]), Some(expected), "len: {}", len);
+        }
+        assert_eq!(
+            parse_digits(b"9999999999999999999"),
+            Some(9999999999999999999)
+        );
+        assert_eq!(parse_digits(b"00000000"), Some(0));
+
+        // Every position of a chunk, with bytes around the digits
+        for len in [8, 16, 19] {
+            for i in 0..len {
+                for b in [b'/', b':', b'+', b'-', b' ', b'a', 0x80, 0xFF, 0x00] {
+                    let mut bytes = digits[..len].to_vec();
+                    bytes[i] = b;
+                    assert_eq!(parse_digits(&bytes), None, "{:?}", bytes);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        let parsed = |value, canonical| Some(ParsedInteger { value, canonical });
+        assert_eq!(parse_integer(b"0"), parsed(Some(0), true));
+        assert_eq!(parse_integer(b"-123"), parsed(Some(-123), true));
+        assert_eq!(
+            parse_integer(b"9223372036854775807"),
+            parsed(Some(i64::MAX), true)
+        );
+        assert_eq!(
+            parse_integer(b"-9223372036854775808"),
+            parsed(Some(i64::MIN), true)
+        );
+        assert_eq!(parse_integer(b"9223372036854775808"), parsed(None, true));
+        assert_eq!(parse_integer(b"-9223372036854775809"), parsed(None, true));
+        assert_eq!(
+            parse_integer(b"123456789012345678901234567890"),
+            parsed(None, true)
+        );
+
+        // Non-canonical
+        assert_eq!(parse_integer(b"-0"), parsed(Some(0), false));
+        assert_eq!(parse_integer(b"00"), parsed(Some(0), false));
+        assert_eq!(parse_integer(b"-01"), parsed(Some(-1), false));
+        assert_eq!(
+            parse_integer(b"00000000000000000000001"),
+            parsed(None, false)
+        );
+
+        // Invalid
+        for bytes in [
+            &b""[..],
+            b"-",
+            b"--1",
+            b"+1",
+            b"1_000",
+            b" 1",
+            b"1-",
+            b"12345678901234567890123x",
+        ] {
+            assert_eq!(parse_integer(bytes), None, "{:?}", bytes);
+        }
+    }
+
+    #[test]
+    fn test_parse_length() {
+        let parsed = |value, canonical| Some(ParsedLength { value, canonical });
+        assert_eq!(parse_length(b"0"), parsed(0, true));
+        assert_eq!(parse_length(b"12345678"), parsed(12345678, true));
+        assert_eq!(parse_length(b"05"), parsed(5, false));
+        assert_eq!(parse_length(b"000000000000000000000"), parsed(0, false));
+        assert_eq!(
+            parse_length(b"0000000000000000000000000012"),
+            parsed(12, false)
+        );
+
+        assert_eq!(parse_length(b""), None);
+        assert_eq!(parse_length(b"-1"), None);
+        assert_eq!(parse_length(b"+1"), None);
+        assert_eq!(parse_length(b"99999999999999999999"), None);
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number(b"-42"), Some(BencodexNumber::from(-42)));
+        assert_eq!(
+            parse_number(b"-123456789012345678901234567890"),
+            Some("-123456789012345678901234567890".parse().unwrap())
+        );
+        // Leading zeros make it long, but it fits in i64
+        assert_eq!(
+            parse_number(b"-000000000000000000001"),
+            Some(BencodexNumber::from(-1))
+        );
+        assert!(parse_number(b"-000000000000000000001").unwrap().is_small());
+        assert_eq!(parse_number(b"+123456789012345678901234567890"), None);
+    }
+
     #[test]
     fn test_try_parse_small_integer() {
         assert_eq!(try_parse_small_integer(b"0"), Some(0));
@@ -0,0 +1,297 @@
+//! Parallel decoding of large top-level lists and dictionaries.
+//!
+//! Stage 1 builds the structural index of the whole input as usual. The positions at the first
+//! level of the top-level container are then the starts of its items, so the items are split
+//! into batches of about the same size, and each batch is parsed by stage 2 on a thread of the
+//! [rayon] pool. Only the top-level container is split; values inside the items are decoded on
+//! the thread of their item.
+//!
+//! [rayon]: https://docs.rs/rayon
+
+use rayon::prelude::*;
+
+use super::stage1::build_structural_index_as;
+use super::stage2::SimdParser;
+use super::structural::{Offset, StructuralIndex};
+use crate::codec::decode::DecodeError;
+use crate::codec::dictionary::DictionaryBuilder;
+use crate::codec::types::*;
+use crate::prelude::*;
+
+/// Inputs shorter than this are decoded by [`decode_simd`](super::decode_simd) on the current
+/// thread, because spreading them over threads costs more than it saves.
+pub const MIN_PARALLEL_LEN: usize = 1 << 20;
+
+/// The number of batches per thread, so threads which finish early can take more.
+const BATCHES_PER_THREAD: usize = 4;
+
+/// Decode `input` like [`decode_simd`](super::decode_simd), parsing the items of a top-level
+/// list or dictionary on several threads of the current [rayon] pool.
+///
+/// It returns the same result as [`decode_simd`](super::decode_simd) for every input. Values
+/// other than lists and dictionaries, and inputs shorter than [`MIN_PARALLEL_LEN`], are decoded
+/// on the current thread. To use a specific pool, call this in
+/// [`ThreadPool::install`](rayon::ThreadPool::install).
+///
+/// [rayon]: https://docs.rs/rayon
+pub fn decode_simd_parallel(input: &[u8]) -> Result<BencodexValue<'_>, DecodeError> {
+    if input.len() < MIN_PARALLEL_LEN {
+        return super::decode_simd(input);
+    }
+    let batch_len = input.len() / (rayon::current_num_threads() * BATCHES_PER_THREAD);
+    if input.len() > u32::MAX_INPUT_LEN {
+        return decode_parallel_as::<u64>(input, batch_len);
+    }
+    decode_parallel_as::<u32>(input, batch_len)
+}
+
+/// Decode `input` in batches of about `batch_len` bytes.
+fn decode_parallel_as<O: Offset>(
+    input: &[u8],
+    batch_len: usize,
+) -> Result<BencodexValue<'_>, DecodeError> {
+    let structural_index = build_structural_index_as::<O>(input);
+    let result = match input.first() {
+        Some(b'l') => decode_list(input, &structural_index, batch_len),
+        Some(b'd') => decode_dict(input, &structural_index, batch_len),
+        _ => SimdParser::new(input, &structural_index).parse(),
+    };
+    // Errors are rare, so they are reported by decoding again sequentially rather than by
+    // working out which batch would have failed first.
+    result.or_else(|_| SimdParser::new(input, &structural_index).parse())
+}
+
+/// The items of the top-level container, split into batches.
+struct Batches {
+    /// `(start, struct_idx)` of the first item of each batch, and of the end of the container.
+    bounds: Vec<(usize, usize)>,
+}
+
+impl Batches {
+    /// Find the items of the container at the beginning of `input` from its structural index,
+    /// and split them into batches of about `batch_len` bytes. A dictionary is split only before
+    /// keys, which are every `stride`th item.
+    fn split<O: Offset>(
+        input: &[u8],
+        structural: &StructuralIndex<O>,
+        batch_len: usize,
+        stride: usize,
+    ) -> Result<Self, DecodeError> {
+        let mut bounds = Vec::new();
+        let mut depth = 0usize;
+        let mut items = 0usize;
+        let mut batch_start = 0usize;
+        for (struct_idx, &pos) in structural.indices.iter().enumerate() {
+            let pos = pos.to_usize();
+            let byte = input[pos];
+            if depth == 1
+                && matches!(
+                    byte,
+                    b'n' | b't' | b'f' | b'i' | b'l' | b'd' | b'u' | b'0'..=b'9'
+                )
+            {
+                if items.is_multiple_of(stride)
+                    && (bounds.is_empty() || pos - batch_start >= batch_len)
+                {
+                    bounds.push((pos, struct_idx));
+                    batch_start = pos;
+                }
+                items += 1;
+            }
+            match byte {
+                b'l' | b'd' | b'i' => depth += 1,
+                b'e' => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or(DecodeError::InvalidBencodexValueError)?;
+                    if depth == 0 {
+                        bounds.push((pos, struct_idx));
+                        return Ok(Self { bounds });
+                    }
+                }
+                _ => {}
+            }
+        }
+        // The container is not closed
+        Err(DecodeError::InvalidBencodexValueError)
+    }
+
+    /// Parse each batch on the rayon pool with `parse_item`, which parses an item at the position
+    /// of the parser.
+    fn parse<'a, O, T, F>(
+        &self,
+        input: &'a [u8],
+        structural: &StructuralIndex<O>,
+        parse_item: F,
+    ) -> Result<Vec<Vec<T>>, DecodeError>
+    where
+        O: Offset,
+        T: Send,
+        F: Fn(&mut SimdParser<'a, '_, O>) -> Result<T, DecodeError> + Sync,
+    {
+        self.bounds
+            .par_windows(2)
+            .map(|batch| {
+                let ((start, struct_idx), (end, _)) = (batch[0], batch[1]);
+                let mut parser = SimdParser::starting_at(input, structural, start, struct_idx);
+                let mut items = Vec::new();
+                while parser.position() < end {
+                    items.push(parse_item(&mut parser)?);
+                }
+                // The last item of the batch must end exactly where the next batch starts
+                if parser.position() != end {
+                    return Err(DecodeError::InvalidBencodexValueError);
+                }
+                Ok(items)
+            })
+            .collect()
+    }
+}
+
+fn decode_list<'a, O: Offset>(
+    input: &'a [u8],
+    structural: &StructuralIndex<O>,
+    batch_len: usize,
+) -> Result<BencodexValue<'a>, DecodeError> {
+    let batches = Batches::split(input, structural, batch_len, 1)?;
+    if batches.bounds.len() == 1 {
+        // An empty list
+        return SimdParser::new(input, structural).parse();
+    }
+    let batches = batches.parse(input, structural, |parser| parser.parse())?;
+    let mut list = Vec::with_capacity(batches.iter().map(Vec::len).sum());
+    for batch in batches {
+        list.extend(batch);
+    }
+    Ok(BencodexValue::List(list))
+}
+
+fn decode_dict<'a, O: Offset>(
+    input: &'a [u8],
+    structural: &StructuralIndex<O>,
+    batch_len: usize,
+) -> Result<BencodexValue<'a>, DecodeError> {
+    let batches = Batches::split(input, structural, batch_len, 2)?;
+    if batches.bounds.len() == 1 {
+        // An empty dictionary
+        return SimdParser::new(input, structural).parse();
+    }
+    let batches = batches.parse(input, structural, |parser| {
+        Ok((parser.parse_key()?, parser.parse()?))
+    })?;
+    let mut map = DictionaryBuilder::default();
+    for (key, value) in batches.into_iter().flatten() {
+        // Reject duplicated keys like the scalar decoder
+        if map.push(key, value, ()).is_some() {
+            return Err(DecodeError::InvalidBencodexValueError);
+        }
+    }
+    let (map, duplicates) = map.finish();
+    if !duplicates.is_empty() {
+        return Err(DecodeError::InvalidBencodexValueError);
+    }
+    Ok(BencodexValue::Dictionary(map))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::encode::Encode;
+    use crate::codec::simd::decode_simd;
+    use alloc::format;
+    use alloc::vec;
+
+    fn encode(value: &BencodexValue<'_>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        value.encode(&mut buf).unwrap();
+        buf
+    }
+
+    fn item(i: usize) -> BencodexValue<'static> {
+        match i % 5 {
+            0 => (i as i64 - 50).into(),
+            1 => format!("text {} with e and l", i).into(),
+            2 => vec![b'd'; i % 70].into(),
+            3 => BencodexValue::List(vec![item(i + 1), BencodexValue::Null]),
+            _ => {
+                let mut dict = BencodexDictionary::new();
+                dict.insert(format!("k{}", i).into(), item(i + 2));
+                BencodexValue::Dictionary(dict)
+            }
+        }
+    }
+
+    fn assert_same_as_decode_simd(input: &[u8]) {
+        for batch_len in [0, 1, 50, 1000, usize::MAX] {
+            assert_eq!(
+                decode_simd(input),
+                decode_parallel_as::<u32>(input, batch_len),
+                "batch_len: {}",
+                batch_len
+            );
+            assert_eq!(
+                decode_simd(input),
+                decode_parallel_as::<u64>(input, batch_len)
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_parallel_list() {
+        let list = BencodexValue::List((0..200).map(item).collect());
+        assert_same_as_decode_simd(&encode(&list));
+        assert_same_as_decode_simd(b"le");
+        assert_same_as_decode_simd(b"li1ee");
+    }
+
+    #[test]
+    fn test_decode_parallel_dict() {
+        let mut dict = BencodexDictionary::new();
+        for i in 0..200 {
+            dict.insert(format!("key {}", i).into(), item(i));
+            dict.insert(vec![i as u8; i % 3].into(), item(i + 3));
+        }
+        assert_same_as_decode_simd(&encode(&BencodexValue::Dictionary(dict)));
+        assert_same_as_decode_simd(b"de");
+        // Unordered keys
+        assert_same_as_decode_simd(b"du1:bi1eu1:ai2ee");
+    }
+
+    #[test]
+    fn test_decode_parallel_other_values() {
+        for input in [&b"n"[..], b"i42e", b"u5:hello", b"3:abc"] {
+            assert_same_as_decode_simd(input);
+        }
+        let data = include_bytes!("../../../_data/ncinventory_1.bin");
+        assert_same_as_decode_simd(data);
+    }
+
+    #[test]
+    fn test_decode_parallel_invalid() {
+        for input in [
+            &b""[..],
+            b"l",
+            b"li1e",
+            b"li1eie",
+            b"li1e1:",
+            b"li1ee1",
+            b"lei1e",
+            b"di1ei2ee",
+            b"du1:ai1eu1:ai2ee",
+            b"du1:ai1eu1:ae",
+            b"du1:ai1e",
+            b"lu2:\xff\xffe",
+        ] {
+            assert_same_as_decode_simd(input);
+        }
+    }
+
+    #[test]
+    fn test_decode_simd_parallel() {
+        // Over the threshold, so it is really split
+        let list = BencodexValue::List((0..40000).map(item).collect());
+        let input = encode(&list);
+        assert!(input.len() >= MIN_PARALLEL_LEN);
+        assert_eq!(decode_simd(&input), decode_simd_parallel(&input));
+    }
+}
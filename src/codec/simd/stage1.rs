@@ -2,63 +2,233 @@
 //!
 //! This stage scans the input buffer using SIMD instructions to find
 //! all structural characters and build a structural index.
+//!
+//! The SIMD scanners find *candidates* in 64-byte blocks: every type marker, `:` and `e`,
+//! including the same bytes inside binary and text payloads. [`scan_structural_with`] then walks
+//! the candidates in order, reads each length prefix, and drops the candidates inside its payload.
+//! Blocks entirely inside a payload are skipped without being scanned at all, which makes stage 1
+//! faster on data with long strings, and leaves stage 2 only the real structural positions.
+//!
+//! Text payloads are validated as UTF-8 with the same backend when the walk reaches them, and the
+//! spans of the valid ones are recorded in [`StructuralIndex::texts`].
+
+#[cfg(doc)]
+use super::backend::force_backend;
+use super::backend::{Backend, selected_backend};
+use super::structural::{Offset, StructuralIndex};
 
-use super::arch::fallback::scan_structural_scalar;
-use super::structural::StructuralIndex;
+/// The largest number of positions reserved before scanning.
+const MAX_ESTIMATED_CAPACITY: usize = 1 << 24;
 
 /// Build structural index from input using the best available SIMD implementation.
 ///
-/// This function automatically selects the best SIMD implementation based on
-/// runtime CPU feature detection:
-/// - AVX2 on x86_64 if available
+/// This function uses the [selected backend](selected_backend), which is the best SIMD
+/// implementation based on runtime CPU feature detection unless [`force_backend`] overrides it:
+/// - AVX-512BW on x86_64 if available
+/// - AVX2 on x86_64 as fallback
 /// - SSE4.2 on x86_64 as fallback
 /// - NEON on AArch64 (always available)
+/// - SIMD128 on wasm32 if compiled with the `simd128` target feature
 /// - Scalar fallback on other platforms
+///
+/// # Panics
+///
+/// Panics if `input` is longer than 4 GiB. Use [`build_structural_index_as`] with `u64` for such
+/// input.
 pub fn build_structural_index(input: &[u8]) -> StructuralIndex {
-    // Estimate capacity: structural chars are typically 10-20% of input
-    let estimated_capacity = input.len() / 8;
-    let mut index = StructuralIndex::with_capacity(estimated_capacity);
+    build_structural_index_as(input)
+}
 
-    scan_structural(input, &mut index.indices);
+/// Build a structural index with positions of type `O`, like [`build_structural_index`].
+///
+/// # Panics
+///
+/// Panics if `input` is longer than [`Offset::MAX_INPUT_LEN`] of `O`.
+pub fn build_structural_index_as<O: Offset>(input: &[u8]) -> StructuralIndex<O> {
+    let mut index = StructuralIndex::new();
+    build_structural_index_into(input, &mut index);
+    index
+}
 
+/// Build a structural index with `backend` instead of the [selected one](selected_backend), like
+/// [`build_structural_index_as`].
+///
+/// # Panics
+///
+/// Panics if `backend` is not [available](Backend::is_available) on this machine, or `input` is
+/// longer than [`Offset::MAX_INPUT_LEN`] of `O`.
+pub fn build_structural_index_with_backend<O: Offset>(
+    input: &[u8],
+    backend: Backend,
+) -> StructuralIndex<O> {
+    assert!(
+        backend.is_available(),
+        "the {} backend is not available on this machine",
+        backend
+    );
+    let mut index = StructuralIndex::new();
+    index
+        .indices
+        .reserve((input.len() / 4).min(MAX_ESTIMATED_CAPACITY));
+    // SAFETY: The backend is checked to be available above
+    unsafe { backend.scan_structural(input, &mut index) };
     index
 }
 
-/// Scan input for structural characters using the best available SIMD.
+/// Build the structural index of `input` into `index`, replacing what it has, like
+/// [`build_structural_index`]. The buffers of `index` are reused, so building many indices into
+/// the same one allocates only when an input needs more room than any before.
+///
+/// # Panics
+///
+/// Panics if `input` is longer than [`Offset::MAX_INPUT_LEN`] of `O`.
+pub fn build_structural_index_into<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    index.clear();
+    // Estimate capacity: structural chars are up to 30% of input with many small values.
+    // Huge inputs are mostly long payloads, so the estimate is capped and grows as needed.
+    let estimated_capacity = (input.len() / 4).min(MAX_ESTIMATED_CAPACITY);
+    index.indices.reserve(estimated_capacity);
+
+    scan_structural(input, index);
+}
+
+/// Scan input for structural characters using the [selected backend](selected_backend).
 #[inline]
-fn scan_structural(input: &[u8], indices: &mut Vec<u32>) {
-    #[cfg(target_arch = "x86_64")]
-    {
-        // SAFETY: We check CPU features before using SIMD instructions
-        unsafe {
-            if is_x86_feature_detected!("avx2") {
-                super::arch::x86_64::scan_structural_avx2(input, indices);
-                return;
+fn scan_structural<O: Offset>(input: &[u8], index: &mut StructuralIndex<O>) {
+    // SAFETY: The selected backend is always available
+    unsafe { selected_backend().scan_structural(input, index) }
+}
+
+/// Parse the length prefix of a binary or a text, or return `None` if it is malformed.
+#[inline(always)]
+fn parse_length(digits: &[u8]) -> Option<usize> {
+    if digits.is_empty() {
+        return None;
+    }
+    digits.iter().try_fold(0usize, |length, &byte| {
+        if !byte.is_ascii_digit() {
+            return None;
+        }
+        length.checked_mul(10)?.checked_add((byte - b'0') as usize)
+    })
+}
+
+/// Build the structural index of `input` into `index`, with `candidate_mask` finding the
+/// candidates of each 64-byte block and `validate_utf8` checking each text payload.
+///
+/// The index has the position of every value, the `:` of every binary and text, and the `e` of
+/// every integer, list and dictionary. Bytes inside binary and text payloads are excluded.
+///
+/// Digits are not candidates. The length of a binary or a text is read from the bytes between
+/// the end of the previous token and its `:`, and a binary starts at the end of the previous
+/// token.
+///
+/// The walk trusts length prefixes without validating the whole input. On malformed input, the
+/// index may lack some positions or have extra ones, but stage 2 checks the bytes at every
+/// position it uses, so it fails instead of misparsing. A text span is recorded only after its
+/// bytes are validated, so it is valid UTF-8 whatever the rest of the input is.
+///
+/// # Panics
+///
+/// Panics if `input` is longer than [`Offset::MAX_INPUT_LEN`] of `O`, rather than truncating
+/// positions which stage 2 would then misparse.
+#[inline(always)]
+pub(super) fn scan_structural_with<O, M, V>(
+    input: &[u8],
+    index: &mut StructuralIndex<O>,
+    mut candidate_mask: M,
+    mut validate_utf8: V,
+) where
+    O: Offset,
+    M: FnMut(&[u8; 64]) -> u64,
+    V: FnMut(&[u8]) -> bool,
+{
+    let StructuralIndex { indices, texts } = index;
+    let len = input.len();
+    assert!(
+        len <= O::MAX_INPUT_LEN,
+        "input of {} bytes is too long for {} offsets",
+        len,
+        core::any::type_name::<O>()
+    );
+    // The end of the last token, where the next token starts.
+    let mut token_end = 0usize;
+    // Whether the last token is `u`, so the next `:` ends the length of a text.
+    let mut in_text = false;
+    // Whether the last token is `i`, so the next `e` ends an integer.
+    let mut in_integer = false;
+    // The end of the last string payload. Candidates before it are not structural.
+    let mut skip_until = 0usize;
+    let mut block_start = 0usize;
+
+    while block_start < len {
+        let mut bits = match input.get(block_start..block_start + 64) {
+            Some(block) => candidate_mask(block.try_into().unwrap()),
+            None => {
+                // Pad the last partial block with zeros, which are never candidates.
+                let mut block = [0u8; 64];
+                block[..len - block_start].copy_from_slice(&input[block_start..]);
+                candidate_mask(&block)
             }
-            if is_x86_feature_detected!("sse4.2") {
-                super::arch::x86_64::scan_structural_sse42(input, indices);
-                return;
+        };
+        if skip_until > block_start {
+            bits &= u64::MAX << (skip_until - block_start);
+        }
+
+        while bits != 0 {
+            let pos = block_start + bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            let byte = input[pos];
+
+            if byte == b':' && !in_integer {
+                let text = in_text;
+                let binary = !in_text && token_end < pos;
+                in_text = false;
+                let Some(length) = parse_length(&input[token_end..pos]) else {
+                    // Not a length prefix; the input is malformed.
+                    indices.push(O::from_usize(pos));
+                    token_end = pos + 1;
+                    continue;
+                };
+                if binary {
+                    indices.push(O::from_usize(token_end));
+                }
+                indices.push(O::from_usize(pos));
+                skip_until = (pos + 1).saturating_add(length);
+                token_end = skip_until;
+                if text
+                    && let Some(payload) = input.get(pos + 1..skip_until)
+                    && validate_utf8(payload)
+                {
+                    texts.push((O::from_usize(pos + 1), O::from_usize(skip_until)));
+                }
+                if skip_until - block_start >= 64 {
+                    break;
+                }
+                bits &= u64::MAX << (skip_until - block_start);
+                continue;
             }
+
+            indices.push(O::from_usize(pos));
+            token_end = pos + 1;
+            in_text = byte == b'u';
+            in_integer = byte == b'i';
         }
-    }
 
-    #[cfg(target_arch = "aarch64")]
-    {
-        // SAFETY: NEON is always available on AArch64
-        unsafe {
-            super::arch::aarch64::scan_structural_neon(input, indices);
-            return;
+        block_start += 64;
+        if skip_until > block_start {
+            // Jump over the blocks entirely inside the payload.
+            block_start = skip_until & !63;
         }
     }
-
-    // Fallback to scalar implementation
-    #[allow(unreachable_code)]
-    scan_structural_scalar(input, indices);
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::backend::available_backends;
     use super::*;
+    use alloc::format;
+    use alloc::vec;
 
     #[test]
     fn test_build_structural_index_empty() {
@@ -73,46 +243,177 @@ mod tests {
         assert_eq!(index.get(0), Some(0));
     }
 
+    fn positions(input: &[u8]) -> Vec<u32> {
+        build_structural_index(input).indices
+    }
+
     #[test]
     fn test_build_structural_index_integer() {
-        // i42e - positions: i=0, 4=1, 2=2, e=3
-        let index = build_structural_index(b"i42e");
-        assert_eq!(index.len(), 4);
-        assert_eq!(index.get(0), Some(0)); // i
-        assert_eq!(index.get(1), Some(1)); // 4
-        assert_eq!(index.get(2), Some(2)); // 2
-        assert_eq!(index.get(3), Some(3)); // e
+        // i42e - the digits of an integer are not needed to find its end
+        assert_eq!(positions(b"i42e"), vec![0, 3]);
+        assert_eq!(positions(b"i-42e"), vec![0, 4]);
     }
 
     #[test]
     fn test_build_structural_index_string() {
-        // 5:hello - The scanner finds ALL potential structural characters:
-        // Position 0: '5' (digit)
-        // Position 1: ':' (colon)
-        // Position 3: 'e' (matches 'e')
-        // Position 4: 'l' (matches 'l')
-        // Position 5: 'l' (matches 'l')
-        // Note: The parser will ignore chars inside string data
-        let index = build_structural_index(b"5:hello");
-        assert!(index.len() >= 2);
-        assert_eq!(index.get(0), Some(0)); // 5
-        assert_eq!(index.get(1), Some(1)); // :
+        // 5:hello - 'e', 'l' and 'l' in the payload are excluded
+        assert_eq!(positions(b"5:hello"), vec![0, 1]);
+        assert_eq!(positions(b"u5:hello"), vec![0, 2]);
+        assert_eq!(positions(b"12:i1e:u1:dlnte"), vec![0, 2]);
     }
 
     #[test]
     fn test_build_structural_index_list() {
-        // li42en5:helloe - list with integer and string
-        let index = build_structural_index(b"li42en5:helloe");
-        // l=0, i=1, 4=2, 2=3, e=4, n=5, 5=6, :=7, e=13
-        assert!(index.len() >= 9);
+        // li42en5:helloe - l=0, i=1, e=4, n=5, 5=6, :=7, e=13
+        assert_eq!(positions(b"li42en5:helloe"), vec![0, 1, 4, 5, 6, 7, 13]);
     }
 
     #[test]
     fn test_build_structural_index_dict() {
-        // du1:ai42ee - dict with key "a" and value 42
-        let index = build_structural_index(b"du1:ai42ee");
-        // d=0, u=1, 1=2, :=3, i=5, 4=6, 2=7, e=8, e=9
-        assert!(index.len() >= 9);
+        // du1:ai42ee - d=0, u=1, :=3, i=5, e=8, e=9
+        assert_eq!(positions(b"du1:ai42ee"), vec![0, 1, 3, 5, 8, 9]);
+    }
+
+    #[test]
+    fn test_build_structural_index_long_payload() {
+        // A payload spanning many blocks, full of candidates
+        let payload = b"li1ee:".repeat(50);
+        let mut input = format!("l{}:", payload.len()).into_bytes();
+        input.extend_from_slice(&payload);
+        input.extend_from_slice(b"i1ee");
+        let end = input.len() as u32;
+        assert_eq!(positions(&input), vec![0, 1, 4, end - 4, end - 2, end - 1]);
+    }
+
+    #[test]
+    fn test_build_structural_index_payload_ending_at_block_boundary() {
+        for len in 55..=70 {
+            let mut input = format!("l{}:", len).into_bytes();
+            input.resize(input.len() + len, b'e');
+            input.push(b'n');
+            input.push(b'e');
+            let end = input.len() as u32;
+            assert_eq!(
+                positions(&input),
+                vec![0, 1, 3, end - 2, end - 1],
+                "len={}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_structural_index_texts() {
+        // Only valid texts are recorded; binaries are never validated
+        let texts = |input: &[u8]| build_structural_index(input).texts;
+        assert_eq!(texts(b"u5:hello"), vec![(3, 8)]);
+        assert_eq!(
+            texts("lu0:u2:éu3:abce".as_bytes()),
+            vec![(4, 4), (7, 9), (12, 15)]
+        );
+        assert_eq!(texts(b"lu2:\xc3\xa9u1:\xffe"), vec![(4, 6)]);
+        assert_eq!(texts(b"l2:\xc3\xa9e"), vec![]);
+        // A text cut off by the end of the input
+        assert_eq!(texts(b"u5:abc"), vec![]);
+
+        let long = "가나다라마바사아자차카타파하".repeat(10);
+        let input = format!("lu{}:{}u1:ae", long.len(), long);
+        let start = format!("lu{}:", long.len()).len() as u32;
+        let end = start + long.len() as u32;
+        assert_eq!(
+            texts(input.as_bytes()),
+            vec![(start, end), (end + 3, end + 4)]
+        );
+    }
+
+    #[test]
+    fn test_build_structural_index_matches_scalar() {
+        for data in [
+            &include_bytes!("../../../_data/ncavatar_1.bin")[..],
+            &include_bytes!("../../../_data/ncinventory_1.bin")[..],
+        ] {
+            let scalar = build_structural_index_with_backend::<u32>(data, Backend::Scalar);
+            let index = build_structural_index(data);
+            assert_eq!(index.indices, scalar.indices);
+            assert_eq!(index.texts, scalar.texts);
+            for backend in available_backends() {
+                let index = build_structural_index_with_backend::<u32>(data, backend);
+                assert_eq!(index.indices, scalar.indices, "{}", backend);
+                assert_eq!(index.texts, scalar.texts, "{}", backend);
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_structural_index_with_unavailable_backend() {
+        let Some(backend) = Backend::ALL.into_iter().find(|b| !b.is_available()) else {
+            return;
+        };
+        let result =
+            std::panic::catch_unwind(|| build_structural_index_with_backend::<u32>(b"n", backend));
+        assert!(result.is_err());
+    }
+
+    // `Offset` is sealed, but u16 is also `Sealed` in the tests of the crate
+    impl Offset for u16 {
+        const MAX_INPUT_LEN: usize = u16::MAX as usize;
+
+        fn from_usize(pos: usize) -> Self {
+            pos as u16
+        }
+
+        fn to_usize(self) -> usize {
+            self as usize
+        }
+    }
+
+    #[test]
+    fn test_build_structural_index_offsets() {
+        let data = include_bytes!("../../../_data/ncinventory_1.bin");
+        let narrow = build_structural_index(data);
+        let wide = build_structural_index_as::<u64>(data);
+        assert_eq!(
+            narrow.indices.iter().map(|&p| p as u64).collect::<Vec<_>>(),
+            wide.indices
+        );
+        assert_eq!(
+            narrow
+                .texts
+                .iter()
+                .map(|&(s, e)| (s as u64, e as u64))
+                .collect::<Vec<_>>(),
+            wide.texts
+        );
+
+        // Positions up to the last byte of the longest input still fit
+        let mut input = vec![b'n'; u16::MAX as usize];
+        input[0] = b'l';
+        *input.last_mut().unwrap() = b'e';
+        let index = build_structural_index_as::<u16>(&input);
+        assert_eq!(index.get(index.len() - 1), Some(u16::MAX - 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "too long")]
+    fn test_build_structural_index_too_long_for_offset() {
+        // Truncated positions would point into the wrong bytes, so it must not build at all
+        let mut input = vec![b'n'; u16::MAX as usize + 1];
+        input[0] = b'l';
+        build_structural_index_as::<u16>(&input);
+    }
+
+    #[test]
+    fn test_build_structural_index_malformed() {
+        // The index of malformed input is not meaningful, but it must not panic
+        for input in [
+            &b"99999999999999999999999:"[..],
+            b"5:ab",
+            b"u:",
+            b"1x:e",
+            b"i1x2e",
+        ] {
+            build_structural_index(input);
+        }
     }
 
     #[test]
@@ -4,42 +4,264 @@
 //! Bencodex values. The structural index allows skipping over data
 //! portions without scanning byte-by-byte.
 
-use crate::codec::decode::DecodeError;
+use crate::codec::canonical::CanonicalIssueKind;
+use crate::codec::decode::{DecodeError, Observer};
+use crate::codec::dictionary::DictionaryBuilder;
 use crate::codec::types::*;
 use crate::prelude::*;
+use core::ops::Range;
 use core::str;
-use num_bigint::BigInt;
 
-use super::structural::StructuralIndex;
+use super::number::{parse_integer, parse_length, parse_number};
+use super::structural::{Offset, StructuralIndex};
+use super::tape::{Tape, TapeEntry};
+
+/// The key of a binary or text entry, ordered like [`BencodexKey`].
+fn tape_key(input: &[u8], entry: TapeEntry) -> Option<(bool, &[u8])> {
+    match entry {
+        TapeEntry::Binary { start, end } => Some((false, &input[start..end])),
+        TapeEntry::Text { start, end } => Some((true, &input[start..end])),
+        _ => None,
+    }
+}
 
 /// SIMD-accelerated Bencodex parser.
 ///
 /// Uses a pre-built structural index to parse values more efficiently
 /// by jumping directly to structural character positions.
-pub struct SimdParser<'a, 's> {
+///
+/// Non-canonical parts of the input are reported to the [`Observer`] like the scalar decoder
+/// does, so `()` accepts them and `Vec<CanonicalIssue>` collects them.
+///
+/// Only the crate creates parsers, each with the index stage 1 built from the same input:
+///
+/// ```compile_fail
+/// use bencodex::codec::simd::{stage1::build_structural_index, stage2::SimdParser};
+///
+/// let index = build_structural_index(b"u2:ab");
+/// SimdParser::new(b"u2:\xff\xfe", &index).parse();
+/// ```
+pub(crate) struct SimdParser<'a, 's, O: Offset = u32, V: Observer = ()> {
     input: &'a [u8],
-    structural: &'s StructuralIndex,
+    structural: &'s StructuralIndex<O>,
     pos: usize,
     /// Cursor into structural index for efficient lookups
     struct_idx: usize,
+    /// Cursor into the validated text spans of the structural index
+    text_idx: usize,
+    observer: V,
 }
 
-impl<'a, 's> SimdParser<'a, 's> {
+impl<'a, 's, O: Offset> SimdParser<'a, 's, O> {
     /// Create a new SIMD parser with the given input and structural index.
-    pub fn new(input: &'a [u8], structural: &'s StructuralIndex) -> Self {
+    ///
+    /// `structural` must be built by stage 1 from `input`, because texts whose spans it has are
+    /// not validated again.
+    pub(crate) fn new(input: &'a [u8], structural: &'s StructuralIndex<O>) -> Self {
+        Self::with_observer(input, structural, ())
+    }
+
+    /// Create a parser which starts at `pos` of the input instead of the beginning, where
+    /// `struct_idx` is the index of `pos` in the structural index.
+    #[cfg(feature = "rayon")]
+    pub(super) fn starting_at(
+        input: &'a [u8],
+        structural: &'s StructuralIndex<O>,
+        pos: usize,
+        struct_idx: usize,
+    ) -> Self {
+        let text_idx = structural
+            .texts
+            .partition_point(|&(start, _)| start.to_usize() < pos);
+        Self {
+            input,
+            structural,
+            pos,
+            struct_idx,
+            text_idx,
+            observer: (),
+        }
+    }
+}
+
+impl<'a, 's, O: Offset, V: Observer> SimdParser<'a, 's, O, V> {
+    /// Create a parser like [`SimdParser::new`] which reports non-canonical parts to `observer`.
+    pub(crate) fn with_observer(
+        input: &'a [u8],
+        structural: &'s StructuralIndex<O>,
+        observer: V,
+    ) -> Self {
         Self {
             input,
             structural,
             pos: 0,
             struct_idx: 0,
+            text_idx: 0,
+            observer,
         }
     }
 
+    /// Take the observer back.
+    pub(super) fn into_observer(self) -> V {
+        self.observer
+    }
+
+    /// Get the position where the next value starts.
+    pub(super) fn position(&self) -> usize {
+        self.pos
+    }
+
     /// Parse a complete Bencodex value from the input.
     pub fn parse(&mut self) -> Result<BencodexValue<'a>, DecodeError> {
         self.parse_value()
     }
 
+    /// Parse a complete Bencodex value from the input into a [`Tape`].
+    pub fn parse_tape(&mut self) -> Result<Tape<'a>, DecodeError> {
+        let mut entries = Vec::with_capacity(self.structural.len() / 2 + 1);
+        self.tape_value(&mut entries)?;
+        Ok(Tape::new(self.input, entries))
+    }
+
+    /// Push the entries of a single value at the current position to `entries`.
+    fn tape_value(&mut self, entries: &mut Vec<TapeEntry>) -> Result<(), DecodeError> {
+        if self.pos >= self.input.len() {
+            return Err(DecodeError::InvalidBencodexValueError);
+        }
+
+        let entry = match self.input[self.pos] {
+            b'd' => return self.tape_dict(entries),
+            b'l' => return self.tape_list(entries),
+            b'u' => {
+                self.pos += 1;
+                if self.pos >= self.input.len() {
+                    return Err(DecodeError::InvalidBencodexValueError);
+                }
+                let span = self.read_string_span()?;
+                self.read_text(span.clone())?;
+                TapeEntry::Text {
+                    start: span.start,
+                    end: span.end,
+                }
+            }
+            b'i' => {
+                let span = self.read_integer_span()?;
+                let parsed = parse_integer(&self.input[span.clone()])
+                    .ok_or(DecodeError::InvalidBencodexValueError)?;
+                if !parsed.canonical {
+                    self.observer
+                        .observe(CanonicalIssueKind::NonCanonicalNumber, span.start)?;
+                }
+                TapeEntry::Number {
+                    start: span.start,
+                    end: span.end,
+                }
+            }
+            b'0'..=b'9' => {
+                let span = self.read_string_span()?;
+                TapeEntry::Binary {
+                    start: span.start,
+                    end: span.end,
+                }
+            }
+            b't' => {
+                self.pos += 1;
+                TapeEntry::Boolean(true)
+            }
+            b'f' => {
+                self.pos += 1;
+                TapeEntry::Boolean(false)
+            }
+            b'n' => {
+                self.pos += 1;
+                TapeEntry::Null
+            }
+            token => {
+                return Err(DecodeError::UnexpectedTokenError {
+                    token,
+                    point: self.pos,
+                });
+            }
+        };
+        entries.push(entry);
+        Ok(())
+    }
+
+    /// Push the entries of a list: l...e
+    fn tape_list(&mut self, entries: &mut Vec<TapeEntry>) -> Result<(), DecodeError> {
+        self.expect(b'l')?;
+        self.pos += 1;
+
+        let index = entries.len();
+        entries.push(TapeEntry::List { len: 0, end: 0 });
+        let mut len = 0;
+        while self.pos < self.input.len() && self.input[self.pos] != b'e' {
+            self.tape_value(entries)?;
+            len += 1;
+        }
+
+        self.expect(b'e')?;
+        self.pos += 1;
+
+        entries[index] = TapeEntry::List {
+            len,
+            end: entries.len(),
+        };
+        Ok(())
+    }
+
+    /// Push the entries of a dictionary: d...e
+    fn tape_dict(&mut self, entries: &mut Vec<TapeEntry>) -> Result<(), DecodeError> {
+        self.expect(b'd')?;
+        self.pos += 1;
+
+        let index = entries.len();
+        entries.push(TapeEntry::Dictionary { len: 0, end: 0 });
+        let mut len = 0;
+        // Canonical input has keys in order, so duplicates are found by comparing each key with
+        // the one before it. Otherwise, all keys are sorted at the end.
+        let mut previous_key = None;
+        let mut ordered = true;
+        while self.pos < self.input.len() && self.input[self.pos] != b'e' {
+            let key_index = entries.len();
+            self.tape_value(entries)?;
+            let key = tape_key(self.input, entries[key_index])
+                .ok_or(DecodeError::InvalidBencodexValueError)?;
+            if ordered && previous_key.is_some_and(|previous| previous >= key) {
+                ordered = false;
+            }
+            previous_key = Some(key);
+
+            self.tape_value(entries)?;
+            len += 1;
+        }
+
+        self.expect(b'e')?;
+        self.pos += 1;
+
+        let end = entries.len();
+        entries[index] = TapeEntry::Dictionary { len, end };
+        if !ordered {
+            let mut keys = Vec::with_capacity(len);
+            let mut next = index + 1;
+            while next < end {
+                keys.push(tape_key(self.input, entries[next]).unwrap());
+                // Skip the key and the value
+                next += 1;
+                next = match entries[next] {
+                    TapeEntry::List { end, .. } | TapeEntry::Dictionary { end, .. } => end,
+                    _ => next + 1,
+                };
+            }
+            keys.sort_unstable();
+            // Reject duplicated keys like the scalar decoder
+            if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+                return Err(DecodeError::InvalidBencodexValueError);
+            }
+        }
+        Ok(())
+    }
+
     /// Parse a single value at the current position.
     fn parse_value(&mut self) -> Result<BencodexValue<'a>, DecodeError> {
         if self.pos >= self.input.len() {
@@ -76,29 +298,45 @@ impl<'a, 's> SimdParser<'a, 's> {
         self.expect(b'd')?;
         self.pos += 1;
 
-        let mut map = BTreeMap::new();
+        let mut map = DictionaryBuilder::default();
 
         while self.pos < self.input.len() && self.input[self.pos] != b'e' {
-            // Parse key (must be binary or text string)
-            let key_value = self.parse_value()?;
-            let key = match key_value {
-                BencodexValue::Text(s) => BencodexKey::Text(s),
-                BencodexValue::Binary(b) => BencodexKey::Binary(b),
-                _ => return Err(DecodeError::InvalidBencodexValueError),
-            };
+            let key_point = self.pos;
+            let key = self.parse_key()?;
+            if V::CHECK_KEY_ORDER && map.max_key().is_some_and(|max| key < *max) {
+                self.observer
+                    .observe(CanonicalIssueKind::UnorderedKey, key_point)?;
+            }
 
             // Parse value
             let value = self.parse_value()?;
 
-            map.insert(key, value);
+            if let Some(point) = map.push(key, value, key_point) {
+                self.observer
+                    .observe(CanonicalIssueKind::DuplicatedKey, point)?;
+            }
         }
 
         self.expect(b'e')?;
         self.pos += 1;
 
+        let (map, duplicates) = map.finish();
+        for point in duplicates {
+            self.observer
+                .observe(CanonicalIssueKind::DuplicatedKey, point)?;
+        }
         Ok(BencodexValue::Dictionary(map))
     }
 
+    /// Parse a dictionary key, which must be a binary or a text.
+    pub(super) fn parse_key(&mut self) -> Result<BencodexKey<'a>, DecodeError> {
+        match self.parse_value()? {
+            BencodexValue::Text(s) => Ok(BencodexKey::Text(s)),
+            BencodexValue::Binary(b) => Ok(BencodexKey::Binary(b)),
+            _ => Err(DecodeError::InvalidBencodexValueError),
+        }
+    }
+
     /// Parse a list: l...e
     fn parse_list(&mut self) -> Result<BencodexValue<'a>, DecodeError> {
         self.expect(b'l')?;
@@ -119,30 +357,8 @@ impl<'a, 's> SimdParser<'a, 's> {
 
     /// Parse a byte string: length:data
     fn parse_byte_string(&mut self) -> Result<BencodexValue<'a>, DecodeError> {
-        // Find ':' using structural index
-        let colon_pos = self
-            .find_next_structural(b':')
-            .ok_or(DecodeError::InvalidBencodexValueError)?;
-
-        // Parse length from current position to colon
-        let length_slice = &self.input[self.pos..colon_pos];
-        let length_str =
-            str::from_utf8(length_slice).map_err(|_| DecodeError::InvalidBencodexValueError)?;
-        let length: usize = length_str
-            .parse()
-            .map_err(|_| DecodeError::InvalidBencodexValueError)?;
-
-        self.pos = colon_pos + 1;
-
-        // Read data
-        if self.pos + length > self.input.len() {
-            return Err(DecodeError::InvalidBencodexValueError);
-        }
-
-        let data = &self.input[self.pos..self.pos + length];
-        self.pos += length;
-
-        Ok(BencodexValue::Binary(Cow::Borrowed(data)))
+        let span = self.read_string_span()?;
+        Ok(BencodexValue::Binary(Cow::Borrowed(&self.input[span])))
     }
 
     /// Parse a unicode string: ulength:data
@@ -154,35 +370,87 @@ impl<'a, 's> SimdParser<'a, 's> {
             return Err(DecodeError::InvalidBencodexValueError);
         }
 
+        let span = self.read_string_span()?;
+        Ok(BencodexValue::Text(Cow::Borrowed(self.read_text(span)?)))
+    }
+
+    /// Read the length at the current position and skip the payload after it, and return the
+    /// span of the payload.
+    fn read_string_span(&mut self) -> Result<Range<usize>, DecodeError> {
         // Find ':' using structural index
         let colon_pos = self
             .find_next_structural(b':')
             .ok_or(DecodeError::InvalidBencodexValueError)?;
 
         // Parse length from current position to colon
-        let length_slice = &self.input[self.pos..colon_pos];
-        let length_str =
-            str::from_utf8(length_slice).map_err(|_| DecodeError::InvalidBencodexValueError)?;
-        let length: usize = length_str
-            .parse()
-            .map_err(|_| DecodeError::InvalidBencodexValueError)?;
+        let length = parse_length(&self.input[self.pos..colon_pos])
+            .ok_or(DecodeError::InvalidBencodexValueError)?;
+        if !length.canonical {
+            self.observer
+                .observe(CanonicalIssueKind::LeadingZeroLength, self.pos)?;
+        }
+        let length = length.value;
 
-        self.pos = colon_pos + 1;
+        let start = colon_pos + 1;
+        let end = start
+            .checked_add(length)
+            .filter(|&end| end <= self.input.len())
+            .ok_or(DecodeError::InvalidBencodexValueError)?;
+        self.pos = end;
+        Ok(start..end)
+    }
 
-        // Read data
-        if self.pos + length > self.input.len() {
-            return Err(DecodeError::InvalidBencodexValueError);
+    /// Get the payload of a text at `span` as a string.
+    fn read_text(&mut self, span: Range<usize>) -> Result<&'a str, DecodeError> {
+        let validated = self.is_validated_text(span.start, span.end);
+        let data = &self.input[span];
+        if validated {
+            // SAFETY: Parsers are only created with the index stage 1 built from this input,
+            // which records a span only after validating its bytes as UTF-8
+            Ok(unsafe { str::from_utf8_unchecked(data) })
+        } else {
+            str::from_utf8(data).map_err(|_| DecodeError::InvalidBencodexValueError)
         }
+    }
 
-        let text = str::from_utf8(&self.input[self.pos..self.pos + length])
-            .map_err(|_| DecodeError::InvalidBencodexValueError)?;
-        self.pos += length;
-
-        Ok(BencodexValue::Text(Cow::Borrowed(text)))
+    /// Check if stage 1 validated exactly `start..end` as UTF-8.
+    ///
+    /// Texts are parsed in order, so the cursor only moves forward.
+    #[inline]
+    fn is_validated_text(&mut self, start: usize, end: usize) -> bool {
+        let texts = &self.structural.texts;
+        while texts
+            .get(self.text_idx)
+            .is_some_and(|&(s, _)| s.to_usize() < start)
+        {
+            self.text_idx += 1;
+        }
+        texts
+            .get(self.text_idx)
+            .is_some_and(|&(s, e)| s.to_usize() == start && e.to_usize() == end)
     }
 
     /// Parse an integer: i...e
     fn parse_integer(&mut self) -> Result<BencodexValue<'a>, DecodeError> {
+        let span = self.read_integer_span()?;
+
+        // Parse number between i and e
+        let digits = &self.input[span.clone()];
+        let parsed = parse_integer(digits).ok_or(DecodeError::InvalidBencodexValueError)?;
+        if !parsed.canonical {
+            self.observer
+                .observe(CanonicalIssueKind::NonCanonicalNumber, span.start)?;
+        }
+        let number = match parsed.value {
+            Some(n) => n.into(),
+            None => parse_number(digits).ok_or(DecodeError::InvalidBencodexValueError)?,
+        };
+
+        Ok(BencodexValue::Number(number))
+    }
+
+    /// Skip an integer at the current position, and return the span of its digits.
+    fn read_integer_span(&mut self) -> Result<Range<usize>, DecodeError> {
         self.expect(b'i')?;
         self.pos += 1;
 
@@ -195,16 +463,9 @@ impl<'a, 's> SimdParser<'a, 's> {
             .find_next_structural(b'e')
             .ok_or(DecodeError::InvalidBencodexValueError)?;
 
-        // Parse number between i and e
-        let num_slice = &self.input[self.pos..e_pos];
-        let num_str =
-            str::from_utf8(num_slice).map_err(|_| DecodeError::InvalidBencodexValueError)?;
-        let number = num_str
-            .parse::<BigInt>()
-            .map_err(|_| DecodeError::InvalidBencodexValueError)?;
-
+        let start = self.pos;
         self.pos = e_pos + 1;
-        Ok(BencodexValue::Number(number))
+        Ok(start..e_pos)
     }
 
     /// Expect a specific byte at the current position.
@@ -223,9 +484,12 @@ impl<'a, 's> SimdParser<'a, 's> {
 
     /// Find next occurrence of `byte` in structural index at or after current position.
     /// Advances struct_idx cursor past the found position.
+    ///
+    /// The index has no positions inside string payloads, so for valid input the cursor only
+    /// passes the positions of values which are already parsed.
     fn find_next_structural(&mut self, byte: u8) -> Option<usize> {
         while self.struct_idx < self.structural.len() {
-            let pos = self.structural.get(self.struct_idx)? as usize;
+            let pos = self.structural.get(self.struct_idx)?.to_usize();
             self.struct_idx += 1;
 
             // Skip positions before current parse position
@@ -240,16 +504,6 @@ impl<'a, 's> SimdParser<'a, 's> {
         }
         None
     }
-
-    /// Reset struct_idx cursor to search from a specific position.
-    /// Uses binary search to find first index >= from_pos.
-    #[allow(dead_code)]
-    fn reset_structural_cursor(&mut self, from_pos: usize) {
-        self.struct_idx = self
-            .structural
-            .indices
-            .partition_point(|&p| (p as usize) < from_pos);
-    }
 }
 
 #[cfg(test)]
@@ -280,18 +534,12 @@ mod tests {
 
     #[test]
     fn test_parse_integer() {
-        assert_eq!(
-            parse(b"i42e").unwrap(),
-            BencodexValue::Number(BigInt::from(42))
-        );
+        assert_eq!(parse(b"i42e").unwrap(), BencodexValue::Number(42.into()));
         assert_eq!(
             parse(b"i-123e").unwrap(),
-            BencodexValue::Number(BigInt::from(-123))
-        );
-        assert_eq!(
-            parse(b"i0e").unwrap(),
-            BencodexValue::Number(BigInt::from(0))
+            BencodexValue::Number((-123).into())
         );
+        assert_eq!(parse(b"i0e").unwrap(), BencodexValue::Number(0.into()));
     }
 
     #[test]
@@ -316,6 +564,25 @@ mod tests {
             parse(b"u0:").unwrap(),
             BencodexValue::Text(Cow::Borrowed(""))
         );
+        assert!(parse(b"u2:\xff\xfe").is_err());
+        assert!(parse(b"lu2:\xff\xfee").is_err());
+    }
+
+    #[test]
+    fn test_parse_non_canonical_lengths() {
+        // Accepted like the scalar decoder
+        assert_eq!(
+            parse(b"u005:hello").unwrap(),
+            BencodexValue::Text(Cow::Borrowed("hello"))
+        );
+        assert_eq!(
+            parse(b"0000000000000000000001:a").unwrap(),
+            BencodexValue::Binary(Cow::Borrowed(b"a".as_slice()))
+        );
+        // Unlike `usize::from_str`, a sign is not a digit
+        assert!(parse(b"u+5:hello").is_err());
+        assert!(parse(b"u-5:hello").is_err());
+        assert!(parse(b"99999999999999999999:").is_err());
     }
 
     #[test]
@@ -325,7 +592,7 @@ mod tests {
         let result = parse(b"li42eu5:helloe").unwrap();
         if let BencodexValue::List(items) = result {
             assert_eq!(items.len(), 2);
-            assert_eq!(items[0], BencodexValue::Number(BigInt::from(42)));
+            assert_eq!(items[0], BencodexValue::Number(42.into()));
             assert_eq!(items[1], BencodexValue::Text(Cow::Borrowed("hello")));
         } else {
             panic!("Expected list");
@@ -336,7 +603,7 @@ mod tests {
     fn test_parse_dict() {
         assert_eq!(
             parse(b"de").unwrap(),
-            BencodexValue::Dictionary(BTreeMap::new())
+            BencodexValue::Dictionary(BencodexDictionary::new())
         );
 
         let result = parse(b"du1:ai42ee").unwrap();
@@ -344,13 +611,18 @@ mod tests {
             assert_eq!(map.len(), 1);
             assert_eq!(
                 map.get(&BencodexKey::Text(Cow::Borrowed("a"))),
-                Some(&BencodexValue::Number(BigInt::from(42)))
+                Some(&BencodexValue::Number(42.into()))
             );
         } else {
             panic!("Expected dictionary");
         }
     }
 
+    #[test]
+    fn test_parse_dict_duplicated_key() {
+        assert!(parse(b"du1:ai1eu1:ai2ee").is_err());
+    }
+
     #[test]
     fn test_parse_nested() {
         // Nested list: [[1, 2], [3]]
@@ -2,25 +2,127 @@
 //!
 //! The structural index contains positions of all structural characters
 //! in the input, allowing the parser to jump directly to relevant positions
-//! rather than scanning byte-by-byte.
+//! rather than scanning byte-by-byte. Bytes inside binary and text payloads
+//! are never in the index.
+//!
+//! The index also has the spans of the texts stage 1 validated as UTF-8, so
+//! stage 2 does not validate them again.
+//!
+//! Positions are stored as an [`Offset`], `u32` by default, which halves the
+//! size of the index compared to `u64` but limits the input to 4 GiB. Inputs
+//! longer than that need a `u64` index.
+
+use core::fmt::Debug;
+
+mod sealed {
+    /// Keeps [`Offset`](super::Offset) from being implemented outside the crate, since stage 2
+    /// trusts the positions it converts back.
+    pub trait Sealed {}
+
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+
+    /// Lets the tests of stage 1 check the length limit with short inputs.
+    #[cfg(test)]
+    impl Sealed for u16 {}
+}
+
+/// The integer type of the positions in a [`StructuralIndex`], which is either `u32` or `u64`.
+///
+/// This trait is sealed and cannot be implemented outside this crate:
+///
+/// ```compile_fail
+/// use bencodex::codec::simd::structural::Offset;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+/// struct Position(u32);
+///
+/// impl Offset for Position {
+///     const MAX_INPUT_LEN: usize = u32::MAX as usize;
+///
+///     fn from_usize(pos: usize) -> Self {
+///         Position(pos as u32)
+///     }
+///
+///     fn to_usize(self) -> usize {
+///         self.0 as usize
+///     }
+/// }
+/// ```
+pub trait Offset: sealed::Sealed + Copy + Ord + Debug + Send + Sync + 'static {
+    /// The length of the longest input whose positions fit in this type.
+    const MAX_INPUT_LEN: usize;
+
+    /// Convert a position into an offset. `pos` must not be over [`Offset::MAX_INPUT_LEN`].
+    fn from_usize(pos: usize) -> Self;
+
+    /// Convert an offset back into a position.
+    fn to_usize(self) -> usize;
+}
+
+impl Offset for u32 {
+    const MAX_INPUT_LEN: usize = if usize::BITS > u32::BITS {
+        u32::MAX as usize
+    } else {
+        usize::MAX
+    };
+
+    #[inline(always)]
+    fn from_usize(pos: usize) -> Self {
+        debug_assert!(pos <= Self::MAX_INPUT_LEN);
+        pos as u32
+    }
+
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl Offset for u64 {
+    const MAX_INPUT_LEN: usize = usize::MAX;
+
+    #[inline(always)]
+    fn from_usize(pos: usize) -> Self {
+        pos as u64
+    }
+
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        // Offsets are built from positions, so they always fit in usize
+        self as usize
+    }
+}
 
 /// Index of structural character positions in the input.
 ///
 /// Structural characters in Bencodex include:
 /// - Type markers: `n`, `t`, `f`, `i`, `l`, `d`, `u`
 /// - Delimiters: `:`, `e`
-/// - Digits: `0-9`
+/// - The first digit of the length of a binary, which is where the binary starts
+///
+/// Stage 2 trusts the text spans without validating them again, so only stage 1 fills them:
+///
+/// ```compile_fail
+/// use bencodex::codec::simd::structural::StructuralIndex;
+///
+/// let mut index = StructuralIndex::<u32>::new();
+/// index.texts.push((3, 5));
+/// ```
 #[derive(Debug, Clone)]
-pub struct StructuralIndex {
+pub struct StructuralIndex<O: Offset = u32> {
     /// Sorted list of positions where structural characters appear
-    pub indices: Vec<u32>,
+    pub(crate) indices: Vec<O>,
+    /// Sorted list of `(start, end)` spans of text payloads which are valid UTF-8
+    pub(crate) texts: Vec<(O, O)>,
 }
 
-impl StructuralIndex {
+impl<O: Offset> StructuralIndex<O> {
     /// Create a new empty structural index
     pub fn new() -> Self {
         Self {
             indices: Vec::new(),
+            texts: Vec::new(),
         }
     }
 
@@ -28,6 +130,7 @@ impl StructuralIndex {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             indices: Vec::with_capacity(capacity),
+            texts: Vec::new(),
         }
     }
 
@@ -43,15 +146,27 @@ impl StructuralIndex {
         self.indices.is_empty()
     }
 
+    /// Get the sorted positions where structural characters appear
+    #[inline]
+    pub fn indices(&self) -> &[O] {
+        &self.indices
+    }
+
+    /// Get the sorted `(start, end)` spans of text payloads which stage 1 validated as UTF-8
+    #[inline]
+    pub fn texts(&self) -> &[(O, O)] {
+        &self.texts
+    }
+
     /// Get position at given index
     #[inline]
-    pub fn get(&self, idx: usize) -> Option<u32> {
+    pub fn get(&self, idx: usize) -> Option<O> {
         self.indices.get(idx).copied()
     }
 
     /// Add a position to the index
     #[inline]
-    pub fn push(&mut self, pos: u32) {
+    pub fn push(&mut self, pos: O) {
         self.indices.push(pos);
     }
 
@@ -59,10 +174,11 @@ impl StructuralIndex {
     #[inline]
     pub fn clear(&mut self) {
         self.indices.clear();
+        self.texts.clear();
     }
 }
 
-impl Default for StructuralIndex {
+impl<O: Offset> Default for StructuralIndex<O> {
     fn default() -> Self {
         Self::new()
     }
@@ -0,0 +1,524 @@
+//! A flat, read-only representation of a decoded value.
+//!
+//! Instead of nested lists and dictionaries, a [`Tape`] is a flat array of [`TapeEntry`], one for
+//! each value in the order they appear in the input. A list or a dictionary is followed by the
+//! entries of its items, and knows where they end, so a whole subtree is skipped in one step.
+//! Numbers, binaries and texts point into the input, and are converted only when they are read.
+//!
+//! Building a tape allocates only the entries, so it is much faster than building a
+//! [`BencodexValue`] for consumers which only read a few parts of a large value.
+//!
+//! ```
+//! use bencodex::{BencodexKey, BencodexValue};
+//! use bencodex::simd::tape::decode_tape;
+//!
+//! let tape = decode_tape(b"du4:nameu3:foou4:tagslu1:au1:bee").unwrap();
+//! let root = tape.root();
+//! assert_eq!(root.get(&"name".into()).unwrap().as_text(), Some("foo"));
+//!
+//! let tags = root.get(&"tags".into()).unwrap();
+//! let tags: Vec<&str> = tags.items().unwrap().filter_map(|tag| tag.as_text()).collect();
+//! assert_eq!(tags, vec!["a", "b"]);
+//!
+//! assert_eq!(root.to_value(), bencodex::simd::decode_simd(b"du4:nameu3:foou4:tagslu1:au1:bee").unwrap());
+//! ```
+
+use core::str;
+
+use super::number;
+use super::stage1::build_structural_index_as;
+use super::stage2::SimdParser;
+use super::structural::Offset;
+use crate::codec::decode::DecodeError;
+use crate::codec::dictionary::DictionaryBuilder;
+use crate::codec::types::*;
+use crate::prelude::*;
+
+/// An entry of a [`Tape`].
+///
+/// Spans are byte offsets into the input, and `end`s of containers are indices into the tape.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum TapeEntry {
+    Null,
+    Boolean(bool),
+    /// The digits of a number, with its sign.
+    Number {
+        start: usize,
+        end: usize,
+    },
+    /// The payload of a binary.
+    Binary {
+        start: usize,
+        end: usize,
+    },
+    /// The payload of a text, which is valid UTF-8.
+    Text {
+        start: usize,
+        end: usize,
+    },
+    /// A list of `len` items, whose entries end before `end`.
+    List {
+        len: usize,
+        end: usize,
+    },
+    /// A dictionary of `len` pairs, whose entries alternate between keys and values and end
+    /// before `end`. Keys are in the order of the input, without duplicates.
+    Dictionary {
+        len: usize,
+        end: usize,
+    },
+}
+
+/// A decoded value as a flat array of [`TapeEntry`], returned by [`decode_tape`].
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Tape<'a> {
+    input: &'a [u8],
+    entries: Vec<TapeEntry>,
+}
+
+impl<'a> Tape<'a> {
+    pub(super) fn new(input: &'a [u8], entries: Vec<TapeEntry>) -> Self {
+        Self { input, entries }
+    }
+
+    /// Get the entries of the tape. The first one is the root.
+    pub fn entries(&self) -> &[TapeEntry] {
+        &self.entries
+    }
+
+    /// Get the root value.
+    pub fn root(&self) -> TapeNode<'_, 'a> {
+        TapeNode {
+            tape: self,
+            index: 0,
+        }
+    }
+
+    /// Get the value at `index` of the tape, or `None` if it is out of range.
+    pub fn node(&self, index: usize) -> Option<TapeNode<'_, 'a>> {
+        (index < self.entries.len()).then_some(TapeNode { tape: self, index })
+    }
+
+    /// Convert the whole tape into a [`BencodexValue`], borrowing from the input.
+    pub fn to_value(&self) -> BencodexValue<'a> {
+        self.root().to_value()
+    }
+}
+
+impl<'a> From<&Tape<'a>> for BencodexValue<'a> {
+    fn from(tape: &Tape<'a>) -> Self {
+        tape.to_value()
+    }
+}
+
+/// A value in a [`Tape`].
+#[derive(Debug, Clone, Copy)]
+pub struct TapeNode<'t, 'a> {
+    tape: &'t Tape<'a>,
+    index: usize,
+}
+
+impl PartialEq for TapeNode<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.tape, other.tape) && self.index == other.index
+    }
+}
+
+impl Eq for TapeNode<'_, '_> {}
+
+impl<'t, 'a> TapeNode<'t, 'a> {
+    /// Get the index of this value in the tape.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the index after this value and all values in it, i.e., of the next sibling if any.
+    pub fn end(&self) -> usize {
+        match self.entry() {
+            TapeEntry::List { end, .. } | TapeEntry::Dictionary { end, .. } => end,
+            _ => self.index + 1,
+        }
+    }
+
+    /// Get the next value after this one and all values in it, if any.
+    pub fn skip(&self) -> Option<TapeNode<'t, 'a>> {
+        self.tape.node(self.end())
+    }
+
+    /// Get the entry of this value.
+    pub fn entry(&self) -> TapeEntry {
+        self.tape.entries[self.index]
+    }
+
+    /// Get the type of this value.
+    pub fn kind(&self) -> BencodexType {
+        match self.entry() {
+            TapeEntry::Null => BencodexType::Null,
+            TapeEntry::Boolean(_) => BencodexType::Boolean,
+            TapeEntry::Number { .. } => BencodexType::Number,
+            TapeEntry::Binary { .. } => BencodexType::Binary,
+            TapeEntry::Text { .. } => BencodexType::Text,
+            TapeEntry::List { .. } => BencodexType::List,
+            TapeEntry::Dictionary { .. } => BencodexType::Dictionary,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.entry() == TapeEntry::Null
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.entry() {
+            TapeEntry::Boolean(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Get a number, or `None` if this is not a number.
+    pub fn as_number(&self) -> Option<BencodexNumber> {
+        match self.entry() {
+            TapeEntry::Number { start, end } => Some(parse_number(&self.tape.input[start..end])),
+            _ => None,
+        }
+    }
+
+    /// Get a number without a [`BencodexNumber`], or `None` if this is not a number or it does
+    /// not fit in `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.entry() {
+            TapeEntry::Number { start, end } => {
+                number::parse_integer(&self.tape.input[start..end])?.value
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_binary(&self) -> Option<&'a [u8]> {
+        match self.entry() {
+            TapeEntry::Binary { start, end } => Some(&self.tape.input[start..end]),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&'a str> {
+        match self.entry() {
+            TapeEntry::Text { start, end } => Some(text(self.tape.input, start, end)),
+            _ => None,
+        }
+    }
+
+    /// Get a binary or a text as a dictionary key.
+    pub fn as_key(&self) -> Option<BencodexKey<'a>> {
+        match self.entry() {
+            TapeEntry::Binary { start, end } => Some(BencodexKey::Binary(Cow::Borrowed(
+                &self.tape.input[start..end],
+            ))),
+            TapeEntry::Text { start, end } => Some(BencodexKey::Text(Cow::Borrowed(text(
+                self.tape.input,
+                start,
+                end,
+            )))),
+            _ => None,
+        }
+    }
+
+    /// Get the number of items of a list or pairs of a dictionary, or `None` for other types.
+    pub fn len(&self) -> Option<usize> {
+        match self.entry() {
+            TapeEntry::List { len, .. } | TapeEntry::Dictionary { len, .. } => Some(len),
+            _ => None,
+        }
+    }
+
+    /// Check if a list or a dictionary is empty, or return `None` for other types.
+    pub fn is_empty(&self) -> Option<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Iterate the items of a list, or return `None` if this is not a list.
+    pub fn items(&self) -> Option<Children<'t, 'a>> {
+        match self.entry() {
+            TapeEntry::List { end, .. } => Some(self.children(end)),
+            _ => None,
+        }
+    }
+
+    /// Iterate the keys and values of a dictionary in the order of the input, or return `None`
+    /// if this is not a dictionary.
+    pub fn pairs(&self) -> Option<Pairs<'t, 'a>> {
+        match self.entry() {
+            TapeEntry::Dictionary { end, .. } => Some(Pairs {
+                children: self.children(end),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Look up `key` in a dictionary, skipping the values of other keys. It returns `None` if
+    /// this is not a dictionary or it does not have `key`.
+    pub fn get(&self, key: &BencodexKey<'_>) -> Option<TapeNode<'t, 'a>> {
+        let (is_text, wanted) = match key {
+            BencodexKey::Binary(b) => (false, b.as_ref()),
+            BencodexKey::Text(t) => (true, t.as_bytes()),
+        };
+        self.pairs()?.find_map(|(k, v)| {
+            let found = match k.entry() {
+                TapeEntry::Binary { start, end } => {
+                    !is_text && &self.tape.input[start..end] == wanted
+                }
+                TapeEntry::Text { start, end } => is_text && &self.tape.input[start..end] == wanted,
+                _ => false,
+            };
+            found.then_some(v)
+        })
+    }
+
+    /// Convert this value and all values in it into a [`BencodexValue`], borrowing from the
+    /// input.
+    pub fn to_value(&self) -> BencodexValue<'a> {
+        match self.entry() {
+            TapeEntry::Null => BencodexValue::Null,
+            TapeEntry::Boolean(b) => BencodexValue::Boolean(b),
+            TapeEntry::Number { start, end } => {
+                BencodexValue::Number(parse_number(&self.tape.input[start..end]))
+            }
+            TapeEntry::Binary { start, end } => {
+                BencodexValue::Binary(Cow::Borrowed(&self.tape.input[start..end]))
+            }
+            TapeEntry::Text { start, end } => {
+                BencodexValue::Text(Cow::Borrowed(text(self.tape.input, start, end)))
+            }
+            TapeEntry::List { end, len } => {
+                let mut list = Vec::with_capacity(len);
+                list.extend(self.children(end).map(|item| item.to_value()));
+                BencodexValue::List(list)
+            }
+            TapeEntry::Dictionary { end, .. } => {
+                let mut map = DictionaryBuilder::default();
+                for (key, value) in (Pairs {
+                    children: self.children(end),
+                }) {
+                    // Keys are checked to be binaries or texts, and not to be duplicated, while
+                    // building the tape
+                    map.push(key.as_key().unwrap(), value.to_value(), ());
+                }
+                BencodexValue::Dictionary(map.finish().0)
+            }
+        }
+    }
+
+    fn children(&self, end: usize) -> Children<'t, 'a> {
+        Children {
+            tape: self.tape,
+            next: self.index + 1,
+            end,
+        }
+    }
+}
+
+impl<'a> From<TapeNode<'_, 'a>> for BencodexValue<'a> {
+    fn from(node: TapeNode<'_, 'a>) -> Self {
+        node.to_value()
+    }
+}
+
+/// An iterator over the items of a list in a [`Tape`], returned by [`TapeNode::items`].
+#[derive(Debug, Clone)]
+pub struct Children<'t, 'a> {
+    tape: &'t Tape<'a>,
+    next: usize,
+    end: usize,
+}
+
+impl<'t, 'a> Iterator for Children<'t, 'a> {
+    type Item = TapeNode<'t, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let node = TapeNode {
+            tape: self.tape,
+            index: self.next,
+        };
+        self.next = node.end();
+        Some(node)
+    }
+}
+
+/// An iterator over the keys and values of a dictionary in a [`Tape`], returned by
+/// [`TapeNode::pairs`].
+#[derive(Debug, Clone)]
+pub struct Pairs<'t, 'a> {
+    children: Children<'t, 'a>,
+}
+
+impl<'t, 'a> Iterator for Pairs<'t, 'a> {
+    type Item = (TapeNode<'t, 'a>, TapeNode<'t, 'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((self.children.next()?, self.children.next()?))
+    }
+}
+
+/// Get a text span which is validated while building the tape.
+fn text(input: &[u8], start: usize, end: usize) -> &str {
+    // SAFETY: Tapes are only built by `SimdParser::parse_tape`, which pushes a text entry only
+    // after its payload is validated as UTF-8 by stage 1 of the same input or by itself
+    unsafe { str::from_utf8_unchecked(&input[start..end]) }
+}
+
+/// Parse the digits of a number entry, which are validated while building the tape.
+fn parse_number(digits: &[u8]) -> BencodexNumber {
+    number::parse_number(digits).unwrap()
+}
+
+/// Decode `input` into a [`Tape`] using the best available SIMD implementation.
+///
+/// It accepts the same input as [`decode_simd`](super::decode_simd), and [`Tape::to_value`]
+/// returns the same value.
+pub fn decode_tape(input: &[u8]) -> Result<Tape<'_>, DecodeError> {
+    if input.len() > u32::MAX_INPUT_LEN {
+        return decode_tape_as::<u64>(input);
+    }
+    decode_tape_as::<u32>(input)
+}
+
+fn decode_tape_as<O: Offset>(input: &[u8]) -> Result<Tape<'_>, DecodeError> {
+    let structural_index = build_structural_index_as::<O>(input);
+    let mut parser = SimdParser::new(input, &structural_index);
+    parser.parse_tape()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::simd::decode_simd;
+    use alloc::vec;
+
+    const DATA: [&[u8]; 2] = [
+        include_bytes!("../../../_data/ncavatar_1.bin"),
+        include_bytes!("../../../_data/ncinventory_1.bin"),
+    ];
+
+    #[test]
+    fn test_decode_tape_same_as_decode_simd() {
+        for input in [
+            &b"n"[..],
+            b"t",
+            b"i-42e",
+            b"i123456789012345678901234567890e",
+            b"5:hello",
+            "u6:한글".as_bytes(),
+            b"le",
+            b"de",
+            b"lli1ei2eeli3eee",
+            b"d1:ai1eu1:ai2e1:bi3ee",
+            b"d1:bi1e1:ai2ee",
+            DATA[0],
+            DATA[1],
+        ] {
+            assert_eq!(
+                decode_simd(input).unwrap(),
+                decode_tape(input).unwrap().to_value(),
+                "input: {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_tape_entries() {
+        let tape = decode_tape(b"lli1ei2een5:helloe").unwrap();
+        assert_eq!(
+            tape.entries(),
+            &[
+                TapeEntry::List { len: 3, end: 6 },
+                TapeEntry::List { len: 2, end: 4 },
+                TapeEntry::Number { start: 3, end: 4 },
+                TapeEntry::Number { start: 6, end: 7 },
+                TapeEntry::Null,
+                TapeEntry::Binary { start: 12, end: 17 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_tape_invalid() {
+        for input in [
+            &b""[..],
+            b"x",
+            b"i42",
+            b"i4x2e",
+            b"i-e",
+            b"5:abc",
+            b"u2:\xc3\x28",
+            b"lu2:\xff\xfee",
+            b"du1:au2:\xff\xfee",
+            b"li1e",
+            b"di1ei2ee",
+            b"du1:ai1e",
+            b"d1:ai1e1:ai2ee",
+            b"d1:bi1e1:ai2e1:bi3ee",
+        ] {
+            assert!(decode_tape(input).is_err(), "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_tape_node_navigation() {
+        let tape = decode_tape(b"d1:xi0eu1:ali1eli2ei3eeeu1:bu2:hie").unwrap();
+        let root = tape.root();
+        assert_eq!(root.kind(), BencodexType::Dictionary);
+        assert_eq!(root.len(), Some(3));
+        assert_eq!(root.is_empty(), Some(false));
+        assert_eq!(root.end(), tape.entries().len());
+        assert_eq!(root.skip(), None);
+
+        let a = root.get(&"a".into()).unwrap();
+        assert_eq!(a.len(), Some(2));
+        let items: Vec<_> = a.items().unwrap().collect();
+        assert_eq!(items[0].as_i64(), Some(1));
+        assert_eq!(items[0].skip(), Some(items[1]));
+        assert_eq!(items[1].len(), Some(2));
+        assert_eq!(items[1].skip().unwrap().as_text(), Some("b"));
+
+        assert_eq!(root.get(&"b".into()).unwrap().as_text(), Some("hi"));
+        assert_eq!(
+            root.get(&b"x".to_vec().into()).unwrap().as_number(),
+            Some(0.into())
+        );
+        assert_eq!(root.get(&"x".into()), None);
+        assert_eq!(items[0].get(&"a".into()), None);
+        assert!(items[0].items().is_none());
+
+        let keys: Vec<_> = root
+            .pairs()
+            .unwrap()
+            .map(|(k, _)| k.as_key().unwrap())
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                BencodexKey::Binary(Cow::Borrowed(b"x")),
+                "a".into(),
+                "b".into()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tape_node_scalars() {
+        let tape = decode_tape(b"lntfi-7e1:\x00u0:e").unwrap();
+        let items: Vec<_> = tape.root().items().unwrap().collect();
+        assert!(items[0].is_null());
+        assert_eq!(items[1].as_bool(), Some(true));
+        assert_eq!(items[2].as_bool(), Some(false));
+        assert_eq!(items[3].as_i64(), Some(-7));
+        assert_eq!(items[4].as_binary(), Some(&[0u8][..]));
+        assert_eq!(items[5].as_text(), Some(""));
+        assert_eq!(items[5].as_binary(), None);
+        assert_eq!(tape.node(items[5].index()), Some(items[5]));
+        assert_eq!(tape.node(tape.entries().len()), None);
+    }
+}
@@ -0,0 +1,321 @@
+//! SIMD-accelerated UTF-8 validation.
+//!
+//! This implements the lookup algorithm of simdjson and simdutf (John Keiser and Daniel Lemire,
+//! *Validating UTF-8 In Less Than One Instruction Per Byte*). Every byte is classified by three
+//! 16-entry table lookups on the high nibble of the byte before it, the low nibble of the byte
+//! before it, and the high nibble of the byte itself. The lookups result in error bits which are
+//! set only for invalid two-byte sequences. Continuations which three- and four-byte sequences
+//! need are checked by looking two and three bytes back.
+
+use core::str;
+
+use super::arch::SimdBackend;
+use super::backend::selected_backend;
+
+// Error bits of the lookup tables. Each names the invalid sequence of two bytes it detects.
+/// `11______ 0_______`
+const TOO_SHORT: u8 = 1 << 0;
+/// `0_______ 10______`
+const TOO_LONG: u8 = 1 << 1;
+/// `11100000 100_____`
+const OVERLONG_3: u8 = 1 << 2;
+/// `11110100 1001____`, `11110100 101_____`, `11110101 1001____`, ...
+const TOO_LARGE: u8 = 1 << 3;
+/// `11101101 101_____`
+const SURROGATE: u8 = 1 << 4;
+/// `1100000_ 10______`
+const OVERLONG_2: u8 = 1 << 5;
+/// `11110101 1000____`, ...
+const TOO_LARGE_1000: u8 = 1 << 6;
+/// `11110000 1000____`
+const OVERLONG_4: u8 = 1 << 6;
+/// `10______ 10______`, which is valid only inside three- and four-byte sequences
+const TWO_CONTS: u8 = 1 << 7;
+/// The errors which do not depend on the low nibble of the first byte.
+const CARRY: u8 = TOO_SHORT | TOO_LONG | TWO_CONTS;
+
+/// Indexed by the high nibble of the first byte.
+const BYTE_1_HIGH: [u8; 16] = [
+    // 0_______ ________ <ASCII in byte 1>
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    TOO_LONG,
+    // 10______ ________ <continuation in byte 1>
+    TWO_CONTS,
+    TWO_CONTS,
+    TWO_CONTS,
+    TWO_CONTS,
+    // 1100____ ________ <two byte lead in byte 1>
+    TOO_SHORT | OVERLONG_2,
+    // 1101____ ________ <two byte lead in byte 1>
+    TOO_SHORT,
+    // 1110____ ________ <three byte lead in byte 1>
+    TOO_SHORT | OVERLONG_3 | SURROGATE,
+    // 1111____ ________ <four+ byte lead in byte 1>
+    TOO_SHORT | TOO_LARGE | TOO_LARGE_1000 | OVERLONG_4,
+];
+
+/// Indexed by the low nibble of the first byte.
+const BYTE_1_LOW: [u8; 16] = [
+    // ____0000 ________
+    CARRY | OVERLONG_3 | OVERLONG_2 | OVERLONG_4,
+    // ____0001 ________
+    CARRY | OVERLONG_2,
+    // ____001_ ________
+    CARRY,
+    CARRY,
+    // ____0100 ________
+    CARRY | TOO_LARGE,
+    // ____0101 ________
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    // ____011_ ________
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    // ____1___ ________
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    // ____1101 ________
+    CARRY | TOO_LARGE | TOO_LARGE_1000 | SURROGATE,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+    CARRY | TOO_LARGE | TOO_LARGE_1000,
+];
+
+/// Indexed by the high nibble of the second byte.
+const BYTE_2_HIGH: [u8; 16] = [
+    // ________ 0_______ <ASCII in byte 2>
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    // ________ 1000____
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3 | TOO_LARGE_1000 | OVERLONG_4,
+    // ________ 1001____
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3 | TOO_LARGE,
+    // ________ 101_____
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | SURROGATE | TOO_LARGE,
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | SURROGATE | TOO_LARGE,
+    // ________ 11______ <lead in byte 2>
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+    TOO_SHORT,
+];
+
+/// The largest bytes which may end an input: a byte over them in the last three bytes starts a
+/// sequence which is cut off. Loaded from the end, so it lines up with the end of any vector.
+const MAX_LAST_BYTES: [u8; 64] = {
+    let mut max = [0xFF; 64];
+    max[61] = 0b1111_0000 - 1;
+    max[62] = 0b1110_0000 - 1;
+    max[63] = 0b1100_0000 - 1;
+    max
+};
+
+/// Texts shorter than this are validated by [`str::from_utf8`], which is faster on short inputs.
+const MIN_SIMD_LEN: usize = 16;
+
+/// Check if `input` is valid UTF-8, using the [selected backend](super::backend::selected_backend).
+///
+/// It returns the same result as [`str::from_utf8`]`(input).is_ok()`.
+pub fn validate_utf8(input: &[u8]) -> bool {
+    // SAFETY: The selected backend is always available
+    unsafe { selected_backend().validate_utf8(input) }
+}
+
+/// Check if `input` is valid UTF-8 with backend `B`.
+///
+/// # Safety
+/// - Requires the backend's SIMD features to be available
+#[inline(always)]
+pub unsafe fn validate_utf8_with<B: SimdBackend>(input: &[u8]) -> bool {
+    if input.len() < MIN_SIMD_LEN.max(B::LANE_WIDTH) {
+        return str::from_utf8(input).is_ok();
+    }
+
+    // SAFETY: Every load reads LANE_WIDTH bytes within `input`, `padded` or `MAX_LAST_BYTES`,
+    // and the caller guarantees the SIMD features.
+    unsafe {
+        let mut state = Utf8State::<B>::new();
+        let mut pos = 0;
+        while pos + B::LANE_WIDTH <= input.len() {
+            state.check(B::load_unaligned(input.as_ptr().add(pos)));
+            pos += B::LANE_WIDTH;
+        }
+        if pos < input.len() {
+            // Pad the rest with ASCII, so a sequence cut off by the end is still detected.
+            let mut padded = [0u8; 64];
+            padded[..input.len() - pos].copy_from_slice(&input[pos..]);
+            state.check(B::load_unaligned(padded.as_ptr()));
+        }
+        state.finish()
+    }
+}
+
+/// The state carried between vectors while validating.
+struct Utf8State<B: SimdBackend> {
+    /// The accumulated error bits. Any nonzero byte means the input is invalid.
+    error: B::Vector,
+    /// The previous vector, whose last three bytes may start a sequence continuing into this one.
+    previous: B::Vector,
+    /// Nonzero if the previous vector ends in the middle of a sequence.
+    previous_incomplete: B::Vector,
+}
+
+impl<B: SimdBackend> Utf8State<B> {
+    #[inline(always)]
+    unsafe fn new() -> Self {
+        unsafe {
+            Self {
+                error: B::splat(0),
+                previous: B::splat(0),
+                previous_incomplete: B::splat(0),
+            }
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn check(&mut self, input: B::Vector) {
+        unsafe {
+            if B::movemask_epi8(input) == 0 {
+                // All ASCII, so it is valid unless the previous vector is waiting for
+                // continuations.
+                self.error = B::or(self.error, self.previous_incomplete);
+                self.previous_incomplete = B::splat(0);
+            } else {
+                let prev1 = B::prev1(input, self.previous);
+                let special_cases = B::and(
+                    B::and(
+                        B::lookup16(&BYTE_1_HIGH, B::shr4(prev1)),
+                        B::lookup16(&BYTE_1_LOW, B::and(prev1, B::splat(0x0F))),
+                    ),
+                    B::lookup16(&BYTE_2_HIGH, B::shr4(input)),
+                );
+
+                // Only 111_____ is 0x80 or over after subtracting 0x60, and only 1111____ after
+                // subtracting 0x70.
+                let is_third_byte =
+                    B::saturating_sub(B::prev2(input, self.previous), B::splat(0xE0 - 0x80));
+                let is_fourth_byte =
+                    B::saturating_sub(B::prev3(input, self.previous), B::splat(0xF0 - 0x80));
+                let must_be_continuation =
+                    B::and(B::or(is_third_byte, is_fourth_byte), B::splat(0x80));
+
+                // A continuation where one must be sets TWO_CONTS, and the XOR clears it.
+                self.error = B::or(self.error, B::xor(must_be_continuation, special_cases));
+                self.previous_incomplete = B::saturating_sub(
+                    input,
+                    B::load_unaligned(MAX_LAST_BYTES.as_ptr().add(64 - B::LANE_WIDTH)),
+                );
+            }
+            self.previous = input;
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn finish(self) -> bool {
+        unsafe {
+            let error = B::or(self.error, self.previous_incomplete);
+            B::movemask_epi8(B::cmpeq_epi8(error, 0)) == lane_mask::<B>()
+        }
+    }
+}
+
+/// A bitmask with a bit for each byte of a vector.
+#[inline(always)]
+fn lane_mask<B: SimdBackend>() -> u64 {
+    u64::MAX >> (64 - B::LANE_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::backend::available_backends;
+    use super::*;
+    use crate::prelude::*;
+    use alloc::vec;
+
+    /// Inputs of every length around the vector widths, with `sequence` at every position.
+    fn cases(sequence: &[u8]) -> Vec<Vec<u8>> {
+        let mut cases = Vec::new();
+        for len in [0, 1, 15, 16, 17, 31, 32, 33, 63, 64, 65, 127, 128, 129] {
+            for at in 0..=len {
+                let mut input = vec![b'a'; len];
+                input.splice(at..at, sequence.iter().copied());
+                cases.push(input);
+            }
+        }
+        cases
+    }
+
+    fn assert_validates(input: &[u8], expected: bool) {
+        assert_eq!(expected, validate_utf8(input), "dispatched: {:x?}", input);
+        for backend in available_backends() {
+            // SAFETY: Only the available backends are used
+            let valid = unsafe { backend.validate_utf8(input) };
+            assert_eq!(expected, valid, "{}: {:x?}", backend, input);
+        }
+    }
+
+    #[test]
+    fn test_validate_utf8_valid() {
+        for sequence in [
+            "".as_bytes(),
+            "é".as_bytes(),
+            "한".as_bytes(),
+            "😀".as_bytes(),
+            "\u{7F}\u{80}\u{7FF}\u{800}\u{FFFF}\u{10000}\u{10FFFF}".as_bytes(),
+        ] {
+            for input in cases(sequence) {
+                assert_validates(&input, true);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_utf8_invalid() {
+        for sequence in [
+            &[0x80][..],               // continuation without a lead
+            &[0xC3],                   // cut off
+            &[0xE0, 0x80],             // cut off
+            &[0xF0, 0x9F, 0x98],       // cut off
+            &[0xC0, 0xAF],             // overlong 2
+            &[0xE0, 0x80, 0xAF],       // overlong 3
+            &[0xF0, 0x80, 0x80, 0xAF], // overlong 4
+            &[0xED, 0xA0, 0x80],       // surrogate
+            &[0xF4, 0x90, 0x80, 0x80], // too large
+            &[0xF5, 0x80, 0x80, 0x80], // too large
+            &[0xFF],
+            &[0xC3, 0xA9, 0xA9],             // too long
+            &[0xF0, 0x9F, 0x98, 0x80, 0x80], // too long
+        ] {
+            for input in cases(sequence) {
+                assert_validates(&input, false);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_utf8_pairs() {
+        // Every pair of bytes after a valid prefix, so each lookup entry is covered
+        let prefix = "가나다라마바사아자차카타파하".as_bytes();
+        for first in 0x70..=0xFFu8 {
+            for second in 0x70..=0xFFu8 {
+                let mut input = prefix.to_vec();
+                input.extend_from_slice(&[first, second]);
+                input.extend_from_slice(prefix);
+                assert_validates(&input, str::from_utf8(&input).is_ok());
+            }
+        }
+    }
+}
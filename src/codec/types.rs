@@ -1,8 +1,12 @@
+pub use super::number::BencodexNumber;
 use crate::prelude::*;
 use num_bigint::BigInt;
 
 /// The type alias of `BTreeMap<BencodexKey, BencodexValue>` to reduce code size.
 ///
+/// With the `sorted-dict` feature, it is [`SortedDictionary`](super::dictionary::SortedDictionary)
+/// instead, which keeps the entries in a sorted [`Vec`].
+///
 /// ```
 /// use bencodex::{ Encode, BencodexDictionary };
 ///
@@ -13,7 +17,10 @@ use num_bigint::BigInt;
 /// dict.encode(&mut buf);
 /// assert_eq!(buf, b"du3:foou3:bare")
 /// ```
+#[cfg(not(feature = "sorted-dict"))]
 pub type BencodexDictionary<'a> = BTreeMap<BencodexKey<'a>, BencodexValue<'a>>;
+#[cfg(feature = "sorted-dict")]
+pub type BencodexDictionary<'a> = super::dictionary::SortedDictionary<'a>;
 /// The type alias of `Vec<BencodexValue>` to reduce code size.
 ///
 /// ```
@@ -45,7 +52,7 @@ pub enum BencodexValue<'a> {
     Binary(Cow<'a, [u8]>),
     Text(Cow<'a, str>),
     Boolean(bool),
-    Number(BigInt),
+    Number(BencodexNumber),
     List(BencodexList<'a>),
     Dictionary(BencodexDictionary<'a>),
     Null,
@@ -57,7 +64,60 @@ pub enum BencodexKey<'a> {
     Text(Cow<'a, str>),
 }
 
+/// The type of a [`BencodexValue`].
+#[derive(PartialEq, Eq, Debug, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum BencodexType {
+    Null,
+    Boolean,
+    Number,
+    Binary,
+    Text,
+    List,
+    Dictionary,
+}
+
+impl BencodexType {
+    /// Get the lowercase name of this type, e.g., `"dictionary"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BencodexType::Null => "null",
+            BencodexType::Boolean => "boolean",
+            BencodexType::Number => "number",
+            BencodexType::Binary => "binary",
+            BencodexType::Text => "text",
+            BencodexType::List => "list",
+            BencodexType::Dictionary => "dictionary",
+        }
+    }
+}
+
+impl core::fmt::Display for BencodexType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 impl<'a> BencodexValue<'a> {
+    /// Get the [`BencodexType`] of this value.
+    ///
+    /// ```
+    /// use bencodex::{ BencodexType, BencodexValue };
+    ///
+    /// assert_eq!(BencodexValue::Null.value_type(), BencodexType::Null);
+    /// assert_eq!(BencodexValue::from("text").value_type(), BencodexType::Text);
+    /// ```
+    pub fn value_type(&self) -> BencodexType {
+        match self {
+            BencodexValue::Binary(_) => BencodexType::Binary,
+            BencodexValue::Text(_) => BencodexType::Text,
+            BencodexValue::Boolean(_) => BencodexType::Boolean,
+            BencodexValue::Number(_) => BencodexType::Number,
+            BencodexValue::List(_) => BencodexType::List,
+            BencodexValue::Dictionary(_) => BencodexType::Dictionary,
+            BencodexValue::Null => BencodexType::Null,
+        }
+    }
+
     /// Convert a borrowed `BencodexValue` into a fully owned one with `'static` lifetime.
     pub fn into_owned(self) -> BencodexValue<'static> {
         match self {
@@ -137,7 +197,7 @@ impl From<String> for BencodexValue<'_> {
 }
 
 macro_rules! bencodex_value_number_impl {
-    ($x:tt) => {
+    ($x:ty) => {
         impl From<$x> for BencodexValue<'_> {
             fn from(val: $x) -> Self {
                 BencodexValue::Number(val.into())
@@ -153,6 +213,12 @@ bencodex_value_number_impl!(i8);
 bencodex_value_number_impl!(i16);
 bencodex_value_number_impl!(i32);
 bencodex_value_number_impl!(i64);
+bencodex_value_number_impl!(i128);
+bencodex_value_number_impl!(u128);
+bencodex_value_number_impl!(isize);
+bencodex_value_number_impl!(usize);
+bencodex_value_number_impl!(BigInt);
+bencodex_value_number_impl!(BencodexNumber);
 
 impl From<bool> for BencodexValue<'_> {
     fn from(val: bool) -> Self {
@@ -180,7 +246,7 @@ where
     U: Into<BencodexValue<'a>>,
 {
     fn from(val: BTreeMap<T, U>) -> Self {
-        let mut map = BTreeMap::<BencodexKey<'a>, BencodexValue<'a>>::new();
+        let mut map = BencodexDictionary::new();
         for (key, value) in val {
             map.insert(key.into(), value.into());
         }
@@ -189,13 +255,19 @@ where
     }
 }
 
+impl<'a> From<super::dictionary::SortedDictionary<'a>> for BencodexValue<'a> {
+    fn from(val: super::dictionary::SortedDictionary<'a>) -> Self {
+        BencodexValue::Dictionary(val.into_iter().collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     mod into {
         use crate::prelude::*;
         use alloc::vec;
 
-        use super::super::{BencodexKey, BencodexValue};
+        use super::super::{BencodexDictionary, BencodexKey, BencodexValue};
 
         #[test]
         fn text() {
@@ -326,7 +398,7 @@ mod tests {
             map.insert("foo".to_string(), b"bar");
             let actual: BencodexValue = map.into();
 
-            let expected = BencodexValue::Dictionary(BTreeMap::from_iter([(
+            let expected = BencodexValue::Dictionary(BencodexDictionary::from_iter([(
                 BencodexKey::Text(Cow::Owned("foo".to_string())),
                 BencodexValue::Binary(Cow::Borrowed(b"bar".as_slice())),
             )]));
@@ -0,0 +1,210 @@
+//! Structural differences between two Bencodex values.
+//!
+//! ```
+//! use bencodex::{ BencodexDictionary, BencodexValue };
+//! use bencodex::diff::diff;
+//!
+//! let mut left = BencodexDictionary::new();
+//! left.insert("name".into(), "foo".into());
+//! left.insert("tags".into(), BencodexValue::List(vec!["a".into()]));
+//! let mut right = left.clone();
+//! right.insert("name".into(), "bar".into());
+//! right.insert("tags".into(), BencodexValue::List(vec!["a".into(), "b".into()]));
+//!
+//! let (left, right) = (BencodexValue::Dictionary(left), BencodexValue::Dictionary(right));
+//! let differences: Vec<String> = diff(&left, &right).iter().map(|d| d.to_string()).collect();
+//! assert_eq!(differences, vec!["$.name: changed", "$.tags[1]: added"]);
+//! ```
+
+use crate::codec::types::*;
+use crate::path::{Path, PathSegment};
+use crate::prelude::*;
+use core::cmp::Ordering;
+use core::fmt;
+
+/// How a value differs between the left and the right.
+#[derive(PartialEq, Debug, Clone)]
+pub enum DifferenceKind<'v> {
+    /// The value exists only in the right.
+    Added(&'v BencodexValue<'v>),
+    /// The value exists only in the left.
+    Removed(&'v BencodexValue<'v>),
+    /// The values differ, and they are not both lists or both dictionaries.
+    Changed {
+        left: &'v BencodexValue<'v>,
+        right: &'v BencodexValue<'v>,
+    },
+}
+
+/// A place where two values differ, returned by [`diff`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct Difference<'v> {
+    pub path: Path,
+    pub kind: DifferenceKind<'v>,
+}
+
+impl fmt::Display for Difference<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            DifferenceKind::Added(_) => "added",
+            DifferenceKind::Removed(_) => "removed",
+            DifferenceKind::Changed { .. } => "changed",
+        };
+        write!(f, "{}: {}", self.path, kind)
+    }
+}
+
+/// Compare `left` and `right`, and return every [`Difference`] in the order of their paths.
+///
+/// Lists and dictionaries are compared item by item. List items are matched by their indices,
+/// so inserting an item in the middle of a list changes every following item.
+pub fn diff<'v>(left: &'v BencodexValue<'v>, right: &'v BencodexValue<'v>) -> Vec<Difference<'v>> {
+    let mut differences = Vec::new();
+    diff_impl(left, right, &mut Path::root(), &mut differences);
+    differences
+}
+
+fn diff_impl<'v>(
+    left: &'v BencodexValue<'v>,
+    right: &'v BencodexValue<'v>,
+    path: &mut Path,
+    differences: &mut Vec<Difference<'v>>,
+) {
+    match (left, right) {
+        (BencodexValue::List(left), BencodexValue::List(right)) => {
+            for (index, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+                path.push(PathSegment::Index(index));
+                diff_impl(l, r, path, differences);
+                path.pop();
+            }
+            for (index, value) in left.iter().enumerate().skip(right.len()) {
+                path.push(PathSegment::Index(index));
+                differences.push(Difference {
+                    path: path.clone(),
+                    kind: DifferenceKind::Removed(value),
+                });
+                path.pop();
+            }
+            for (index, value) in right.iter().enumerate().skip(left.len()) {
+                path.push(PathSegment::Index(index));
+                differences.push(Difference {
+                    path: path.clone(),
+                    kind: DifferenceKind::Added(value),
+                });
+                path.pop();
+            }
+        }
+        (BencodexValue::Dictionary(left), BencodexValue::Dictionary(right)) => {
+            // Both are sorted by keys, so merge them in order.
+            let mut left = left.iter().peekable();
+            let mut right = right.iter().peekable();
+            loop {
+                let ordering = match (left.peek(), right.peek()) {
+                    (None, None) => break,
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (Some((l, _)), Some((r, _))) => l.cmp(r),
+                };
+                match ordering {
+                    Ordering::Less => {
+                        let (key, value) = left.next().unwrap();
+                        path.push(PathSegment::Key(key.clone().into_owned()));
+                        differences.push(Difference {
+                            path: path.clone(),
+                            kind: DifferenceKind::Removed(value),
+                        });
+                    }
+                    Ordering::Greater => {
+                        let (key, value) = right.next().unwrap();
+                        path.push(PathSegment::Key(key.clone().into_owned()));
+                        differences.push(Difference {
+                            path: path.clone(),
+                            kind: DifferenceKind::Added(value),
+                        });
+                    }
+                    Ordering::Equal => {
+                        let (key, l) = left.next().unwrap();
+                        let (_, r) = right.next().unwrap();
+                        path.push(PathSegment::Key(key.clone().into_owned()));
+                        diff_impl(l, r, path, differences);
+                    }
+                }
+                path.pop();
+            }
+        }
+        (left, right) if left != right => differences.push(Difference {
+            path: path.clone(),
+            kind: DifferenceKind::Changed { left, right },
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod diff {
+        use super::super::*;
+        use alloc::vec;
+
+        #[test]
+        fn should_return_nothing_for_equal_values() {
+            let value = BencodexValue::List(vec![1.into(), "a".into(), BencodexValue::Null]);
+            assert_eq!(Vec::<Difference>::new(), diff(&value, &value.clone()));
+        }
+
+        #[test]
+        fn should_return_changed_scalars() {
+            let (left, right) = (BencodexValue::from(1), BencodexValue::from("1"));
+            assert_eq!(
+                vec![Difference {
+                    path: Path::root(),
+                    kind: DifferenceKind::Changed {
+                        left: &left,
+                        right: &right,
+                    },
+                }],
+                diff(&left, &right)
+            );
+        }
+
+        #[test]
+        fn should_compare_lists_by_index() {
+            let left = BencodexValue::List(vec![1.into(), 2.into(), 3.into()]);
+            let right = BencodexValue::List(vec![1.into(), 3.into()]);
+            assert_eq!(
+                vec!["$[1]: changed", "$[2]: removed"],
+                diff(&left, &right)
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn should_compare_dictionaries_by_key() {
+            let mut inner = BencodexDictionary::new();
+            inner.insert(vec![0u8].into(), true.into());
+            let mut left = BencodexDictionary::new();
+            left.insert("a".into(), 1.into());
+            left.insert("b".into(), BencodexValue::Dictionary(inner.clone()));
+            left.insert("c".into(), 1.into());
+            inner.insert(vec![0u8].into(), false.into());
+            let mut right = BencodexDictionary::new();
+            right.insert("b".into(), BencodexValue::Dictionary(inner));
+            right.insert("c".into(), 1.into());
+            right.insert("d".into(), 1.into());
+
+            let (left, right) = (
+                BencodexValue::Dictionary(left),
+                BencodexValue::Dictionary(right),
+            );
+            assert_eq!(
+                vec!["$.a: removed", "$.b[0x00]: changed", "$.d: added"],
+                diff(&left, &right)
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+}
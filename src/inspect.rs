@@ -0,0 +1,507 @@
+//! Summaries of the shape of Bencodex values.
+//!
+//! ```
+//! use bencodex::{ BencodexDictionary, BencodexType, BencodexValue };
+//! use bencodex::inspect::inspect;
+//!
+//! let mut dict = BencodexDictionary::new();
+//! dict.insert("tags".into(), BencodexValue::List(vec!["a".into(), "b".into()]));
+//! let inspection = inspect(&BencodexValue::Dictionary(dict));
+//!
+//! assert_eq!(inspection.value_type, BencodexType::Dictionary);
+//! assert_eq!(inspection.encoded_len, 19);
+//! assert_eq!(inspection.depth, 2);
+//! assert_eq!(inspection.type_counts[&BencodexType::Text], 2);
+//! assert_eq!(inspection.keys.total, 1);
+//! ```
+//!
+//! [`statistics`] breaks down where the bytes of a value go, e.g., to find out why a payload is
+//! larger than expected.
+
+use crate::codec::types::*;
+use crate::path::{Path, PathSegment};
+use crate::prelude::*;
+use alloc::collections::{BTreeSet, BinaryHeap};
+use core::cmp::Reverse;
+use core::fmt;
+
+/// A summary of a value, returned by [`inspect`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct Inspection {
+    /// The type of the root value.
+    pub value_type: BencodexType,
+    /// The length in bytes of the encoded value.
+    pub encoded_len: usize,
+    /// The number of nested lists and dictionaries. `0` for a value which is not a container.
+    pub depth: usize,
+    /// The number of values of each type, including the root value but excluding dictionary keys.
+    pub type_counts: BTreeMap<BencodexType, usize>,
+    /// Statistics of dictionary keys.
+    pub keys: KeyStatistics,
+}
+
+/// Statistics of the keys of every dictionary in a value.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct KeyStatistics {
+    /// The number of dictionaries.
+    pub dictionaries: usize,
+    /// The number of keys of all dictionaries.
+    pub total: usize,
+    /// The number of distinct keys of all dictionaries.
+    pub distinct: usize,
+    /// The number of text keys.
+    pub text: usize,
+    /// The number of binary keys.
+    pub binary: usize,
+    /// The largest number of keys in a dictionary.
+    pub max_per_dictionary: usize,
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "type: {}", self.value_type)?;
+        writeln!(f, "size: {} bytes", self.encoded_len)?;
+        writeln!(f, "depth: {}", self.depth)?;
+        f.write_str("values:")?;
+        for (value_type, count) in &self.type_counts {
+            write!(f, " {} {}", value_type, count)?;
+        }
+        writeln!(f)?;
+        let keys = &self.keys;
+        write!(
+            f,
+            "keys: {} in {} dictionaries ({} distinct, {} text, {} binary, at most {} per dictionary)",
+            keys.total,
+            keys.dictionaries,
+            keys.distinct,
+            keys.text,
+            keys.binary,
+            keys.max_per_dictionary
+        )
+    }
+}
+
+/// Summarize the type, size, depth and keys of `value`.
+pub fn inspect(value: &BencodexValue<'_>) -> Inspection {
+    let mut inspection = Inspection {
+        value_type: value.value_type(),
+        encoded_len: value.encoded_len(),
+        depth: 0,
+        type_counts: BTreeMap::new(),
+        keys: KeyStatistics::default(),
+    };
+    let mut distinct_keys = BTreeSet::new();
+    inspection.depth = inspect_impl(value, &mut inspection, &mut distinct_keys);
+    inspection.keys.distinct = distinct_keys.len();
+    inspection
+}
+
+/// Count `value` and its children into `inspection`, and return the depth of `value`.
+fn inspect_impl<'v>(
+    value: &'v BencodexValue<'_>,
+    inspection: &mut Inspection,
+    distinct_keys: &mut BTreeSet<&'v BencodexKey<'v>>,
+) -> usize {
+    *inspection
+        .type_counts
+        .entry(value.value_type())
+        .or_default() += 1;
+    match value {
+        BencodexValue::List(list) => {
+            1 + list
+                .iter()
+                .map(|item| inspect_impl(item, inspection, distinct_keys))
+                .max()
+                .unwrap_or(0)
+        }
+        BencodexValue::Dictionary(map) => {
+            let keys = &mut inspection.keys;
+            keys.dictionaries += 1;
+            keys.total += map.len();
+            keys.max_per_dictionary = keys.max_per_dictionary.max(map.len());
+            for key in map.keys() {
+                match key {
+                    BencodexKey::Text(_) => keys.text += 1,
+                    BencodexKey::Binary(_) => keys.binary += 1,
+                }
+                distinct_keys.insert(key);
+            }
+            1 + map
+                .values()
+                .map(|item| inspect_impl(item, inspection, distinct_keys))
+                .max()
+                .unwrap_or(0)
+        }
+        _ => 0,
+    }
+}
+
+/// A breakdown of the encoded bytes of a value, returned by [`statistics`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct Statistics {
+    /// The length in bytes of the encoded value.
+    pub encoded_len: usize,
+    /// The number of nested lists and dictionaries. `0` for a value which is not a container.
+    pub depth: usize,
+    /// The encoded bytes of the values of each type, excluding their children. The bytes of a
+    /// list are its `l` and `e`, and the bytes of a dictionary are its `d`, `e` and keys, so they
+    /// sum up to [`encoded_len`](Statistics::encoded_len).
+    pub type_bytes: BTreeMap<BencodexType, usize>,
+    /// The paths and the encoded lengths of the largest values except the root, the largest
+    /// first.
+    pub largest: Vec<(Path, usize)>,
+    /// The most frequent dictionary keys and the number of dictionaries having them, the most
+    /// frequent first.
+    pub key_counts: Vec<(BencodexKey<'static>, usize)>,
+    /// The number of binaries and texts by their length in bytes. The first bucket counts empty
+    /// ones, and the `n`-th bucket counts lengths from `2^(n-1)` to `2^n - 1`. Dictionary keys
+    /// are not counted.
+    pub string_lengths: Vec<usize>,
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "size: {} bytes", self.encoded_len)?;
+        writeln!(f, "depth: {}", self.depth)?;
+        f.write_str("bytes by type:")?;
+        for (value_type, bytes) in &self.type_bytes {
+            let ratio = *bytes as f64 * 100.0 / self.encoded_len as f64;
+            write!(
+                f,
+                "\n  {:<10} {:>10} {:>5.1}%",
+                value_type.name(),
+                bytes,
+                ratio
+            )?;
+        }
+        f.write_str("\nlargest values:")?;
+        for (path, len) in &self.largest {
+            write!(f, "\n  {:>10} {}", len, path)?;
+        }
+        f.write_str("\nkey frequency:")?;
+        for (key, count) in &self.key_counts {
+            write!(f, "\n  {:>10} ", count)?;
+            match key {
+                BencodexKey::Text(text) => write!(f, "{:?}", text)?,
+                BencodexKey::Binary(bytes) => {
+                    f.write_str("0x")?;
+                    for byte in bytes.iter() {
+                        write!(f, "{:02x}", byte)?;
+                    }
+                }
+            }
+        }
+        f.write_str("\nstring lengths:")?;
+        for (bucket, count) in self.string_lengths.iter().enumerate() {
+            match bucket {
+                0 => write!(f, "\n  {:>10} {}", count, 0)?,
+                1 => write!(f, "\n  {:>10} {}", count, 1)?,
+                _ => write!(
+                    f,
+                    "\n  {:>10} {}-{}",
+                    count,
+                    1usize << (bucket - 1),
+                    (1usize << bucket) - 1
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Break down the encoded bytes of `value` by type, and find the `top` largest values and the
+/// `top` most frequent dictionary keys.
+///
+/// ```
+/// use bencodex::{ BencodexDictionary, BencodexType, BencodexValue };
+/// use bencodex::inspect::statistics;
+///
+/// let mut dict = BencodexDictionary::new();
+/// dict.insert("data".into(), vec![0u8; 100].into());
+/// dict.insert("tags".into(), BencodexValue::List(vec!["a".into(), "b".into()]));
+/// let stats = statistics(&BencodexValue::Dictionary(dict), 1);
+///
+/// assert_eq!(stats.encoded_len, 130);
+/// assert_eq!(stats.type_bytes[&BencodexType::Binary], 104);
+/// assert_eq!(stats.largest[0].0.to_string(), "$.data");
+/// assert_eq!(stats.string_lengths, vec![0, 2, 0, 0, 0, 0, 0, 1]);
+/// ```
+pub fn statistics(value: &BencodexValue<'_>, top: usize) -> Statistics {
+    let mut walker = StatisticsWalker {
+        top,
+        steps: Vec::new(),
+        type_bytes: BTreeMap::new(),
+        largest: BinaryHeap::new(),
+        key_counts: BTreeMap::new(),
+        string_lengths: Vec::new(),
+    };
+    let (encoded_len, depth) = walker.walk(value);
+
+    let mut largest: Vec<_> = walker
+        .largest
+        .into_iter()
+        .map(|Reverse(entry)| entry)
+        .collect();
+    largest.sort_by(|(a_len, a_path), (b_len, b_path)| b_len.cmp(a_len).then(a_path.cmp(b_path)));
+    let mut key_counts: Vec<_> = walker.key_counts.into_iter().collect();
+    // Stable, so keys of the same count stay in their order.
+    key_counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+    key_counts.truncate(top);
+
+    Statistics {
+        encoded_len,
+        depth,
+        type_bytes: walker.type_bytes,
+        largest: largest.into_iter().map(|(len, path)| (path, len)).collect(),
+        key_counts: key_counts
+            .into_iter()
+            .map(|(key, count)| (key.clone().into_owned(), count))
+            .collect(),
+        string_lengths: walker.string_lengths,
+    }
+}
+
+/// A step of the path to the current value, which is converted to [`PathSegment`] only when the
+/// value is one of the largest.
+enum Step<'v> {
+    Key(&'v BencodexKey<'v>),
+    Index(usize),
+}
+
+struct StatisticsWalker<'v> {
+    top: usize,
+    steps: Vec<Step<'v>>,
+    type_bytes: BTreeMap<BencodexType, usize>,
+    /// A min-heap of the largest values, so the smallest of them is replaced first.
+    largest: BinaryHeap<Reverse<(usize, Path)>>,
+    key_counts: BTreeMap<&'v BencodexKey<'v>, usize>,
+    string_lengths: Vec<usize>,
+}
+
+impl<'v> StatisticsWalker<'v> {
+    /// Count `value` and its children, and return the encoded length and the depth of `value`.
+    fn walk(&mut self, value: &'v BencodexValue<'v>) -> (usize, usize) {
+        let (encoded_len, own_len, depth) = match value {
+            BencodexValue::List(list) => {
+                let (mut encoded_len, mut depth) = (2, 0);
+                for (index, item) in list.iter().enumerate() {
+                    self.steps.push(Step::Index(index));
+                    let (len, d) = self.walk(item);
+                    self.steps.pop();
+                    encoded_len += len;
+                    depth = depth.max(d);
+                }
+                (encoded_len, 2, depth + 1)
+            }
+            BencodexValue::Dictionary(map) => {
+                let (mut encoded_len, mut own_len, mut depth) = (2, 2, 0);
+                for (key, item) in map.iter() {
+                    *self.key_counts.entry(key).or_default() += 1;
+                    self.steps.push(Step::Key(key));
+                    let (len, d) = self.walk(item);
+                    self.steps.pop();
+                    own_len += key.encoded_len();
+                    encoded_len += key.encoded_len() + len;
+                    depth = depth.max(d);
+                }
+                (encoded_len, own_len, depth + 1)
+            }
+            BencodexValue::Binary(bytes) => {
+                self.count_string(bytes.len());
+                let len = value.encoded_len();
+                (len, len, 0)
+            }
+            BencodexValue::Text(text) => {
+                self.count_string(text.len());
+                let len = value.encoded_len();
+                (len, len, 0)
+            }
+            _ => {
+                let len = value.encoded_len();
+                (len, len, 0)
+            }
+        };
+
+        *self.type_bytes.entry(value.value_type()).or_default() += own_len;
+        if !self.steps.is_empty() {
+            self.offer(encoded_len);
+        }
+        (encoded_len, depth)
+    }
+
+    fn count_string(&mut self, len: usize) {
+        let bucket = len.checked_ilog2().map_or(0, |log| log as usize + 1);
+        if self.string_lengths.len() <= bucket {
+            self.string_lengths.resize(bucket + 1, 0);
+        }
+        self.string_lengths[bucket] += 1;
+    }
+
+    /// Keep the current value if it is one of the `top` largest values so far.
+    fn offer(&mut self, encoded_len: usize) {
+        if self.largest.len() >= self.top {
+            match self.largest.peek() {
+                Some(Reverse((smallest, _))) if *smallest < encoded_len => {
+                    self.largest.pop();
+                }
+                _ => return,
+            }
+        }
+        let mut path = Path::root();
+        for step in &self.steps {
+            path.push(match step {
+                Step::Key(key) => PathSegment::Key((*key).clone().into_owned()),
+                Step::Index(index) => PathSegment::Index(*index),
+            });
+        }
+        self.largest.push(Reverse((encoded_len, path)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod inspect {
+        use super::super::*;
+        use alloc::vec;
+
+        #[test]
+        fn should_inspect_scalar() {
+            let value = BencodexValue::from(42);
+            let inspection = inspect(&value);
+            assert_eq!(
+                Inspection {
+                    value_type: BencodexType::Number,
+                    encoded_len: 4,
+                    depth: 0,
+                    type_counts: BTreeMap::from([(BencodexType::Number, 1)]),
+                    keys: KeyStatistics::default(),
+                },
+                inspection
+            );
+        }
+
+        #[test]
+        fn should_count_keys_and_depth() {
+            let mut inner = BencodexDictionary::new();
+            inner.insert("a".into(), BencodexValue::List(vec![]));
+            inner.insert(vec![0u8].into(), BencodexValue::Null);
+            let mut map = BencodexDictionary::new();
+            map.insert("a".into(), BencodexValue::Dictionary(inner));
+            map.insert("b".into(), true.into());
+            map.insert("c".into(), BencodexValue::List(vec![1.into()]));
+            let inspection = inspect(&BencodexValue::Dictionary(map));
+
+            assert_eq!(3, inspection.depth);
+            assert_eq!(
+                BTreeMap::from([
+                    (BencodexType::Null, 1),
+                    (BencodexType::Boolean, 1),
+                    (BencodexType::Number, 1),
+                    (BencodexType::List, 2),
+                    (BencodexType::Dictionary, 2),
+                ]),
+                inspection.type_counts
+            );
+            assert_eq!(
+                KeyStatistics {
+                    dictionaries: 2,
+                    total: 5,
+                    distinct: 4,
+                    text: 4,
+                    binary: 1,
+                    max_per_dictionary: 3,
+                },
+                inspection.keys
+            );
+            assert_eq!(
+                "type: dictionary\nsize: 32 bytes\ndepth: 3\n\
+                 values: null 1 boolean 1 number 1 list 2 dictionary 2\n\
+                 keys: 5 in 2 dictionaries (4 distinct, 4 text, 1 binary, at most 3 per dictionary)",
+                inspection.to_string()
+            );
+        }
+    }
+
+    mod statistics {
+        use super::super::*;
+        use alloc::{format, vec};
+
+        #[test]
+        fn should_break_down_scalar() {
+            let stats = statistics(&BencodexValue::from("abc"), 10);
+            assert_eq!(
+                Statistics {
+                    encoded_len: 6,
+                    depth: 0,
+                    type_bytes: BTreeMap::from([(BencodexType::Text, 6)]),
+                    largest: vec![],
+                    key_counts: vec![],
+                    string_lengths: vec![0, 0, 1],
+                },
+                stats
+            );
+        }
+
+        #[test]
+        fn should_break_down_nested_values() {
+            let mut inner = BencodexDictionary::new();
+            inner.insert("a".into(), vec![0u8; 10].into());
+            inner.insert(vec![0u8].into(), BencodexValue::Null);
+            let mut map = BencodexDictionary::new();
+            map.insert("a".into(), BencodexValue::Dictionary(inner));
+            map.insert("b".into(), BencodexValue::List(vec![1.into(), "".into()]));
+            let value = BencodexValue::Dictionary(map);
+            let stats = statistics(&value, 3);
+
+            assert_eq!(value.encoded_len(), stats.encoded_len);
+            assert_eq!(2, stats.depth);
+            assert_eq!(
+                BTreeMap::from([
+                    (BencodexType::Null, 1),
+                    (BencodexType::Number, 3),
+                    (BencodexType::Binary, 13),
+                    (BencodexType::Text, 3),
+                    (BencodexType::List, 2),
+                    (BencodexType::Dictionary, 19),
+                ]),
+                stats.type_bytes
+            );
+            assert_eq!(stats.encoded_len, stats.type_bytes.values().sum::<usize>());
+            assert_eq!(
+                vec!["$.a: 23", "$.a.a: 13", "$.b: 8"],
+                stats
+                    .largest
+                    .iter()
+                    .map(|(path, len)| format!("{}: {}", path, len))
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(
+                vec![
+                    (BencodexKey::from("a"), 2),
+                    (BencodexKey::from(vec![0u8]), 1)
+                ],
+                stats.key_counts[..2]
+            );
+            assert_eq!(vec![1, 0, 0, 0, 1], stats.string_lengths);
+        }
+
+        #[test]
+        fn should_display_report() {
+            let value = BencodexValue::List(vec!["ab".into(), vec![0u8].into()]);
+            assert_eq!(
+                "size: 10 bytes\ndepth: 1\nbytes by type:\
+                 \n  binary              3  30.0%\
+                 \n  text                5  50.0%\
+                 \n  list                2  20.0%\
+                 \nlargest values:\
+                 \n           5 $[0]\
+                 \nkey frequency:\
+                 \nstring lengths:\
+                 \n           0 0\
+                 \n           1 1\
+                 \n           1 2-3",
+                statistics(&value, 1).to_string()
+            );
+        }
+    }
+}
@@ -1,35 +1,237 @@
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::io;
 use std::result::Result;
 
-use base64::Engine;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde_json::Value;
 
-use crate::{BencodexDictionary, BencodexKey, BencodexValue};
+use super::encode::{
+    BinaryEncoding, JsonEncodeOptions, NumberEncoding, TextEncoding, is_number_like,
+};
+use crate::codec::dictionary::DictionaryBuilder;
+use crate::{BencodexKey, BencodexNumber, BencodexValue};
+
+/// Options used by [`from_json_with_options`], [`from_json_string_with_options`] and
+/// [`from_json_reader_with_options`] when decoding JSON to Bencodex.
+///
+/// Each field accepts what the field of the same name in [`JsonEncodeOptions`] produces, so
+/// [`From<JsonEncodeOptions>`] gives the options to decode JSON encoded with them. The default
+/// accepts what [`JsonEncodeOptions::default`] produces.
+///
+/// Hexadecimal is accepted in both cases regardless of [`binary_encoding`], and `b64:` strings
+/// are decoded with the alphabet and padding of [`binary_encoding`], or with the standard base64
+/// if it is a hexadecimal variant.
+///
+/// [`binary_encoding`]: JsonDecodeOptions::binary_encoding
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct JsonDecodeOptions {
+    pub binary_encoding: BinaryEncoding,
+    /// With [`TextEncoding::BomIfAmbiguous`], strings without any prefix which don't look like a
+    /// number are decoded as texts.
+    pub text_encoding: TextEncoding,
+    /// With [`NumberEncoding::NumberIfSafe`], integral JSON numbers are decoded as numbers.
+    pub number_encoding: NumberEncoding,
+}
+
+impl From<JsonEncodeOptions> for JsonDecodeOptions {
+    fn from(options: JsonEncodeOptions) -> Self {
+        Self {
+            binary_encoding: options.binary_encoding,
+            text_encoding: options.text_encoding,
+            number_encoding: options.number_encoding,
+        }
+    }
+}
 
 /// The error type which is returned from decoding json to bencodex.
-#[derive(Debug, PartialEq)]
+///
+/// `pointer`s are [JSON Pointers] to the offending node, e.g., `/items/0`. When the key of an
+/// object member is invalid, it points to the member.
+///
+/// [JSON Pointers]: https://datatracker.ietf.org/doc/html/rfc6901
+#[derive(Debug, PartialEq, Clone)]
 pub enum JsonDecodeError {
-    /// This should be used when it failed to decode because the given json string is invalid. It is used by [`from_json_string`].
-    /// For example, it will be returned when `nulll` string is given.
-    InvalidJsonString,
-    /// This should be used when it failed to decode because the given json is invalid. It is used by [`from_json`] and [`from_json_string`].
-    /// For example, it will be returned when `serde_json::Value::String("0xZZ")` is given.
-    InvalidJson,
-}
-
-fn from_json_key_impl(s: &str) -> Result<BencodexKey<'static>, JsonDecodeError> {
-    if let Some(rest) = s.strip_prefix("b64:") {
-        let binary = base64::engine::general_purpose::STANDARD
-            .decode(rest)
-            .map_err(|_| JsonDecodeError::InvalidJson)?;
-        Ok(BencodexKey::Binary(Cow::Owned(binary)))
-    } else if let Some(rest) = s.strip_prefix("0x") {
-        let binary = hex::decode(rest).map_err(|_| JsonDecodeError::InvalidJson)?;
-        Ok(BencodexKey::Binary(Cow::Owned(binary)))
-    } else if let Some(rest) = s.strip_prefix('\u{FEFF}') {
-        Ok(BencodexKey::Text(Cow::Owned(rest.to_string())))
-    } else {
-        Err(JsonDecodeError::InvalidJson)
+    /// This should be used when it failed to decode because the given json string is invalid. It is used by
+    /// [`from_json_string`] and [`from_json_reader`]. For example, it will be returned when `nulll` string is given.
+    ///
+    /// `reason` is the message of the underlying JSON parser, including where it failed.
+    InvalidJsonString { reason: String },
+    /// This should be used when it failed to decode because the given json is invalid. It is used by [`from_json`],
+    /// [`from_json_string`] and [`from_json_reader`]. For example, it will be returned when
+    /// `serde_json::Value::String("0xZZ")` is given.
+    InvalidJson {
+        pointer: String,
+        reason: InvalidJsonReason,
+    },
+    /// This should be used when it failed to decode because the given json object has the same key more than once,
+    /// including keys written differently for the same Bencodex key, e.g., `"0x00"` and `"b64:AA=="`.
+    DuplicatedKey { pointer: String },
+}
+
+/// The reason why a JSON node couldn't be decoded, carried by [`JsonDecodeError::InvalidJson`].
+#[derive(Debug, PartialEq, Clone)]
+pub enum InvalidJsonReason {
+    /// The string has none of the `\u{FEFF}`, `b64:` and `0x` prefixes and isn't a number. Such
+    /// strings are only decoded as texts with [`TextEncoding::BomIfAmbiguous`].
+    UnprefixedString,
+    /// The string after `b64:` isn't valid base64.
+    InvalidBase64(base64::DecodeError),
+    /// The string after `0x` isn't valid hexadecimal.
+    InvalidHex(hex::FromHexError),
+    /// The string looks like a number but isn't, e.g., `"1-2"`.
+    InvalidNumber,
+    /// A JSON number is given where a number in a string, e.g., `"123"`, is expected. Integral
+    /// JSON numbers are only decoded with [`NumberEncoding::NumberIfSafe`].
+    UnexpectedNumber,
+    /// A JSON number with a fraction or an exponent is given while importing with
+    /// [`FloatPolicy::Reject`](super::FloatPolicy::Reject).
+    UnexpectedFloat,
+    /// A JSON number has more fractional digits than the scale of
+    /// [`FloatPolicy::ScaledInteger`](super::FloatPolicy::ScaledInteger).
+    InexactFloat,
+}
+
+impl fmt::Display for InvalidJsonReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidJsonReason::UnprefixedString => {
+                write!(
+                    f,
+                    "string should start with \"\\uFEFF\", \"b64:\" or \"0x\""
+                )
+            }
+            InvalidJsonReason::InvalidBase64(e) => {
+                write!(f, "invalid base64 after \"b64:\": {}", e)
+            }
+            InvalidJsonReason::InvalidHex(e) => write!(f, "invalid hex after \"0x\": {}", e),
+            InvalidJsonReason::InvalidNumber => write!(f, "invalid number string"),
+            InvalidJsonReason::UnexpectedNumber => {
+                write!(f, "numbers should be written as strings, e.g., \"123\"")
+            }
+            InvalidJsonReason::UnexpectedFloat => write!(f, "floats are not allowed"),
+            InvalidJsonReason::InexactFloat => {
+                write!(f, "float has more fractional digits than the scale")
+            }
+        }
+    }
+}
+
+impl fmt::Display for JsonDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonDecodeError::InvalidJsonString { reason } => write!(f, "invalid JSON: {}", reason),
+            JsonDecodeError::InvalidJson { pointer, reason } => {
+                write!(f, "invalid Bencodex JSON at \"{}\": {}", pointer, reason)
+            }
+            JsonDecodeError::DuplicatedKey { pointer } => {
+                write!(f, "duplicated key at \"{}\"", pointer)
+            }
+        }
+    }
+}
+
+impl Error for JsonDecodeError {}
+
+/// A [JSON Pointer](https://datatracker.ietf.org/doc/html/rfc6901) built on the stack while
+/// decoding, so it is only formatted when an error occurs.
+#[derive(Clone, Copy)]
+pub(super) enum Pointer<'p> {
+    Root,
+    Index(&'p Pointer<'p>, usize),
+    Key(&'p Pointer<'p>, &'p str),
+}
+
+impl fmt::Display for Pointer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pointer::Root => Ok(()),
+            Pointer::Index(parent, index) => write!(f, "{}/{}", parent, index),
+            Pointer::Key(parent, key) => {
+                write!(f, "{}/", parent)?;
+                for c in key.chars() {
+                    match c {
+                        '~' => f.write_str("~0")?,
+                        '/' => f.write_str("~1")?,
+                        c => fmt::Write::write_char(f, c)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Pointer<'_> {
+    fn error(&self, reason: InvalidJsonReason) -> JsonDecodeError {
+        JsonDecodeError::InvalidJson {
+            pointer: self.to_string(),
+            reason,
+        }
+    }
+}
+
+/// How JSON object keys, strings and numbers are interpreted as Bencodex values.
+///
+/// [`JsonDecodeOptions`] interprets the JSON representation of Bencodex, and
+/// [`JsonImportOptions`](super::JsonImportOptions) interprets arbitrary JSON documents.
+pub(super) trait Interpret {
+    fn key(&self, s: &str) -> Result<BencodexKey<'static>, InvalidJsonReason>;
+    fn string(&self, s: &str) -> Result<BencodexValue<'static>, InvalidJsonReason>;
+    fn integer(&self, n: BencodexNumber) -> Result<BencodexValue<'static>, InvalidJsonReason>;
+    fn float(&self, n: f64) -> Result<BencodexValue<'static>, InvalidJsonReason>;
+}
+
+impl Interpret for JsonDecodeOptions {
+    fn key(&self, s: &str) -> Result<BencodexKey<'static>, InvalidJsonReason> {
+        if let Some(rest) = s.strip_prefix("b64:") {
+            let binary = self
+                .binary_encoding
+                .decode_base64(rest)
+                .map_err(InvalidJsonReason::InvalidBase64)?;
+            Ok(BencodexKey::Binary(Cow::Owned(binary)))
+        } else if let Some(rest) = s.strip_prefix("0x") {
+            let binary = hex::decode(rest).map_err(InvalidJsonReason::InvalidHex)?;
+            Ok(BencodexKey::Binary(Cow::Owned(binary)))
+        } else if let Some(rest) = s.strip_prefix('\u{FEFF}') {
+            Ok(BencodexKey::Text(Cow::Owned(rest.to_string())))
+        } else if self.text_encoding == TextEncoding::BomIfAmbiguous && !is_number_like(s) {
+            Ok(BencodexKey::Text(Cow::Owned(s.to_string())))
+        } else {
+            Err(InvalidJsonReason::UnprefixedString)
+        }
+    }
+
+    fn string(&self, s: &str) -> Result<BencodexValue<'static>, InvalidJsonReason> {
+        match self.key(s) {
+            Ok(key) => Ok(match key {
+                BencodexKey::Text(t) => BencodexValue::Text(t),
+                BencodexKey::Binary(b) => BencodexValue::Binary(b),
+            }),
+            Err(InvalidJsonReason::UnprefixedString)
+                if s.as_bytes()
+                    .iter()
+                    .all(|x| x.is_ascii_digit() || *x == b'-') =>
+            {
+                Ok(BencodexValue::Number(
+                    s.parse().map_err(|_| InvalidJsonReason::InvalidNumber)?,
+                ))
+            }
+            Err(reason) => Err(reason),
+        }
+    }
+
+    fn integer(&self, n: BencodexNumber) -> Result<BencodexValue<'static>, InvalidJsonReason> {
+        match self.number_encoding {
+            NumberEncoding::NumberIfSafe => Ok(BencodexValue::Number(n)),
+            NumberEncoding::String => Err(InvalidJsonReason::UnexpectedNumber),
+        }
+    }
+
+    fn float(&self, _: f64) -> Result<BencodexValue<'static>, InvalidJsonReason> {
+        Err(InvalidJsonReason::UnexpectedNumber)
     }
 }
 
@@ -53,47 +255,77 @@ fn from_json_key_impl(s: &str) -> Result<BencodexKey<'static>, JsonDecodeError>
 /// In error case:
 ///
 /// ```
-/// use serde_json::Value;
-/// use bencodex::json::{ from_json, JsonDecodeError };
+/// use serde_json::json;
+/// use bencodex::json::{ from_json, InvalidJsonReason, JsonDecodeError };
 ///
-/// let result = from_json(&Value::String("0xZZ".to_string()));
-/// assert!(result.is_err());
-/// assert_eq!(result.unwrap_err(), JsonDecodeError::InvalidJson);
+/// let result = from_json(&json!({ "\u{FEFF}items": ["0xZ"] }));
+/// assert_eq!(
+///     result.unwrap_err(),
+///     JsonDecodeError::InvalidJson {
+///         pointer: "/\u{FEFF}items/0".to_string(),
+///         reason: InvalidJsonReason::InvalidHex(hex::FromHexError::OddLength),
+///     }
+/// );
 /// ```
 pub fn from_json(value: &Value) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    from_json_with_options(value, JsonDecodeOptions::default())
+}
+
+/// Decode JSON value to Bencodex value with the given options.
+pub fn from_json_with_options(
+    value: &Value,
+    options: JsonDecodeOptions,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    from_json_impl(value, &options, Pointer::Root)
+}
+
+pub(super) fn from_json_impl<O: Interpret>(
+    value: &Value,
+    options: &O,
+    pointer: Pointer<'_>,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
     match value {
         Value::Null => Ok(BencodexValue::Null),
         Value::Bool(b) => Ok(BencodexValue::Boolean(*b)),
-        Value::Number(_) => Err(JsonDecodeError::InvalidJson),
+        Value::Number(n) => {
+            let result = if let Some(n) = n.as_i64() {
+                options.integer(n.into())
+            } else if let Some(n) = n.as_u64() {
+                options.integer(n.into())
+            } else {
+                // Without the `arbitrary_precision` feature of serde_json, the other numbers are
+                // always f64.
+                options.float(n.as_f64().unwrap_or(f64::NAN))
+            };
+            result.map_err(|reason| pointer.error(reason))
+        }
         Value::Array(arr) => Ok(BencodexValue::List(
-            arr.iter().map(from_json).collect::<Result<Vec<_>, _>>()?,
+            arr.iter()
+                .enumerate()
+                .map(|(i, v)| from_json_impl(v, options, Pointer::Index(&pointer, i)))
+                .collect::<Result<Vec<_>, _>>()?,
         )),
         Value::Object(obj) => {
-            let mut map = BencodexDictionary::new();
+            let duplicated_key = |k: &str| JsonDecodeError::DuplicatedKey {
+                pointer: Pointer::Key(&pointer, k).to_string(),
+            };
+            let mut map = DictionaryBuilder::default();
             for (k, v) in obj {
-                map.insert(from_json_key_impl(k)?, from_json(v)?);
+                let member = Pointer::Key(&pointer, k);
+                let key = options.key(k).map_err(|reason| member.error(reason))?;
+                let value = from_json_impl(v, options, member)?;
+                if let Some(k) = map.push(key, value, k.as_str()) {
+                    return Err(duplicated_key(k));
+                }
             }
 
-            Ok(BencodexValue::Dictionary(map))
-        }
-        Value::String(s) => {
-            if let Ok(key) = from_json_key_impl(s) {
-                Ok(match key {
-                    BencodexKey::Text(t) => BencodexValue::Text(t),
-                    BencodexKey::Binary(b) => BencodexValue::Binary(b),
-                })
-            } else if s
-                .as_bytes()
-                .iter()
-                .all(|x| x.is_ascii_digit() || *x == b'-')
-            {
-                Ok(BencodexValue::Number(
-                    s.parse().map_err(|_| JsonDecodeError::InvalidJson)?,
-                ))
-            } else {
-                Err(JsonDecodeError::InvalidJson)
+            let (map, duplicates) = map.finish();
+            if let Some(k) = duplicates.first() {
+                return Err(duplicated_key(k));
             }
+            Ok(BencodexValue::Dictionary(map))
         }
+        Value::String(s) => options.string(s).map_err(|reason| pointer.error(reason)),
     }
 }
 
@@ -118,20 +350,501 @@ pub fn from_json(value: &Value) -> Result<BencodexValue<'static>, JsonDecodeErro
 /// use bencodex::json::{ from_json_string, JsonDecodeError };
 ///
 /// let result = from_json_string("nulll");
-/// assert!(result.is_err());
-/// assert_eq!(result.unwrap_err(), JsonDecodeError::InvalidJsonString);
+/// assert!(matches!(result, Err(JsonDecodeError::InvalidJsonString { .. })));
 /// ```
 ///
 /// In error case which return [`JsonDecodeError::InvalidJson`]:
 ///
 /// ```
-/// use bencodex::json::{ from_json_string, JsonDecodeError };
+/// use bencodex::json::{ from_json_string, InvalidJsonReason, JsonDecodeError };
 ///
-/// let result = from_json_string("\"0xZZ\"");
-/// assert!(result.is_err());
-/// assert_eq!(result.unwrap_err(), JsonDecodeError::InvalidJson);
+/// let result = from_json_string("[\"1\", 2]");
+/// assert_eq!(
+///     result.unwrap_err(),
+///     JsonDecodeError::InvalidJson {
+///         pointer: "/1".to_string(),
+///         reason: InvalidJsonReason::UnexpectedNumber,
+///     }
+/// );
 /// ```
 pub fn from_json_string(s: &str) -> Result<BencodexValue<'static>, JsonDecodeError> {
-    let json = serde_json::from_str(s).map_err(|_| JsonDecodeError::InvalidJsonString)?;
-    from_json(&json)
+    from_json_string_with_options(s, JsonDecodeOptions::default())
+}
+
+/// Decode JSON string to Bencodex value with the given options.
+pub fn from_json_string_with_options(
+    s: &str,
+    options: JsonDecodeOptions,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    from_json_deserializer(&mut deserializer, &options)
+}
+
+/// Decode JSON from `reader` to Bencodex value.
+///
+/// Like [`from_json_string`], it doesn't build the whole JSON tree first. Note that reading
+/// byte by byte from an unbuffered reader is slow, so wrap it with [`io::BufReader`] if it is a
+/// file or a socket. Errors while reading are returned as [`JsonDecodeError::InvalidJsonString`].
+///
+/// ```
+/// use bencodex::BencodexValue;
+/// use bencodex::json::from_json_reader;
+///
+/// let result = from_json_reader("[\"\u{FEFF}foo\", \"1\"]".as_bytes());
+/// assert_eq!(
+///     result.unwrap(),
+///     BencodexValue::List(vec!["foo".into(), 1.into()])
+/// );
+/// ```
+pub fn from_json_reader<R: io::Read>(reader: R) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    from_json_reader_with_options(reader, JsonDecodeOptions::default())
+}
+
+/// Decode JSON from `reader` to Bencodex value with the given options.
+pub fn from_json_reader_with_options<R: io::Read>(
+    reader: R,
+    options: JsonDecodeOptions,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    from_json_deserializer(&mut deserializer, &options)
+}
+
+pub(super) fn from_json_deserializer<'de, R: serde_json::de::Read<'de>, O: Interpret>(
+    deserializer: &mut serde_json::Deserializer<R>,
+    options: &O,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    let value = deserialize_value(deserializer, options)?;
+    deserializer
+        .end()
+        .map_err(|e| JsonDecodeError::InvalidJsonString {
+            reason: e.to_string(),
+        })?;
+    Ok(value)
+}
+
+/// Deserialize the next JSON value from `deserializer`, leaving the rest of the input.
+fn deserialize_value<'de, R: serde_json::de::Read<'de>, O: Interpret>(
+    deserializer: &mut serde_json::Deserializer<R>,
+    options: &O,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    let error = Cell::new(None);
+    BencodexSeed {
+        error: &error,
+        options,
+        pointer: Pointer::Root,
+    }
+    .deserialize(&mut *deserializer)
+    .map_err(|e| {
+        error.take().unwrap_or(JsonDecodeError::InvalidJsonString {
+            reason: e.to_string(),
+        })
+    })
+}
+
+/// Decode a stream of JSON values separated by whitespace from `reader`, e.g., [JSON Lines],
+/// to Bencodex values.
+///
+/// The iterator yields each value in turn. After yielding an error, it yields nothing more.
+///
+/// [JSON Lines]: https://jsonlines.org/
+///
+/// ```
+/// use bencodex::BencodexValue;
+/// use bencodex::json::from_json_stream;
+///
+/// let values: Vec<_> = from_json_stream("\"1\"\nnull\n[]\n".as_bytes())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(values, vec![1.into(), BencodexValue::Null, BencodexValue::List(vec![])]);
+/// ```
+pub fn from_json_stream<R: io::Read>(reader: R) -> JsonStream<R> {
+    from_json_stream_with_options(reader, JsonDecodeOptions::default())
+}
+
+/// Decode a stream of JSON values from `reader` to Bencodex values with the given options.
+pub fn from_json_stream_with_options<R: io::Read>(
+    reader: R,
+    options: JsonDecodeOptions,
+) -> JsonStream<R> {
+    JsonStream {
+        deserializer: serde_json::Deserializer::from_reader(reader),
+        options,
+        failed: false,
+    }
+}
+
+/// An iterator over Bencodex values decoded from a stream of JSON values, returned by
+/// [`from_json_stream`].
+pub struct JsonStream<R: io::Read> {
+    deserializer: serde_json::Deserializer<serde_json::de::IoRead<R>>,
+    options: JsonDecodeOptions,
+    failed: bool,
+}
+
+impl<R: io::Read> Iterator for JsonStream<R> {
+    type Item = Result<BencodexValue<'static>, JsonDecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // `end` succeeds only if nothing but whitespace is left.
+        if self.failed || self.deserializer.end().is_ok() {
+            return None;
+        }
+
+        let value = deserialize_value(&mut self.deserializer, &self.options);
+        self.failed = value.is_err();
+        Some(value)
+    }
+}
+
+impl<R: io::Read> std::iter::FusedIterator for JsonStream<R> {}
+
+/// Deserializes JSON into [`BencodexValue`] in one pass, without building [`Value`].
+///
+/// Serde errors can't carry [`JsonDecodeError`], so it is stashed in `error` before returning a
+/// custom serde error.
+struct BencodexSeed<'e, 'p, O> {
+    error: &'e Cell<Option<JsonDecodeError>>,
+    options: &'e O,
+    pointer: Pointer<'p>,
+}
+
+impl<O> Clone for BencodexSeed<'_, '_, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O> Copy for BencodexSeed<'_, '_, O> {}
+
+impl<'e, 'p, O: Interpret> BencodexSeed<'e, 'p, O> {
+    fn fail<E: de::Error>(&self, error: JsonDecodeError) -> E {
+        let message = error.to_string();
+        self.error.set(Some(error));
+        E::custom(message)
+    }
+
+    /// Fail with [`JsonDecodeError::DuplicatedKey`] at the member `raw` of this object.
+    fn duplicated_key<E: de::Error>(&self, raw: &str) -> E {
+        self.fail(JsonDecodeError::DuplicatedKey {
+            pointer: Pointer::Key(&self.pointer, raw).to_string(),
+        })
+    }
+
+    fn child<'c>(&self, pointer: Pointer<'c>) -> BencodexSeed<'e, 'c, O> {
+        BencodexSeed {
+            error: self.error,
+            options: self.options,
+            pointer,
+        }
+    }
+}
+
+impl<'de, O: Interpret> DeserializeSeed<'de> for BencodexSeed<'_, '_, O> {
+    type Value = BencodexValue<'static>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, O: Interpret> Visitor<'de> for BencodexSeed<'_, '_, O> {
+    type Value = BencodexValue<'static>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON representation of a Bencodex value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(BencodexValue::Null)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(BencodexValue::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.options
+            .integer(v.into())
+            .map_err(|reason| self.fail(self.pointer.error(reason)))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.options
+            .integer(v.into())
+            .map_err(|reason| self.fail(self.pointer.error(reason)))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        self.options
+            .float(v)
+            .map_err(|reason| self.fail(self.pointer.error(reason)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.options
+            .string(v)
+            .map_err(|reason| self.fail(self.pointer.error(reason)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) =
+            seq.next_element_seed(self.child(Pointer::Index(&self.pointer, list.len())))?
+        {
+            list.push(value);
+        }
+        Ok(BencodexValue::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut dict = DictionaryBuilder::default();
+        while let Some(raw) = map.next_key::<RawKey<'de>>()? {
+            let member = Pointer::Key(&self.pointer, &raw.0);
+            let key = match self.options.key(&raw.0) {
+                Ok(key) => key,
+                Err(reason) => return Err(self.fail(member.error(reason))),
+            };
+            let value = map.next_value_seed(self.child(member))?;
+            if let Some(raw) = dict.push(key, value, raw.0) {
+                return Err(self.duplicated_key(&raw));
+            }
+        }
+        let (dict, duplicates) = dict.finish();
+        if let Some(raw) = duplicates.first() {
+            return Err(self.duplicated_key(raw));
+        }
+        Ok(BencodexValue::Dictionary(dict))
+    }
+}
+
+/// A JSON object key as it is written, kept for [`Pointer`]. It borrows from the input if
+/// possible.
+struct RawKey<'de>(Cow<'de, str>);
+
+impl<'de> de::Deserialize<'de> for RawKey<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(RawKeyVisitor)
+    }
+}
+
+struct RawKeyVisitor;
+
+impl<'de> Visitor<'de> for RawKeyVisitor {
+    type Value = RawKey<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object key")
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(RawKey(Cow::Borrowed(v)))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(RawKey(Cow::Owned(v.to_string())))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(RawKey(Cow::Owned(v)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod from_json_string {
+        use super::super::*;
+
+        fn error(pointer: &str, reason: InvalidJsonReason) -> JsonDecodeError {
+            JsonDecodeError::InvalidJson {
+                pointer: pointer.to_string(),
+                reason,
+            }
+        }
+
+        #[test]
+        fn nested() {
+            assert_eq!(
+                from_json_string(r#"{"\uFEFFa": ["1", true, null, "0x0102"], "b64:AA==": {}}"#),
+                from_json(
+                    &serde_json::from_str(
+                        r#"{"\uFEFFa": ["1", true, null, "0x0102"], "b64:AA==": {}}"#
+                    )
+                    .unwrap()
+                )
+            );
+        }
+
+        #[test]
+        fn duplicated_key() {
+            assert_eq!(
+                Err(JsonDecodeError::DuplicatedKey {
+                    pointer: "/\u{FEFF}a".to_string()
+                }),
+                from_json_string(r#"{"\uFEFFa": null, "\uFEFFa": null}"#)
+            );
+            assert_eq!(
+                Err(JsonDecodeError::DuplicatedKey {
+                    pointer: "/b64:AA==".to_string()
+                }),
+                from_json_string(r#"{"0x00": null, "b64:AA==": null}"#)
+            );
+        }
+
+        #[test]
+        fn duplicated_key_same_as_from_json() {
+            for json in [
+                r#"{"0x00": "1", "b64:AA==": "2"}"#,
+                r#"{"\uFEFFa": {"0x0000": null, "b64:AAA=": null}}"#,
+                r#"{"\uFEFFb": null, "0x61": null, "b64:YQ==": null, "\uFEFFa": null}"#,
+            ] {
+                let value = serde_json::from_str(json).unwrap();
+                let expected = from_json_string(json);
+                assert!(
+                    matches!(expected, Err(JsonDecodeError::DuplicatedKey { .. })),
+                    "{}",
+                    json
+                );
+                assert_eq!(expected, from_json(&value), "{}", json);
+            }
+        }
+
+        #[test]
+        fn invalid_json() {
+            assert_eq!(
+                Err(error("/0", InvalidJsonReason::UnexpectedNumber)),
+                from_json_string(r#"[1]"#)
+            );
+            assert_eq!(
+                Err(error("/a", InvalidJsonReason::UnprefixedString)),
+                from_json_string(r#"{"a": null}"#)
+            );
+            assert_eq!(
+                Err(error(
+                    "/\u{FEFF}a~1b~0c/1",
+                    InvalidJsonReason::InvalidHex(hex::FromHexError::OddLength)
+                )),
+                from_json_string(r#"{"\uFEFFa/b~c": ["0x00", "0x0"]}"#)
+            );
+            assert_eq!(
+                Err(error(
+                    "",
+                    InvalidJsonReason::InvalidBase64(base64::DecodeError::InvalidPadding)
+                )),
+                from_json_string(r#""b64:AA=""#)
+            );
+            assert_eq!(
+                Err(error("", InvalidJsonReason::InvalidNumber)),
+                from_json_string(r#""1-2""#)
+            );
+        }
+
+        #[test]
+        fn invalid_json_string() {
+            for json in ["null null", r#"["1""#, "nulll"] {
+                assert!(matches!(
+                    from_json_string(json),
+                    Err(JsonDecodeError::InvalidJsonString { .. })
+                ));
+            }
+        }
+
+        #[test]
+        fn same_as_from_json() {
+            let json = r#"{"\uFEFFx": [{"0xzz": null}]}"#;
+            assert_eq!(
+                from_json(&serde_json::from_str(json).unwrap()),
+                from_json_string(json)
+            );
+        }
+    }
+
+    mod with_options {
+        use super::super::*;
+        use crate::BencodexDictionary;
+
+        #[test]
+        fn relaxed() {
+            let options = JsonDecodeOptions {
+                binary_encoding: BinaryEncoding::Base64UrlNoPad,
+                text_encoding: TextEncoding::BomIfAmbiguous,
+                number_encoding: NumberEncoding::NumberIfSafe,
+            };
+            let json = r#"{"foo": [1, "2", "b64:-_8", "0xFBFF", "", "\uFEFF3"]}"#;
+            let expected = BencodexValue::from(BencodexDictionary::from_iter([(
+                BencodexKey::from("foo"),
+                BencodexValue::List(vec![
+                    1.into(),
+                    2.into(),
+                    vec![0xfbu8, 0xff].into(),
+                    vec![0xfbu8, 0xff].into(),
+                    "".into(),
+                    "3".into(),
+                ]),
+            )]));
+            assert_eq!(
+                Ok(&expected),
+                from_json_string_with_options(json, options).as_ref()
+            );
+            assert_eq!(
+                Ok(&expected),
+                from_json_with_options(&serde_json::from_str(json).unwrap(), options).as_ref()
+            );
+        }
+
+        #[test]
+        fn floats_are_not_numbers() {
+            let options = JsonDecodeOptions {
+                number_encoding: NumberEncoding::NumberIfSafe,
+                ..Default::default()
+            };
+            assert_eq!(
+                Err(JsonDecodeError::InvalidJson {
+                    pointer: "".to_string(),
+                    reason: InvalidJsonReason::UnexpectedNumber,
+                }),
+                from_json_string_with_options("1.5", options)
+            );
+        }
+    }
+    mod from_json_stream {
+        use super::super::*;
+        use crate::BencodexDictionary;
+
+        #[test]
+        fn should_decode_each_value() {
+            let json = " \"\u{FEFF}a\" {\"0x00\":true}\n\n[\"1\"]\r\n";
+            let values: Vec<_> = from_json_stream(json.as_bytes()).collect();
+            let mut dict = BencodexDictionary::new();
+            dict.insert(vec![0u8].into(), true.into());
+            assert_eq!(
+                vec![
+                    Ok("a".into()),
+                    Ok(BencodexValue::Dictionary(dict)),
+                    Ok(BencodexValue::List(vec![1.into()])),
+                ],
+                values
+            );
+            assert_eq!(0, from_json_stream(" \n".as_bytes()).count());
+        }
+
+        #[test]
+        fn should_stop_after_error() {
+            let mut values = from_json_stream("null \"x\" null".as_bytes());
+            assert_eq!(Some(Ok(BencodexValue::Null)), values.next());
+            assert_eq!(
+                Some(Err(JsonDecodeError::InvalidJson {
+                    pointer: "".to_string(),
+                    reason: InvalidJsonReason::UnprefixedString,
+                })),
+                values.next()
+            );
+            assert_eq!(None, values.next());
+        }
+    }
 }
@@ -1,6 +1,9 @@
 use base64::Engine;
-use num_traits::ToPrimitive;
+use base64::display::Base64Display;
+use base64::engine::GeneralPurpose;
+use core::fmt::{self, Write as _};
 use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::io;
 
 use crate::{BencodexKey, BencodexValue};
 
@@ -24,6 +27,10 @@ impl Serialize for BencodexJsonEncoder<'_> {
     }
 }
 
+/// The largest integer which [`f64`] represents exactly, i.e., `Number.MAX_SAFE_INTEGER` in
+/// JavaScript.
+pub(super) const MAX_SAFE_INTEGER: i64 = (1 << 53) - 1;
+
 fn serialize_value<S>(
     value: &BencodexValue<'_>,
     options: &JsonEncodeOptions,
@@ -37,18 +44,25 @@ where
         BencodexValue::Boolean(b) => serializer.serialize_bool(*b),
         BencodexValue::Number(n) => {
             // Use itoa for small numbers (i64 range) for faster serialization
-            if let Some(small) = n.to_i64() {
+            if let Some(small) = n.as_i64() {
+                if options.number_encoding == NumberEncoding::NumberIfSafe
+                    && (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&small)
+                {
+                    return serializer.serialize_i64(small);
+                }
                 let mut buf = itoa::Buffer::new();
                 serializer.serialize_str(buf.format(small))
             } else {
-                // Large numbers fall back to BigInt::to_string()
-                serializer.serialize_str(&n.to_string())
+                // Large numbers are written through their Display
+                serializer.collect_str(n)
             }
         }
         BencodexValue::Binary(data) => {
-            serializer.serialize_str(&format_binary(data, options.binary_encoding))
+            serializer.collect_str(&BinaryDisplay::new(data, options.binary_encoding))
+        }
+        BencodexValue::Text(text) => {
+            serializer.collect_str(&TextDisplay::new(text, options.text_encoding))
         }
-        BencodexValue::Text(text) => serializer.serialize_str(&format_text(text)),
         BencodexValue::List(items) => {
             let mut seq = serializer.serialize_seq(Some(items.len()))?;
             for item in items {
@@ -58,55 +72,216 @@ where
         }
         BencodexValue::Dictionary(map) => {
             let mut m = serializer.serialize_map(Some(map.len()))?;
-            for (k, v) in map {
-                m.serialize_entry(
-                    &format_key(k, options),
-                    &BencodexJsonEncoder::new(v, *options),
-                )?;
+            match options.key_order {
+                KeyOrder::Bencodex => {
+                    for (k, v) in map {
+                        m.serialize_entry(
+                            &KeyEncoder::new(k, options),
+                            &BencodexJsonEncoder::new(v, *options),
+                        )?;
+                    }
+                }
+                KeyOrder::Json => {
+                    let mut entries: Vec<_> = map
+                        .iter()
+                        .map(|(k, v)| (KeyEncoder::new(k, options).to_string(), v))
+                        .collect();
+                    entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                    for (k, v) in entries {
+                        m.serialize_entry(&k, &BencodexJsonEncoder::new(v, *options))?;
+                    }
+                }
             }
             m.end()
         }
     }
 }
 
-fn format_key(key: &BencodexKey<'_>, options: &JsonEncodeOptions) -> String {
-    match key {
-        BencodexKey::Binary(data) => format_binary(data, options.binary_encoding),
-        BencodexKey::Text(text) => format_text(text),
+/// Writes a dictionary key as its JSON string representation.
+pub(super) struct KeyEncoder<'a> {
+    key: &'a BencodexKey<'a>,
+    options: &'a JsonEncodeOptions,
+}
+
+impl<'a> KeyEncoder<'a> {
+    pub(super) fn new(key: &'a BencodexKey<'a>, options: &'a JsonEncodeOptions) -> Self {
+        Self { key, options }
+    }
+}
+
+impl fmt::Display for KeyEncoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.key {
+            BencodexKey::Binary(data) => {
+                BinaryDisplay::new(data, self.options.binary_encoding).fmt(f)
+            }
+            BencodexKey::Text(text) => TextDisplay::new(text, self.options.text_encoding).fmt(f),
+        }
+    }
+}
+
+impl Serialize for KeyEncoder<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Writes a binary as its JSON string representation, without allocating a [`String`].
+struct BinaryDisplay<'a> {
+    data: &'a [u8],
+    encoding: BinaryEncoding,
+}
+
+impl<'a> BinaryDisplay<'a> {
+    #[inline(always)]
+    fn new(data: &'a [u8], encoding: BinaryEncoding) -> Self {
+        Self { data, encoding }
     }
 }
 
-#[inline(always)]
-fn format_binary(data: &[u8], encoding: BinaryEncoding) -> String {
-    match encoding {
-        BinaryEncoding::Base64 => {
-            format!(
-                "b64:{}",
-                base64::engine::general_purpose::STANDARD.encode(data)
-            )
+impl fmt::Display for BinaryDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.encoding.base64_engine() {
+            Some(engine) => {
+                f.write_str("b64:")?;
+                fmt::Display::fmt(&Base64Display::new(self.data, engine), f)
+            }
+            None => {
+                let encode = match self.encoding {
+                    BinaryEncoding::HexUpper => faster_hex::hex_encode_upper,
+                    _ => faster_hex::hex_encode,
+                };
+                f.write_str("0x")?;
+                let mut buf = [0u8; HEX_CHUNK_SIZE * 2];
+                for chunk in self.data.chunks(HEX_CHUNK_SIZE) {
+                    let hex =
+                        encode(chunk, &mut buf[..chunk.len() * 2]).expect("buffer size is correct");
+                    f.write_str(hex)?;
+                }
+                Ok(())
+            }
         }
-        BinaryEncoding::Hex => {
-            let mut buf = vec![0u8; data.len() * 2 + 2];
-            buf[0] = b'0';
-            buf[1] = b'x';
-            faster_hex::hex_encode(data, &mut buf[2..]).expect("buffer size is correct");
-            // SAFETY: hex_encode produces valid ASCII (0-9, a-f)
-            unsafe { String::from_utf8_unchecked(buf) }
+    }
+}
+
+/// The number of bytes hex-encoded at once into the stack buffer of [`BinaryDisplay`].
+const HEX_CHUNK_SIZE: usize = 512;
+
+/// Writes a text as its JSON string representation, i.e., prefixed with BOM if needed.
+struct TextDisplay<'a> {
+    text: &'a str,
+    encoding: TextEncoding,
+}
+
+impl<'a> TextDisplay<'a> {
+    #[inline(always)]
+    fn new(text: &'a str, encoding: TextEncoding) -> Self {
+        Self { text, encoding }
+    }
+}
+
+impl fmt::Display for TextDisplay<'_> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.encoding == TextEncoding::Bom || is_ambiguous_text(self.text) {
+            f.write_char('\u{FEFF}')?;
         }
+        f.write_str(self.text)
     }
 }
 
-#[inline(always)]
-fn format_text(text: &str) -> String {
-    format!("\u{FEFF}{}", text)
+/// Check if `text` would be read as another type when it is written without BOM, i.e., it has
+/// a prefix or looks like a number.
+pub(super) fn is_ambiguous_text(text: &str) -> bool {
+    text.starts_with('\u{FEFF}')
+        || text.starts_with("b64:")
+        || text.starts_with("0x")
+        || is_number_like(text)
+}
+
+/// Check if `s` is written only with digits and `-`, which is how numbers are encoded in strings.
+pub(super) fn is_number_like(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|x| x.is_ascii_digit() || x == b'-')
 }
 
 /// An enum type to choose how to encode Bencodex binary type when encoding to JSON.
-#[derive(Default, Copy, Clone)]
+///
+/// Base64 variants are prefixed with `b64:` and hexadecimal variants are prefixed with `0x`.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum BinaryEncoding {
+    /// Base64 with the standard alphabet and padding, e.g., `b64:+/8=`.
     #[default]
     Base64,
+    /// Lowercase hexadecimal, e.g., `0xfbff`.
     Hex,
+    /// Base64 with the URL-safe alphabet and padding, e.g., `b64:-_8=`.
+    Base64Url,
+    /// Base64 with the URL-safe alphabet and without padding, e.g., `b64:-_8`.
+    Base64UrlNoPad,
+    /// Uppercase hexadecimal, e.g., `0xFBFF`.
+    HexUpper,
+}
+
+impl BinaryEncoding {
+    /// Get the base64 engine if it is one of the base64 variants.
+    pub(super) fn base64_engine(&self) -> Option<&'static GeneralPurpose> {
+        match self {
+            BinaryEncoding::Base64 => Some(&base64::engine::general_purpose::STANDARD),
+            BinaryEncoding::Base64Url => Some(&base64::engine::general_purpose::URL_SAFE),
+            BinaryEncoding::Base64UrlNoPad => {
+                Some(&base64::engine::general_purpose::URL_SAFE_NO_PAD)
+            }
+            BinaryEncoding::Hex | BinaryEncoding::HexUpper => None,
+        }
+    }
+
+    /// Decode the base64 after `b64:` in this encoding. The hexadecimal variants use the
+    /// standard base64.
+    pub(super) fn decode_base64(&self, s: &str) -> Result<Vec<u8>, base64::DecodeError> {
+        self.base64_engine()
+            .unwrap_or(&base64::engine::general_purpose::STANDARD)
+            .decode(s)
+    }
+}
+
+/// An enum type to choose how to encode Bencodex text type when encoding to JSON.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TextEncoding {
+    /// Always prefix texts with `\u{FEFF}`, e.g., `"\u{FEFF}foo"`.
+    #[default]
+    Bom,
+    /// Prefix texts with `\u{FEFF}` only if they would be read as another type without it, e.g.,
+    /// `"foo"` but `"\u{FEFF}0x00"` and `"\u{FEFF}123"`.
+    BomIfAmbiguous,
+}
+
+/// An enum type to choose how to encode Bencodex number type when encoding to JSON.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum NumberEncoding {
+    /// Always write numbers as strings, e.g., `"123"`.
+    #[default]
+    String,
+    /// Write numbers as JSON numbers if [`f64`] represents them exactly, i.e., in
+    /// `-(2^53 - 1)..=2^53 - 1`, and as strings otherwise.
+    NumberIfSafe,
+}
+
+/// An enum type to choose the order of JSON object members when encoding to JSON.
+///
+/// Bencodex dictionaries don't keep insertion order, so members are either in the canonical
+/// Bencodex order or sorted by their encoded JSON keys.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum KeyOrder {
+    /// The canonical Bencodex order of the dictionary, where binary keys come before text keys,
+    /// each sorted by their bytes.
+    #[default]
+    Bencodex,
+    /// The order of the encoded JSON keys, e.g., `"0xff"` before `"b64:AA=="` before
+    /// `"\u{FEFF}a"`. This allocates the keys of each dictionary.
+    Json,
 }
 
 /// Options used by [`to_json_with_options`] when encoding Bencodex to JSON.
@@ -120,17 +295,32 @@ pub enum BinaryEncoding {
 ///
 /// JsonEncodeOptions {
 ///   binary_encoding: BinaryEncoding::Hex,
+///   ..Default::default()
 /// };
 /// ```
 ///
-/// If you want to encode binary as base64 string, you can use like below:
+/// If you want a relaxed JSON for web frontends, you can use like below. Decode it with the
+/// matching [`JsonDecodeOptions`](super::JsonDecodeOptions), which can be made with [`From`].
 ///
 /// ```
-/// use bencodex::json::{ JsonEncodeOptions, BinaryEncoding };
+/// use bencodex::BencodexValue;
+/// use bencodex::json::{
+///     BinaryEncoding, JsonDecodeOptions, JsonEncodeOptions, NumberEncoding, TextEncoding,
+///     from_json_string_with_options, to_json_with_options,
+/// };
 ///
-/// JsonEncodeOptions {
-///   binary_encoding: BinaryEncoding::Base64,
+/// let options = JsonEncodeOptions {
+///   binary_encoding: BinaryEncoding::Base64UrlNoPad,
+///   text_encoding: TextEncoding::BomIfAmbiguous,
+///   number_encoding: NumberEncoding::NumberIfSafe,
+///   ..Default::default()
 /// };
+/// let value = BencodexValue::List(vec!["foo".into(), "123".into(), 123.into()]);
+/// let json = to_json_with_options(&value, options).unwrap();
+/// assert_eq!(json, "[\"foo\",\"\u{FEFF}123\",123]");
+///
+/// let decoded = from_json_string_with_options(&json, JsonDecodeOptions::from(options));
+/// assert_eq!(decoded.unwrap(), value);
 /// ```
 ///
 /// Or you can use [`JsonEncodeOptions::default`] for base64 case:
@@ -140,9 +330,12 @@ pub enum BinaryEncoding {
 ///
 /// JsonEncodeOptions::default();
 /// ```
-#[derive(Default, Copy, Clone)]
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
 pub struct JsonEncodeOptions {
     pub binary_encoding: BinaryEncoding,
+    pub text_encoding: TextEncoding,
+    pub number_encoding: NumberEncoding,
+    pub key_order: KeyOrder,
 }
 
 /// Encode Bencodex to JSON with default options.
@@ -160,3 +353,144 @@ pub fn to_json_with_options(
 ) -> Result<String, serde_json::Error> {
     serde_json::to_string(&BencodexJsonEncoder::new(value, options))
 }
+
+/// Encode Bencodex to pretty-printed JSON with the given options.
+///
+/// ```
+/// use bencodex::BencodexValue;
+/// use bencodex::json::{ JsonEncodeOptions, to_json_pretty };
+///
+/// let value = BencodexValue::List(vec![1.into(), true.into()]);
+/// let json = to_json_pretty(&value, JsonEncodeOptions::default()).unwrap();
+/// assert_eq!(json, "[\n  \"1\",\n  true\n]");
+/// ```
+pub fn to_json_pretty(
+    value: &BencodexValue<'_>,
+    options: JsonEncodeOptions,
+) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&BencodexJsonEncoder::new(value, options))
+}
+
+/// Encode Bencodex to JSON with the given options, writing into `writer` without building the
+/// whole JSON as a [`String`] first.
+///
+/// `writer` receives many small writes, so wrap it with [`io::BufWriter`] if it is unbuffered
+/// such as a file or stdout.
+///
+/// ```
+/// use bencodex::BencodexValue;
+/// use bencodex::json::{ JsonEncodeOptions, to_json_writer };
+///
+/// let mut buf = Vec::new();
+/// to_json_writer(&mut buf, &BencodexValue::from("foo"), JsonEncodeOptions::default()).unwrap();
+/// assert_eq!(buf, "\"\u{FEFF}foo\"".as_bytes());
+/// ```
+pub fn to_json_writer<W: io::Write>(
+    writer: W,
+    value: &BencodexValue<'_>,
+    options: JsonEncodeOptions,
+) -> Result<(), serde_json::Error> {
+    serde_json::to_writer(writer, &BencodexJsonEncoder::new(value, options))
+}
+
+/// Encode Bencodex to pretty-printed JSON with the given options, writing into `writer`.
+///
+/// See [`to_json_writer`] for details.
+pub fn to_json_pretty_writer<W: io::Write>(
+    writer: W,
+    value: &BencodexValue<'_>,
+    options: JsonEncodeOptions,
+) -> Result<(), serde_json::Error> {
+    serde_json::to_writer_pretty(writer, &BencodexJsonEncoder::new(value, options))
+}
+
+#[cfg(test)]
+mod tests {
+    mod to_json_with_options {
+        use super::super::*;
+        use crate::BencodexDictionary;
+
+        fn encode(value: &BencodexValue<'_>, options: JsonEncodeOptions) -> String {
+            to_json_with_options(value, options).unwrap()
+        }
+
+        #[test]
+        fn binary_encoding() {
+            let value = BencodexValue::from(vec![0xfbu8, 0xff]);
+            for (binary_encoding, expected) in [
+                (BinaryEncoding::Base64, r#""b64:+/8=""#),
+                (BinaryEncoding::Base64Url, r#""b64:-_8=""#),
+                (BinaryEncoding::Base64UrlNoPad, r#""b64:-_8""#),
+                (BinaryEncoding::Hex, r#""0xfbff""#),
+                (BinaryEncoding::HexUpper, r#""0xFBFF""#),
+            ] {
+                let options = JsonEncodeOptions {
+                    binary_encoding,
+                    ..Default::default()
+                };
+                assert_eq!(expected, encode(&value, options));
+            }
+        }
+
+        #[test]
+        fn text_encoding() {
+            let options = JsonEncodeOptions {
+                text_encoding: TextEncoding::BomIfAmbiguous,
+                ..Default::default()
+            };
+            for (text, expected) in [
+                ("foo", "\"foo\""),
+                ("", "\"\""),
+                ("12", "\"\u{FEFF}12\""),
+                ("-", "\"\u{FEFF}-\""),
+                ("0x12", "\"\u{FEFF}0x12\""),
+                ("b64:", "\"\u{FEFF}b64:\""),
+                ("\u{FEFF}", "\"\u{FEFF}\u{FEFF}\""),
+            ] {
+                assert_eq!(expected, encode(&text.into(), options));
+            }
+        }
+
+        #[test]
+        fn number_encoding() {
+            let options = JsonEncodeOptions {
+                number_encoding: NumberEncoding::NumberIfSafe,
+                ..Default::default()
+            };
+            for (number, expected) in [
+                (0i64, "0"),
+                (-12, "-12"),
+                (MAX_SAFE_INTEGER, "9007199254740991"),
+                (-MAX_SAFE_INTEGER, "-9007199254740991"),
+                (MAX_SAFE_INTEGER + 1, "\"9007199254740992\""),
+                (i64::MIN, "\"-9223372036854775808\""),
+            ] {
+                assert_eq!(expected, encode(&number.into(), options));
+            }
+        }
+
+        #[test]
+        fn key_order() {
+            let mut dict = BencodexDictionary::new();
+            dict.insert("a".into(), BencodexValue::Null);
+            dict.insert(vec![0xffu8].into(), BencodexValue::Null);
+            dict.insert(vec![0x00u8].into(), BencodexValue::Null);
+            let value = BencodexValue::Dictionary(dict);
+
+            assert_eq!(
+                "{\"b64:AA==\":null,\"b64:/w==\":null,\"\u{FEFF}a\":null}",
+                encode(&value, JsonEncodeOptions::default())
+            );
+            assert_eq!(
+                "{\"b64:/w==\":null,\"b64:AA==\":null,\"\u{FEFF}a\":null}",
+                encode(
+                    &value,
+                    JsonEncodeOptions {
+                        key_order: KeyOrder::Json,
+                        ..Default::default()
+                    }
+                )
+            );
+        }
+    }
+}
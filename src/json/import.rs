@@ -0,0 +1,254 @@
+use std::borrow::Cow;
+use std::io;
+
+use num_bigint::BigInt;
+use serde_json::Value;
+
+use super::decode::{
+    Interpret, InvalidJsonReason, JsonDecodeError, Pointer, from_json_deserializer, from_json_impl,
+};
+use crate::{BencodexKey, BencodexNumber, BencodexValue};
+
+/// How JSON numbers with a fraction or an exponent are imported by [`JsonImportOptions`].
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FloatPolicy {
+    /// Fail with [`InvalidJsonReason::UnexpectedFloat`].
+    #[default]
+    Reject,
+    /// Import as a text of its shortest decimal representation, e.g., `1.5` as `"1.5"`.
+    Text,
+    /// Import every number, including integers, as a number multiplied by `10^scale`, e.g., `1.25`
+    /// as `125` and `3` as `300` with `scale: 2`. It fails with
+    /// [`InvalidJsonReason::InexactFloat`] if a float has more fractional digits than `scale`.
+    ScaledInteger { scale: u32 },
+}
+
+/// Options used by [`import_json`], [`import_json_string`] and [`import_json_reader`] when
+/// importing arbitrary JSON documents to Bencodex.
+///
+/// Unlike [`JsonDecodeOptions`](super::JsonDecodeOptions), which reads the JSON representation
+/// of Bencodex, every JSON string is imported as a text without looking at its prefix, and
+/// integers are imported as numbers.
+///
+/// Note that integers out of the range of [`i64`] and [`u64`] are parsed as floats by the JSON
+/// parser, so they are imported by [`float_policy`](JsonImportOptions::float_policy).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct JsonImportOptions {
+    pub float_policy: FloatPolicy,
+    /// Import object keys as texts if `true`, or as binaries of their UTF-8 bytes if `false`.
+    pub keys_as_text: bool,
+}
+
+impl Default for JsonImportOptions {
+    fn default() -> Self {
+        Self {
+            float_policy: FloatPolicy::default(),
+            keys_as_text: true,
+        }
+    }
+}
+
+impl Interpret for JsonImportOptions {
+    fn key(&self, s: &str) -> Result<BencodexKey<'static>, InvalidJsonReason> {
+        Ok(if self.keys_as_text {
+            BencodexKey::Text(Cow::Owned(s.to_string()))
+        } else {
+            BencodexKey::Binary(Cow::Owned(s.as_bytes().to_vec()))
+        })
+    }
+
+    fn string(&self, s: &str) -> Result<BencodexValue<'static>, InvalidJsonReason> {
+        Ok(BencodexValue::Text(Cow::Owned(s.to_string())))
+    }
+
+    fn integer(&self, n: BencodexNumber) -> Result<BencodexValue<'static>, InvalidJsonReason> {
+        Ok(BencodexValue::Number(match self.float_policy {
+            FloatPolicy::ScaledInteger { scale } => {
+                n * BencodexNumber::from(BigInt::from(10).pow(scale))
+            }
+            FloatPolicy::Reject | FloatPolicy::Text => n,
+        }))
+    }
+
+    fn float(&self, n: f64) -> Result<BencodexValue<'static>, InvalidJsonReason> {
+        match self.float_policy {
+            FloatPolicy::Reject => Err(InvalidJsonReason::UnexpectedFloat),
+            FloatPolicy::Text => Ok(BencodexValue::Text(Cow::Owned(n.to_string()))),
+            FloatPolicy::ScaledInteger { scale } => {
+                scale_float(n, scale as usize).map(BencodexValue::Number)
+            }
+        }
+    }
+}
+
+/// Multiply `n` by `10^scale` on its decimal representation, so `0.1` with `scale: 1` is exactly
+/// `1` unlike `0.1 * 10.0`.
+fn scale_float(n: f64, scale: usize) -> Result<BencodexNumber, InvalidJsonReason> {
+    // Display of f64 is the shortest representation which roundtrips, and never has an exponent.
+    let literal = n.to_string();
+    let (integer, fraction) = literal.split_once('.').unwrap_or((&literal, ""));
+    if fraction.len() > scale && fraction.bytes().skip(scale).any(|b| b != b'0') {
+        return Err(InvalidJsonReason::InexactFloat);
+    }
+
+    let mut digits = String::with_capacity(integer.len() + scale);
+    digits.push_str(integer);
+    digits.push_str(&fraction[..fraction.len().min(scale)]);
+    digits.extend(std::iter::repeat_n(
+        '0',
+        scale.saturating_sub(fraction.len()),
+    ));
+    digits.parse().map_err(|_| InvalidJsonReason::InexactFloat)
+}
+
+/// Import an arbitrary JSON value to Bencodex value.
+///
+/// ```
+/// use serde_json::json;
+/// use bencodex::{ BencodexDictionary, BencodexValue };
+/// use bencodex::json::{ FloatPolicy, JsonImportOptions, import_json };
+///
+/// let options = JsonImportOptions {
+///     float_policy: FloatPolicy::ScaledInteger { scale: 2 },
+///     ..Default::default()
+/// };
+/// let value = import_json(&json!({ "name": "0x00", "price": 1.25 }), options).unwrap();
+///
+/// let mut expected = BencodexDictionary::new();
+/// expected.insert("name".into(), "0x00".into());
+/// expected.insert("price".into(), 125.into());
+/// assert_eq!(value, BencodexValue::Dictionary(expected));
+/// ```
+pub fn import_json(
+    value: &Value,
+    options: JsonImportOptions,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    from_json_impl(value, &options, Pointer::Root)
+}
+
+/// Import an arbitrary JSON string to Bencodex value, without building the whole JSON tree first.
+pub fn import_json_string(
+    s: &str,
+    options: JsonImportOptions,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    from_json_deserializer(&mut deserializer, &options)
+}
+
+/// Import an arbitrary JSON document from `reader` to Bencodex value.
+///
+/// See [`from_json_reader`](super::from_json_reader) for details.
+pub fn import_json_reader<R: io::Read>(
+    reader: R,
+    options: JsonImportOptions,
+) -> Result<BencodexValue<'static>, JsonDecodeError> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    from_json_deserializer(&mut deserializer, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    mod import_json_string {
+        use super::super::*;
+        use crate::BencodexDictionary;
+
+        fn import(
+            json: &str,
+            options: JsonImportOptions,
+        ) -> Result<BencodexValue<'static>, JsonDecodeError> {
+            let imported = import_json_string(json, options);
+            assert_eq!(
+                imported,
+                import_json(&serde_json::from_str(json).unwrap(), options)
+            );
+            imported
+        }
+
+        #[test]
+        fn plain_values() {
+            let mut expected = BencodexDictionary::new();
+            expected.insert("b64:AA==".into(), "\u{FEFF}".into());
+            expected.insert(
+                "list".into(),
+                BencodexValue::List(vec![
+                    "123".into(),
+                    (-123).into(),
+                    u64::MAX.into(),
+                    true.into(),
+                    BencodexValue::Null,
+                ]),
+            );
+            assert_eq!(
+                Ok(BencodexValue::Dictionary(expected)),
+                import(
+                    r#"{"b64:AA==": "﻿", "list": ["123", -123, 18446744073709551615, true, null]}"#,
+                    JsonImportOptions::default()
+                )
+            );
+        }
+
+        #[test]
+        fn keys_as_binary() {
+            let mut expected = BencodexDictionary::new();
+            expected.insert(b"a".to_vec().into(), "a".into());
+            assert_eq!(
+                Ok(BencodexValue::Dictionary(expected)),
+                import(
+                    r#"{"a": "a"}"#,
+                    JsonImportOptions {
+                        keys_as_text: false,
+                        ..Default::default()
+                    }
+                )
+            );
+        }
+
+        #[test]
+        fn float_policy() {
+            let reject = JsonImportOptions::default();
+            assert_eq!(
+                Err(JsonDecodeError::InvalidJson {
+                    pointer: "/1".to_string(),
+                    reason: InvalidJsonReason::UnexpectedFloat,
+                }),
+                import("[1, 1.5]", reject)
+            );
+
+            let text = JsonImportOptions {
+                float_policy: FloatPolicy::Text,
+                ..Default::default()
+            };
+            assert_eq!(
+                Ok(BencodexValue::List(vec![
+                    1.into(),
+                    "1.5".into(),
+                    "0.1".into(),
+                    "-0.000001".into()
+                ])),
+                import("[1, 1.5, 1e-1, -1e-6]", text)
+            );
+
+            let scaled = JsonImportOptions {
+                float_policy: FloatPolicy::ScaledInteger { scale: 2 },
+                ..Default::default()
+            };
+            assert_eq!(
+                Ok(BencodexValue::List(vec![
+                    100.into(),
+                    150.into(),
+                    (-1).into(),
+                    0.into(),
+                    123000.into(),
+                ])),
+                import("[1, 1.5, -0.01, 0.0, 1.23e3]", scaled)
+            );
+            assert_eq!(
+                Err(JsonDecodeError::InvalidJson {
+                    pointer: "/0".to_string(),
+                    reason: InvalidJsonReason::InexactFloat,
+                }),
+                import("[0.125]", scaled)
+            );
+        }
+    }
+}
@@ -1,5 +1,18 @@
 mod decode;
 mod encode;
+mod import;
+mod schema;
 
-pub use decode::{JsonDecodeError, from_json, from_json_string};
-pub use encode::{BinaryEncoding, JsonEncodeOptions, to_json, to_json_with_options};
+pub use decode::{
+    InvalidJsonReason, JsonDecodeError, JsonDecodeOptions, JsonStream, from_json, from_json_reader,
+    from_json_reader_with_options, from_json_stream, from_json_stream_with_options,
+    from_json_string, from_json_string_with_options, from_json_with_options,
+};
+pub use encode::{
+    BinaryEncoding, JsonEncodeOptions, KeyOrder, NumberEncoding, TextEncoding, to_json,
+    to_json_pretty, to_json_pretty_writer, to_json_with_options, to_json_writer,
+};
+pub use import::{
+    FloatPolicy, JsonImportOptions, import_json, import_json_reader, import_json_string,
+};
+pub use schema::to_json_schema;
@@ -0,0 +1,388 @@
+use serde_json::{Map, Value, json};
+
+use super::encode::{KeyEncoder, MAX_SAFE_INTEGER};
+use super::{BinaryEncoding, JsonEncodeOptions, NumberEncoding, TextEncoding};
+use crate::BencodexNumber;
+use crate::schema::{DictionarySchema, LengthRange, NumberSchema, Schema, TextSchema};
+
+/// The dialect of the JSON Schema documents generated by [`to_json_schema`].
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The pattern of numbers written as JSON strings.
+const NUMBER_PATTERN: &str = "^-?[0-9]+$";
+
+/// Generate a [JSON Schema](https://json-schema.org/) (draft 2020-12) document describing the
+/// Bencodex JSON representation, which [`to_json_with_options`](super::to_json_with_options)
+/// produces with `options`, of values following `schema`.
+///
+/// Binaries are described as strings with the `0x` or `b64:` prefix, texts as strings with the
+/// `\uFEFF` prefix and numbers as strings of digits, or as JSON integers with
+/// [`NumberEncoding::NumberIfSafe`]. So it is useful to validate a JSON document before
+/// decoding it with [`from_json`](super::from_json).
+///
+/// Some constraints are loosened where JSON Schema cannot express them:
+///
+///  - Bounds of numbers are only checked when they are written as JSON integers.
+///  - Lengths of binaries are checked on the length of their base64 strings, which is shared by
+///    up to three lengths in bytes. Hexadecimal strings are checked exactly.
+///
+/// To generate a schema from sample values instead, infer a [`Schema`] with [`Schema::infer`].
+///
+/// ```
+/// use serde_json::json;
+/// use bencodex::json::{ JsonEncodeOptions, to_json_schema };
+/// use bencodex::schema::{ DictionarySchema, Schema };
+///
+/// let schema: Schema = DictionarySchema::new()
+///     .required("amount", Schema::number())
+///     .into();
+/// let json_schema = to_json_schema(&schema, JsonEncodeOptions::default());
+///
+/// assert_eq!(json_schema, json!({
+///     "$schema": "https://json-schema.org/draft/2020-12/schema",
+///     "type": "object",
+///     "properties": {
+///         "\u{FEFF}amount": { "type": "string", "pattern": "^-?[0-9]+$" },
+///     },
+///     "required": ["\u{FEFF}amount"],
+///     "additionalProperties": false,
+/// }));
+/// ```
+pub fn to_json_schema(schema: &Schema, options: JsonEncodeOptions) -> Value {
+    let mut document = match to_json_schema_impl(schema, &options) {
+        Value::Object(object) => object,
+        Value::Bool(true) => Map::new(),
+        _ => {
+            let mut object = Map::new();
+            object.insert("not".to_string(), json!({}));
+            object
+        }
+    };
+    document.insert("$schema".to_string(), json!(JSON_SCHEMA_DIALECT));
+    Value::Object(document)
+}
+
+fn to_json_schema_impl(schema: &Schema, options: &JsonEncodeOptions) -> Value {
+    match schema {
+        Schema::Any => Value::Bool(true),
+        Schema::Null => json!({ "type": "null" }),
+        Schema::Boolean => json!({ "type": "boolean" }),
+        Schema::Number(number) => number_schema(number, options.number_encoding),
+        Schema::Binary(length) => binary_schema(length, options.binary_encoding),
+        Schema::Text(text) => text_schema(text, options.text_encoding),
+        Schema::List(list) => {
+            let mut object = Map::new();
+            object.insert("type".to_string(), json!("array"));
+            object.insert(
+                "items".to_string(),
+                to_json_schema_impl(&list.items, options),
+            );
+            insert_length(&mut object, "minItems", "maxItems", &list.length, |n| n);
+            Value::Object(object)
+        }
+        Schema::Dictionary(dict) => dictionary_schema(dict, options),
+        Schema::Union(variants) => match variants.as_slice() {
+            [] => Value::Bool(false),
+            [variant] => to_json_schema_impl(variant, options),
+            variants => json!({
+                "anyOf": variants
+                    .iter()
+                    .map(|variant| to_json_schema_impl(variant, options))
+                    .collect::<Vec<_>>(),
+            }),
+        },
+    }
+}
+
+fn number_schema(schema: &NumberSchema, encoding: NumberEncoding) -> Value {
+    let string = json!({ "type": "string", "pattern": NUMBER_PATTERN });
+    if encoding == NumberEncoding::String {
+        return string;
+    }
+
+    let max_safe = BencodexNumber::from(MAX_SAFE_INTEGER);
+    let min_safe = BencodexNumber::from(-MAX_SAFE_INTEGER);
+    let minimum = match &schema.min {
+        Some(min) if *min > min_safe => min,
+        _ => &min_safe,
+    };
+    let maximum = match &schema.max {
+        Some(max) if *max < max_safe => max,
+        _ => &max_safe,
+    };
+    // Numbers out of the safe range are still written as strings.
+    let has_unsafe = schema.min.as_ref().is_none_or(|min| *min < min_safe)
+        || schema.max.as_ref().is_none_or(|max| *max > max_safe);
+
+    let mut variants = Vec::with_capacity(2);
+    if minimum <= maximum {
+        variants.push(json!({
+            "type": "integer",
+            "minimum": minimum.as_i64(),
+            "maximum": maximum.as_i64(),
+        }));
+    }
+    if has_unsafe {
+        variants.push(string);
+    }
+    match variants.len() {
+        1 => variants.pop().unwrap(),
+        _ => json!({ "anyOf": variants }),
+    }
+}
+
+fn binary_schema(length: &LengthRange, encoding: BinaryEncoding) -> Value {
+    let mut object = Map::new();
+    object.insert("type".to_string(), json!("string"));
+    let pattern = match encoding {
+        BinaryEncoding::Base64 => {
+            "^b64:([A-Za-z0-9+/]{4})*([A-Za-z0-9+/]{2}==|[A-Za-z0-9+/]{3}=)?$"
+        }
+        BinaryEncoding::Base64Url => {
+            "^b64:([A-Za-z0-9_-]{4})*([A-Za-z0-9_-]{2}==|[A-Za-z0-9_-]{3}=)?$"
+        }
+        BinaryEncoding::Base64UrlNoPad => "^b64:([A-Za-z0-9_-]{4})*([A-Za-z0-9_-]{2,3})?$",
+        BinaryEncoding::Hex => "^0x([0-9a-f]{2})*$",
+        BinaryEncoding::HexUpper => "^0x([0-9A-F]{2})*$",
+    };
+    object.insert("pattern".to_string(), json!(pattern));
+    let encoded_len = |n: usize| match encoding {
+        BinaryEncoding::Base64 | BinaryEncoding::Base64Url => "b64:".len() + n.div_ceil(3) * 4,
+        BinaryEncoding::Base64UrlNoPad => "b64:".len() + (n * 4).div_ceil(3),
+        BinaryEncoding::Hex | BinaryEncoding::HexUpper => "0x".len() + n * 2,
+    };
+    insert_length(&mut object, "minLength", "maxLength", length, encoded_len);
+    Value::Object(object)
+}
+
+fn text_schema(schema: &TextSchema, encoding: TextEncoding) -> Value {
+    let pattern = schema.pattern.as_deref().map(glob_to_regex);
+    let text = |prefix: &str, prefix_len: usize| {
+        let mut object = Map::new();
+        object.insert("type".to_string(), json!("string"));
+        let pattern = match &pattern {
+            Some(pattern) => format!("^{}{}$", prefix, pattern),
+            None => format!("^{}", prefix),
+        };
+        if pattern != "^" {
+            object.insert("pattern".to_string(), json!(pattern));
+        }
+        insert_length(&mut object, "minLength", "maxLength", &schema.length, |n| {
+            n + prefix_len
+        });
+        object
+    };
+
+    match encoding {
+        TextEncoding::Bom => Value::Object(text("\\uFEFF", 1)),
+        TextEncoding::BomIfAmbiguous => {
+            let mut plain = text("", 0);
+            // Texts which would be read as another type are prefixed with BOM.
+            plain.insert(
+                "not".to_string(),
+                json!({ "pattern": "^(\\uFEFF|b64:|0x|[0-9-]+$)" }),
+            );
+            json!({ "anyOf": [Value::Object(plain), Value::Object(text("\\uFEFF", 1))] })
+        }
+    }
+}
+
+fn dictionary_schema(schema: &DictionarySchema, options: &JsonEncodeOptions) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in &schema.fields {
+        let key = KeyEncoder::new(&field.key, options).to_string();
+        properties.insert(key.clone(), to_json_schema_impl(&field.schema, options));
+        if field.required {
+            required.push(Value::String(key));
+        }
+    }
+
+    let mut object = Map::new();
+    object.insert("type".to_string(), json!("object"));
+    object.insert("properties".to_string(), Value::Object(properties));
+    if !required.is_empty() {
+        object.insert("required".to_string(), Value::Array(required));
+    }
+    object.insert(
+        "additionalProperties".to_string(),
+        Value::Bool(schema.allow_unknown_keys),
+    );
+    Value::Object(object)
+}
+
+fn insert_length(
+    object: &mut Map<String, Value>,
+    min_keyword: &str,
+    max_keyword: &str,
+    range: &LengthRange,
+    to_json_length: impl Fn(usize) -> usize,
+) {
+    if let Some(min) = range.min {
+        object.insert(min_keyword.to_string(), json!(to_json_length(min)));
+    }
+    if let Some(max) = range.max {
+        object.insert(max_keyword.to_string(), json!(to_json_length(max)));
+    }
+}
+
+/// Convert a glob pattern of [`TextSchema`] to an unanchored ECMA-262 regular expression.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::with_capacity(glob.len() * 2);
+    let mut chars = glob.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str("[\\s\\S]*"),
+            '?' => regex.push_str("[\\s\\S]"),
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    push_escaped(&mut regex, escaped);
+                }
+            }
+            c => push_escaped(&mut regex, c),
+        }
+    }
+    regex
+}
+
+fn push_escaped(regex: &mut String, c: char) {
+    if "\\^$.|?*+()[]{}/".contains(c) {
+        regex.push('\\');
+    }
+    regex.push(c);
+}
+
+#[cfg(test)]
+mod tests {
+    mod to_json_schema {
+        use super::super::*;
+
+        fn options() -> JsonEncodeOptions {
+            JsonEncodeOptions::default()
+        }
+
+        #[test]
+        fn root_keywords() {
+            let dialect = json!(JSON_SCHEMA_DIALECT);
+            assert_eq!(
+                json!({ "$schema": dialect }),
+                to_json_schema(&Schema::Any, options())
+            );
+            assert_eq!(
+                json!({ "$schema": dialect, "not": {} }),
+                to_json_schema(&Schema::union(vec![]), options())
+            );
+            assert_eq!(
+                json!({ "$schema": dialect, "type": "null" }),
+                to_json_schema(&Schema::Null, options())
+            );
+        }
+
+        #[test]
+        fn number() {
+            assert_eq!(
+                json!({ "type": "string", "pattern": NUMBER_PATTERN }),
+                to_json_schema_impl(&Schema::number_in(0, 10), &options())
+            );
+
+            let options = JsonEncodeOptions {
+                number_encoding: NumberEncoding::NumberIfSafe,
+                ..Default::default()
+            };
+            assert_eq!(
+                json!({ "type": "integer", "minimum": 0, "maximum": 10 }),
+                to_json_schema_impl(&Schema::number_in(0, 10), &options)
+            );
+            assert_eq!(
+                json!({ "anyOf": [
+                    { "type": "integer", "minimum": -MAX_SAFE_INTEGER, "maximum": MAX_SAFE_INTEGER },
+                    { "type": "string", "pattern": NUMBER_PATTERN },
+                ] }),
+                to_json_schema_impl(&Schema::number(), &options)
+            );
+            assert_eq!(
+                json!({ "type": "string", "pattern": NUMBER_PATTERN }),
+                to_json_schema_impl(&Schema::number_in(1i64 << 60, 1i64 << 61), &options)
+            );
+        }
+
+        #[test]
+        fn binary() {
+            for (binary_encoding, length) in [
+                (BinaryEncoding::Base64, 32),
+                (BinaryEncoding::Base64Url, 32),
+                (BinaryEncoding::Base64UrlNoPad, 31),
+                (BinaryEncoding::Hex, 42),
+                (BinaryEncoding::HexUpper, 42),
+            ] {
+                let options = JsonEncodeOptions {
+                    binary_encoding,
+                    ..Default::default()
+                };
+                let schema = to_json_schema_impl(&Schema::binary_of_length(20), &options);
+                assert_eq!(json!(length), schema["minLength"]);
+                assert_eq!(json!(length), schema["maxLength"]);
+
+                let encoded =
+                    crate::json::to_json_with_options(&vec![0xfbu8; 20].into(), options).unwrap();
+                assert_eq!(
+                    length,
+                    serde_json::from_str::<String>(&encoded)
+                        .unwrap()
+                        .chars()
+                        .count()
+                );
+            }
+        }
+
+        #[test]
+        fn text() {
+            let schema = Schema::Text(TextSchema {
+                length: LengthRange::exactly(5),
+                pattern: Some("a*.?\\*".to_string()),
+            });
+            assert_eq!(
+                json!({
+                    "type": "string",
+                    "pattern": "^\\uFEFFa[\\s\\S]*\\.[\\s\\S]\\*$",
+                    "minLength": 6,
+                    "maxLength": 6,
+                }),
+                to_json_schema_impl(&schema, &options())
+            );
+
+            let options = JsonEncodeOptions {
+                text_encoding: TextEncoding::BomIfAmbiguous,
+                ..Default::default()
+            };
+            assert_eq!(
+                json!({ "anyOf": [
+                    { "type": "string", "not": { "pattern": "^(\\uFEFF|b64:|0x|[0-9-]+$)" } },
+                    { "type": "string", "pattern": "^\\uFEFF" },
+                ] }),
+                to_json_schema_impl(&Schema::text(), &options)
+            );
+        }
+
+        #[test]
+        fn list_and_dictionary() {
+            let schema: Schema = DictionarySchema::new()
+                .required(vec![0u8], Schema::list_of(Schema::Boolean))
+                .optional("memo", Schema::union(vec![Schema::Null, Schema::Any]))
+                .allow_unknown_keys()
+                .into();
+            assert_eq!(
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "b64:AA==": { "type": "array", "items": { "type": "boolean" } },
+                        "\u{FEFF}memo": { "anyOf": [{ "type": "null" }, true] },
+                    },
+                    "required": ["b64:AA=="],
+                    "additionalProperties": true,
+                }),
+                to_json_schema_impl(&schema, &options())
+            );
+        }
+    }
+}
@@ -3,23 +3,43 @@
 extern crate alloc;
 
 pub mod codec;
+pub mod diff;
+pub mod inspect;
 mod io;
+pub mod path;
 mod prelude;
+pub mod schema;
 
-pub use codec::decode::{Decode, DecodeError, decode_borrowed};
+pub use codec::canonical;
+pub use codec::decode::{Decode, DecodeError, DecodeIter, decode_borrowed, decode_iter};
+pub use codec::dictionary;
 pub use codec::encode::Encode;
+pub use codec::merge::{MergeError, MergeResolver, MergeStrategy, merge};
 pub use codec::types::{
-    BENCODEX_NULL, BencodexDictionary, BencodexKey, BencodexList, BencodexValue,
+    BENCODEX_NULL, BencodexDictionary, BencodexKey, BencodexList, BencodexNumber, BencodexType,
+    BencodexValue,
 };
 
 #[cfg(feature = "json")]
 pub mod json;
 
+/// Decoding into a bump arena, see [`arena::decode_in`].
+#[cfg(feature = "arena")]
+pub use codec::arena;
+
 /// SIMD-accelerated decoding module.
 ///
 /// Provides `decode_simd()` function for faster Bencodex decoding using
 /// SIMD instructions on supported platforms (x86_64 SSE4.2/AVX2, AArch64 NEON).
 #[cfg(feature = "simd")]
 pub mod simd {
-    pub use crate::codec::simd::decode_simd;
+    pub use crate::codec::simd::backend::{
+        Backend, UnavailableBackendError, available_backends, force_backend, selected_backend,
+    };
+    pub use crate::codec::simd::decoder::{DEFAULT_SCALAR_THRESHOLD, SimdDecodeIter, SimdDecoder};
+    #[cfg(feature = "rayon")]
+    pub use crate::codec::simd::parallel::{MIN_PARALLEL_LEN, decode_simd_parallel};
+    pub use crate::codec::simd::tape;
+    pub use crate::codec::simd::utf8;
+    pub use crate::codec::simd::{decode_simd, decode_simd_lenient, decode_simd_with_backend};
 }
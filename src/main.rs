@@ -1,114 +1,632 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bencodex::canonical::{canonicalize, decode_lenient};
 #[cfg(not(feature = "simd"))]
-use bencodex::Decode;
-use bencodex::Encode;
-use bencodex::json::{BinaryEncoding, JsonEncodeOptions, from_json, to_json_with_options};
-use clap::Parser;
-use std::io::{Read, Write};
+use bencodex::decode_iter;
+use bencodex::diff::{DifferenceKind, diff};
+use bencodex::inspect::{inspect, statistics};
+use bencodex::json::{
+    BinaryEncoding, JsonEncodeOptions, from_json_stream, to_json_pretty_writer,
+    to_json_with_options, to_json_writer,
+};
+use bencodex::path::Path;
+use bencodex::schema::{Schema, validate};
+use bencodex::{BencodexValue, DecodeError, Encode};
+use clap::{Parser, Subcommand};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
 use std::process::ExitCode;
 
+/// The exit code when the command ran but the input did not pass, e.g., an invalid value, a
+/// difference or a missing path.
+const EXIT_MISMATCH: u8 = 1;
+/// The exit code when the command failed, e.g., an I/O error or a malformed input.
+const EXIT_ERROR: u8 = 2;
+
 /// A program to encode and decode between Bencodex and JSON.
+///
+/// Without a subcommand, it converts stdin to stdout as the `encode` or `decode` subcommand.
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Decode to Bencodex from JSON.
+    #[arg(short, long)]
+    decode: bool,
+
+    #[command(flatten)]
+    json: JsonArgs,
+
+    #[command(flatten)]
+    format: FormatArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct JsonArgs {
     /// Encode Bencodex Binary by base64 string.
     /// If not given, it will encode as hexadecimal string.
     #[arg(short, long)]
     base64: bool,
 
-    /// Decode to Bencodex from JSON.
+    /// Pretty-print the JSON output. A shorthand for `--output-format pretty`.
     #[arg(short, long)]
-    decode: bool,
+    pretty: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct FormatArgs {
+    /// The format of the Bencodex input, which may have many values.
+    #[arg(long, value_enum, default_value_t = InputFormat::Raw)]
+    input_format: InputFormat,
+
+    /// The format of the output. If not given, `json` when encoding and `raw` when decoding.
+    #[arg(long, value_enum)]
+    output_format: Option<OutputFormat>,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+enum InputFormat {
+    /// Concatenated Bencodex values. The whole input is read into memory before decoding.
+    Raw,
+    /// Hexadecimal strings of Bencodex values, one per line. Read line by line.
+    Hex,
+    /// Base64 strings of Bencodex values, one per line. Read line by line.
+    Base64,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+enum OutputFormat {
+    /// A single JSON value. It fails if the input does not have exactly one value.
+    Json,
+    /// JSON values, one per line.
+    Ndjson,
+    /// Pretty-printed JSON values.
+    Pretty,
+    /// Hexadecimal strings of Bencodex values, one per line.
+    Hex,
+    /// Base64 strings of Bencodex values, one per line.
+    Base64,
+    /// Concatenated Bencodex values.
+    Raw,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Encode Bencodex to JSON.
+    Encode {
+        /// The Bencodex file to read. If not given or `-`, read stdin.
+        input: Option<PathBuf>,
+        /// The file to write. If not given, write stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        json: JsonArgs,
+        #[command(flatten)]
+        format: FormatArgs,
+    },
+    /// Decode JSON to Bencodex.
+    Decode {
+        /// The JSON file to read, which may have many values separated by whitespace, e.g., JSON
+        /// Lines. If not given or `-`, read stdin.
+        input: Option<PathBuf>,
+        /// The file to write. If not given, write stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// The format of the output.
+        #[arg(long, value_enum, default_value_t = OutputFormat::Raw)]
+        output_format: OutputFormat,
+    },
+    /// Check if the input is a valid Bencodex value.
+    Validate {
+        /// The Bencodex file to read. If not given or `-`, read stdin.
+        input: Option<PathBuf>,
+        /// Also require the input to be canonical, i.e., sorted keys, no leading zeros and no
+        /// trailing bytes.
+        #[arg(long)]
+        canonical: bool,
+        /// A Bencodex file of a schema which the value should follow.
+        #[arg(long)]
+        schema: Option<PathBuf>,
+    },
+    /// Rewrite the input in the canonical form, and print what changed to stderr.
+    Canonicalize {
+        /// The Bencodex file to read. If not given or `-`, read stdin.
+        input: Option<PathBuf>,
+        /// The file to write. If not given, write stdout.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Only check if the input is canonical, and write nothing.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Print the type, size, depth and key statistics of the input.
+    Inspect {
+        /// The Bencodex file to read. If not given or `-`, read stdin.
+        input: Option<PathBuf>,
+    },
+    /// Print where the bytes of the input go: bytes by type, the largest values, frequent keys,
+    /// the depth and a histogram of string lengths.
+    Stats {
+        /// The Bencodex file to read. If not given or `-`, read stdin.
+        input: Option<PathBuf>,
+        /// The number of the largest values and the most frequent keys to print.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Print the value at a path as JSON.
+    Query {
+        /// The path of the value, e.g., `$.items[0][0x1234]["item id"]`.
+        path: Path,
+        /// The Bencodex file to read. If not given or `-`, read stdin.
+        input: Option<PathBuf>,
+        #[command(flatten)]
+        json: JsonArgs,
+    },
+    /// Print the differences between two Bencodex files.
+    Diff {
+        /// The Bencodex file of the left side.
+        left: PathBuf,
+        /// The Bencodex file of the right side.
+        right: PathBuf,
+        /// Encode Bencodex Binary by base64 string.
+        /// If not given, it will encode as hexadecimal string.
+        #[arg(short, long)]
+        base64: bool,
+    },
+}
+
+/// The result of a command. [`Err`] has a message to print before exiting with [`EXIT_ERROR`].
+type CommandResult = Result<ExitCode, String>;
+
 fn main() -> ExitCode {
     let args = Args::parse();
 
-    if !args.decode {
-        encode(&args)
-    } else {
-        decode()
+    let result = match args.command {
+        None if args.decode => match args.format {
+            FormatArgs {
+                input_format: InputFormat::Raw,
+                output_format,
+            } => decode(None, None, output_format.unwrap_or(OutputFormat::Raw)),
+            _ => Err("--input-format cannot be used with --decode.".to_string()),
+        },
+        None => encode(None, None, &args.json, &args.format),
+        Some(Command::Encode {
+            input,
+            output,
+            json,
+            format,
+        }) => encode(input, output, &json, &format),
+        Some(Command::Decode {
+            input,
+            output,
+            output_format,
+        }) => decode(input, output, output_format),
+        Some(Command::Validate {
+            input,
+            canonical,
+            schema,
+        }) => validate_command(input, canonical, schema),
+        Some(Command::Canonicalize {
+            input,
+            output,
+            check,
+        }) => canonicalize_command(input, output, check),
+        Some(Command::Inspect { input }) => inspect_command(input),
+        Some(Command::Stats { input, top }) => stats_command(input, top),
+        Some(Command::Query { path, input, json }) => query(&path, input, &json),
+        Some(Command::Diff {
+            left,
+            right,
+            base64,
+        }) => diff_command(&left, &right, base64),
+    };
+
+    result.unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        ExitCode::from(EXIT_ERROR)
+    })
+}
+
+fn encode(
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+    json: &JsonArgs,
+    format: &FormatArgs,
+) -> CommandResult {
+    let output_format = match (format.output_format, json.pretty) {
+        (None, false) => OutputFormat::Json,
+        (None, true) | (Some(OutputFormat::Pretty), _) => OutputFormat::Pretty,
+        (Some(_), true) => return Err("--pretty cannot be used with --output-format.".to_string()),
+        (Some(output_format), false) => output_format,
+    };
+
+    let mut writer = ValueWriter::new(open_output(output.as_ref())?, output_format, json.base64);
+    let mut decoder = ValueDecoder::default();
+    match format.input_format {
+        InputFormat::Raw => {
+            // Decoded values borrow their payloads from the input, so it is read as a whole
+            let buf = read_input(input.as_ref())?;
+            let values = decoder.decode_iter(&buf).map(|value| {
+                value.map_err(|err| format!("Failed to decode to Bencodex: {:?}", err))
+            });
+            writer.write_all(values, false)?;
+        }
+        InputFormat::Hex | InputFormat::Base64 => {
+            let input_format = format.input_format;
+            let mut lines = open_input(input.as_ref())?
+                .lines()
+                .enumerate()
+                .filter_map(|(index, line)| {
+                    let line = match line {
+                        Ok(line) => line,
+                        Err(err) => return Some(Err(format!("Failed to read: {}", err))),
+                    };
+                    let line = line.trim();
+                    if line.is_empty() {
+                        return None;
+                    }
+                    let buf = match input_format {
+                        InputFormat::Hex => hex::decode(line).map_err(|err| err.to_string()),
+                        _ => STANDARD.decode(line).map_err(|err| err.to_string()),
+                    }
+                    .map_err(|err| format!("Failed to read line {}: {}", index + 1, err));
+                    Some(buf.map(|buf| (index, buf)))
+                })
+                .peekable();
+            while let Some(line) = lines.next() {
+                let (index, buf) = line?;
+                let values = decoder.decode_iter(&buf).map(|value| {
+                    value.map_err(|err| {
+                        format!("Failed to decode line {} to Bencodex: {:?}", index + 1, err)
+                    })
+                });
+                writer.write_all(values, lines.peek().is_some())?;
+            }
+        }
     }
+    writer.finish()
 }
 
-fn decode() -> ExitCode {
-    let mut buf = Vec::new();
-    if let Err(err) = std::io::stdin().read_to_end(&mut buf) {
-        eprintln!("Failed to read from stdin: {:?}", err);
-        return ExitCode::FAILURE;
+fn decode(
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+    output_format: OutputFormat,
+) -> CommandResult {
+    let mut writer = ValueWriter::new(open_output(output.as_ref())?, output_format, false);
+    let values = from_json_stream(open_input(input.as_ref())?)
+        .map(|value| value.map_err(|err| format!("Failed to decode JSON to Bencodex: {}", err)));
+    writer.write_all(values, false)?;
+    writer.finish()
+}
+
+/// Decodes concatenated Bencodex values, with SIMD if the `simd` feature is enabled.
+#[derive(Default)]
+struct ValueDecoder {
+    #[cfg(feature = "simd")]
+    simd: bencodex::simd::SimdDecoder,
+}
+
+impl ValueDecoder {
+    #[cfg(feature = "simd")]
+    fn decode_iter<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+    ) -> impl Iterator<Item = Result<BencodexValue<'a>, DecodeError>> {
+        self.simd.decode_iter(buf)
+    }
+
+    #[cfg(not(feature = "simd"))]
+    fn decode_iter<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+    ) -> impl Iterator<Item = Result<BencodexValue<'a>, DecodeError>> {
+        decode_iter(buf)
+    }
+}
+
+/// Writes each value in an [`OutputFormat`].
+struct ValueWriter<W: Write> {
+    writer: W,
+    format: OutputFormat,
+    json_options: JsonEncodeOptions,
+    count: usize,
+}
+
+impl<W: Write> ValueWriter<W> {
+    fn new(writer: W, format: OutputFormat, base64: bool) -> Self {
+        Self {
+            writer,
+            format,
+            json_options: json_encode_options(base64),
+            count: 0,
+        }
+    }
+
+    /// Write each of `values`, where `more` tells if more values follow them.
+    ///
+    /// With [`OutputFormat::Json`], the value after each one is decoded before writing it, so an
+    /// input with more than one value fails without writing anything.
+    fn write_all<'a>(
+        &mut self,
+        mut values: impl Iterator<Item = Result<BencodexValue<'a>, String>>,
+        more: bool,
+    ) -> Result<(), String> {
+        while let Some(value) = values.next() {
+            let value = value?;
+            if self.format == OutputFormat::Json {
+                match values.next() {
+                    // Invalid input fails as such, not as one with many values
+                    Some(Err(err)) => return Err(err),
+                    Some(Ok(_)) => {}
+                    None if more => {}
+                    None => return self.write(&value),
+                }
+                return Err(
+                    "The input has more than one value. Use --output-format ndjson instead."
+                        .to_string(),
+                );
+            }
+            self.write(&value)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, value: &BencodexValue) -> Result<(), String> {
+        self.count += 1;
+        let writer = &mut self.writer;
+        match self.format {
+            OutputFormat::Json | OutputFormat::Ndjson => {
+                to_json_writer(&mut *writer, value, self.json_options)
+                    .map_err(|err| format!("Failed to encode Bencodex to JSON: {}", err))?;
+            }
+            OutputFormat::Pretty => {
+                to_json_pretty_writer(&mut *writer, value, self.json_options)
+                    .map_err(|err| format!("Failed to encode Bencodex to JSON: {}", err))?;
+            }
+            OutputFormat::Hex | OutputFormat::Base64 => {
+                let mut buf = Vec::with_capacity(value.encoded_len());
+                value
+                    .encode(&mut buf)
+                    .map_err(|err| format!("Failed to encode Bencodex: {}", err))?;
+                let encoded = match self.format {
+                    OutputFormat::Hex => hex::encode(buf),
+                    _ => STANDARD.encode(buf),
+                };
+                writer
+                    .write_all(encoded.as_bytes())
+                    .map_err(|err| format!("Failed to write: {}", err))?;
+            }
+            OutputFormat::Raw => {
+                return value
+                    .encode(writer)
+                    .map_err(|err| format!("Failed to write Bencodex: {}", err));
+            }
+        }
+        writeln!(writer).map_err(|err| format!("Failed to write: {}", err))
     }
 
-    let json = match serde_json::from_slice(&buf) {
-        Ok(x) => x,
-        Err(err) => {
-            eprintln!("Failed to parse JSON: {:?}", err);
-            return ExitCode::FAILURE;
+    fn finish(self) -> CommandResult {
+        if self.format == OutputFormat::Json && self.count == 0 {
+            return Err("The input has no value.".to_string());
         }
+        flush(self.writer)
+    }
+}
+
+fn validate_command(
+    input: Option<PathBuf>,
+    canonical: bool,
+    schema: Option<PathBuf>,
+) -> CommandResult {
+    let schema = match schema {
+        Some(path) => {
+            let value = decode_bencodex(read_input(Some(&path))?)?;
+            Some(Schema::from_bencodex(&value).map_err(|err| format!("Invalid schema: {}", err))?)
+        }
+        None => None,
     };
 
-    let bencodex_value = match from_json(&json) {
-        Ok(x) => x,
-        Err(err) => {
-            eprintln!("Failed to decode JSON to Bencodex: {:?}", err);
-            return ExitCode::FAILURE;
+    let buf = read_input(input.as_ref())?;
+    let value = match decode_bencodex(buf.clone()) {
+        Ok(value) => value,
+        Err(message) => {
+            println!("{}", message);
+            return Ok(ExitCode::from(EXIT_MISMATCH));
         }
     };
 
-    buf = Vec::new();
-    if let Err(err) = bencodex_value.encode(&mut buf) {
-        eprintln!("Failed to encode Bencodex to binary: {:?}", err);
-        return ExitCode::FAILURE;
+    if canonical {
+        let (_, issues) = decode_lenient(&buf)
+            .map_err(|err| format!("Failed to decode to Bencodex: {:?}", err))?;
+        if !issues.is_empty() {
+            for issue in issues {
+                println!("Not canonical: {}.", issue);
+            }
+            return Ok(ExitCode::from(EXIT_MISMATCH));
+        }
     }
 
-    if let Err(err) = std::io::stdout().write_all(&buf) {
-        eprintln!("Failed to write to stdout: {:?}", err);
-        return ExitCode::FAILURE;
+    if let Some(schema) = schema
+        && let Err(violations) = validate(&value, &schema)
+    {
+        for violation in violations {
+            println!("{}", violation);
+        }
+        return Ok(ExitCode::from(EXIT_MISMATCH));
     }
 
-    ExitCode::SUCCESS
+    println!("The input is valid.");
+    Ok(ExitCode::SUCCESS)
 }
 
-fn encode(args: &Args) -> ExitCode {
-    let mut buf = Vec::new();
-    if let Err(err) = std::io::stdin().read_to_end(&mut buf) {
-        eprintln!("Failed to read from stdin: {:?}", err);
-        return ExitCode::FAILURE;
+fn canonicalize_command(
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+    check: bool,
+) -> CommandResult {
+    let buf = read_input(input.as_ref())?;
+    let (canonical, issues) =
+        canonicalize(&buf).map_err(|err| format!("Failed to decode to Bencodex: {:?}", err))?;
+    for issue in &issues {
+        eprintln!("{}", issue);
     }
 
-    #[cfg(feature = "simd")]
-    let decoded = match bencodex::simd::decode_simd(&buf) {
-        Ok(value) => value,
-        Err(err) => {
-            eprintln!("Failed to decode to Bencodex: {:?}", err);
-            return ExitCode::FAILURE;
-        }
+    if check {
+        return Ok(if issues.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(EXIT_MISMATCH)
+        });
+    }
+
+    let mut writer = open_output(output.as_ref())?;
+    writer
+        .write_all(&canonical)
+        .map_err(|err| format!("Failed to write: {}", err))?;
+    flush(writer)
+}
+
+fn inspect_command(input: Option<PathBuf>) -> CommandResult {
+    let value = decode_bencodex(read_input(input.as_ref())?)?;
+    println!("{}", inspect(&value));
+    Ok(ExitCode::SUCCESS)
+}
+
+fn stats_command(input: Option<PathBuf>, top: usize) -> CommandResult {
+    let value = decode_bencodex(read_input(input.as_ref())?)?;
+    println!("{}", statistics(&value, top));
+    Ok(ExitCode::SUCCESS)
+}
+
+fn query(path: &Path, input: Option<PathBuf>, json: &JsonArgs) -> CommandResult {
+    let value = decode_bencodex(read_input(input.as_ref())?)?;
+    let Some(found) = path.resolve(&value) else {
+        eprintln!("No value at {}.", path);
+        return Ok(ExitCode::from(EXIT_MISMATCH));
     };
 
-    #[cfg(not(feature = "simd"))]
-    let decoded = match buf.decode() {
-        Ok(value) => value,
-        Err(err) => {
-            eprintln!("Failed to decode to Bencodex: {:?}", err);
-            return ExitCode::FAILURE;
-        }
+    let mut writer = open_output(None)?;
+    write_json(&mut writer, found, json)?;
+    flush(writer)
+}
+
+fn diff_command(left: &PathBuf, right: &PathBuf, base64: bool) -> CommandResult {
+    let left = decode_bencodex(read_input(Some(left))?)?;
+    let right = decode_bencodex(read_input(Some(right))?)?;
+    let differences = diff(&left, &right);
+
+    let options = json_encode_options(base64);
+    let to_json = |value: &BencodexValue| {
+        to_json_with_options(value, options)
+            .map_err(|err| format!("Failed to encode Bencodex to JSON: {}", err))
     };
+    let mut writer = open_output(None)?;
+    for difference in &differences {
+        let path = &difference.path;
+        let lines = match difference.kind {
+            DifferenceKind::Added(value) => format!("+ {}: {}", path, to_json(value)?),
+            DifferenceKind::Removed(value) => format!("- {}: {}", path, to_json(value)?),
+            DifferenceKind::Changed { left, right } => format!(
+                "- {}: {}\n+ {}: {}",
+                path,
+                to_json(left)?,
+                path,
+                to_json(right)?
+            ),
+        };
+        writeln!(writer, "{}", lines).map_err(|err| format!("Failed to write: {}", err))?;
+    }
+    flush(writer)?;
 
-    let json_encode_options = JsonEncodeOptions {
-        binary_encoding: if args.base64 {
+    Ok(if differences.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_MISMATCH)
+    })
+}
+
+fn is_stdin(path: &std::path::Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn open_input(path: Option<&PathBuf>) -> Result<Box<dyn BufRead>, String> {
+    Ok(match path.filter(|path| !is_stdin(path)) {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).map_err(|err| format!("Failed to open {:?}: {}", path, err))?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    })
+}
+
+fn read_input(path: Option<&PathBuf>) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::new();
+    match path.filter(|path| !is_stdin(path)) {
+        Some(path) => File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut buf))
+            .map_err(|err| format!("Failed to read {:?}: {}", path, err))?,
+        None => std::io::stdin()
+            .read_to_end(&mut buf)
+            .map_err(|err| format!("Failed to read from stdin: {}", err))?,
+    };
+    Ok(buf)
+}
+
+fn open_output(path: Option<&PathBuf>) -> Result<BufWriter<Box<dyn Write>>, String> {
+    let writer: Box<dyn Write> = match path {
+        Some(path) => Box::new(
+            File::create(path).map_err(|err| format!("Failed to create {:?}: {}", path, err))?,
+        ),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    Ok(BufWriter::new(writer))
+}
+
+fn flush(mut writer: impl Write) -> CommandResult {
+    writer
+        .flush()
+        .map_err(|err| format!("Failed to write: {}", err))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Decode the only value in `buf`, failing if there are bytes after it.
+fn decode_bencodex(buf: Vec<u8>) -> Result<BencodexValue<'static>, String> {
+    let mut decoder = ValueDecoder::default();
+    let mut values = decoder.decode_iter(&buf);
+    let value = values
+        .next()
+        .unwrap_or(Err(DecodeError::InvalidBencodexValueError))
+        .map_err(|err| format!("Failed to decode to Bencodex: {:?}", err))?;
+    if values.next().is_some() {
+        return Err("Failed to decode to Bencodex: trailing data after the value.".to_string());
+    }
+    Ok(value.into_owned())
+}
+
+fn json_encode_options(base64: bool) -> JsonEncodeOptions {
+    JsonEncodeOptions {
+        binary_encoding: if base64 {
             BinaryEncoding::Base64
         } else {
             BinaryEncoding::Hex
         },
-    };
+        ..Default::default()
+    }
+}
 
-    let json_str = match to_json_with_options(&decoded, json_encode_options) {
-        Ok(json_str) => json_str,
-        Err(err) => {
-            eprintln!("Failed to encode Bencodex to JSON: {:?}", err);
-            return ExitCode::FAILURE;
-        }
+fn write_json(
+    writer: &mut impl Write,
+    value: &BencodexValue,
+    json: &JsonArgs,
+) -> Result<(), String> {
+    let options = json_encode_options(json.base64);
+    let result = if json.pretty {
+        to_json_pretty_writer(&mut *writer, value, options)
+    } else {
+        to_json_writer(&mut *writer, value, options)
     };
-
-    println!("{}", json_str);
-
-    ExitCode::SUCCESS
+    result.map_err(|err| format!("Failed to encode Bencodex to JSON: {}", err))?;
+    writeln!(writer).map_err(|err| format!("Failed to write: {}", err))
 }
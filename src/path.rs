@@ -0,0 +1,419 @@
+//! Paths to locate a value inside of a Bencodex tree.
+
+use crate::codec::types::*;
+use crate::prelude::*;
+use core::error::Error;
+use core::fmt;
+use core::iter::Peekable;
+use core::str::{CharIndices, FromStr};
+
+/// A step from a container value to one of its children.
+#[derive(PartialEq, Eq, Debug, PartialOrd, Ord, Clone)]
+pub enum PathSegment {
+    /// A key of a [`BencodexValue::Dictionary`].
+    Key(BencodexKey<'static>),
+    /// An index of a [`BencodexValue::List`].
+    Index(usize),
+}
+
+/// A list of [`PathSegment`]s from the root value.
+///
+/// It is displayed as `$` followed by the segments. Text keys which are valid identifiers are
+/// written as `.key`, and the other keys and indices are written in brackets.
+///
+/// ```
+/// use bencodex::BencodexKey;
+/// use bencodex::path::{ Path, PathSegment };
+///
+/// let mut path = Path::root();
+/// path.push(PathSegment::Key("items".into()));
+/// path.push(PathSegment::Index(0));
+/// path.push(PathSegment::Key(BencodexKey::from(vec![0x12u8, 0x34])));
+/// path.push(PathSegment::Key("item id".into()));
+/// assert_eq!(path.to_string(), "$.items[0][0x1234][\"item id\"]");
+/// assert_eq!(path.to_string().parse(), Ok(path));
+/// ```
+#[derive(PartialEq, Eq, Debug, PartialOrd, Ord, Clone, Default)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Create a path pointing the root value.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Get the segments of this path.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Check if this path points the root value.
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Append a segment to the end of this path.
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    /// Remove the last segment of this path.
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// Return a new path appending `segment` to this path.
+    pub fn join(&self, segment: PathSegment) -> Self {
+        let mut path = self.clone();
+        path.push(segment);
+        path
+    }
+}
+
+impl Path {
+    /// Get the value which this path points inside of `value`, or `None` if it does not exist.
+    ///
+    /// ```
+    /// use bencodex::{ BencodexDictionary, BencodexValue };
+    /// use bencodex::path::Path;
+    ///
+    /// let mut dict = BencodexDictionary::new();
+    /// dict.insert("items".into(), BencodexValue::List(vec!["a".into(), "b".into()]));
+    /// let value = BencodexValue::Dictionary(dict);
+    ///
+    /// let path: Path = "$.items[1]".parse().unwrap();
+    /// assert_eq!(path.resolve(&value), Some(&"b".into()));
+    /// ```
+    pub fn resolve<'v, 'a>(&self, value: &'v BencodexValue<'a>) -> Option<&'v BencodexValue<'a>> {
+        self.segments
+            .iter()
+            .try_fold(value, |value, segment| match (segment, value) {
+                (PathSegment::Index(index), BencodexValue::List(list)) => list.get(*index),
+                (PathSegment::Key(key), BencodexValue::Dictionary(map)) => map.get(key),
+                _ => None,
+            })
+    }
+}
+
+impl From<Vec<PathSegment>> for Path {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self { segments }
+    }
+}
+
+impl From<BencodexKey<'_>> for PathSegment {
+    fn from(key: BencodexKey<'_>) -> Self {
+        PathSegment::Key(key.into_owned())
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Index(index) => write!(f, "[{}]", index),
+            PathSegment::Key(BencodexKey::Text(text)) if is_identifier(text) => {
+                write!(f, ".{}", text)
+            }
+            PathSegment::Key(BencodexKey::Text(text)) => write!(f, "[{:?}]", text),
+            PathSegment::Key(BencodexKey::Binary(bytes)) => {
+                f.write_str("[0x")?;
+                for byte in bytes.iter() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("$")?;
+        for segment in &self.segments {
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error type which is returned from parsing a [`Path`] through [`str::parse`].
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum PathParseError {
+    /// This should be used when the path does not start with `$`.
+    MissingRootError,
+    /// This should be used when an unexpected character appeared at `point`, in bytes.
+    UnexpectedCharError { char: char, point: usize },
+    /// This should be used when the path ended in the middle of a segment.
+    UnexpectedEndError,
+}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathParseError::MissingRootError => write!(f, "path should start with '$'"),
+            PathParseError::UnexpectedCharError { char, point } => {
+                write!(f, "unexpected character {:?} at {}", char, point)
+            }
+            PathParseError::UnexpectedEndError => write!(f, "unexpected end of path"),
+        }
+    }
+}
+
+impl Error for PathParseError {}
+
+/// Parse the format written by [`Display`](fmt::Display), e.g., `$.items[0][0x1234]["item id"]`.
+///
+/// Quoted keys accept the escapes of Rust string literals, i.e., `\"`, `\\`, `\'`, `\n`,
+/// `\r`, `\t`, `\0` and `\u{...}`.
+impl FromStr for Path {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.char_indices().peekable();
+        match chars.next() {
+            Some((_, '$')) => {}
+            _ => return Err(PathParseError::MissingRootError),
+        }
+
+        let unexpected =
+            |(point, char): (usize, char)| PathParseError::UnexpectedCharError { char, point };
+        let mut path = Path::root();
+        while let Some((point, c)) = chars.next() {
+            let segment = match c {
+                '.' => {
+                    let start = point + 1;
+                    let end =
+                        skip_while(&mut chars, start, |c| c.is_ascii_alphanumeric() || c == '_');
+                    match s[start..end].chars().next() {
+                        None => {
+                            return Err(chars
+                                .next()
+                                .map_or(PathParseError::UnexpectedEndError, unexpected));
+                        }
+                        Some(c) if c.is_ascii_digit() => return Err(unexpected((start, c))),
+                        Some(_) => PathSegment::Key(BencodexKey::Text(Cow::Owned(
+                            s[start..end].to_string(),
+                        ))),
+                    }
+                }
+                '[' => {
+                    let segment = match chars.next() {
+                        Some((_, '"')) => parse_quoted(&mut chars)?,
+                        Some((zero, '0')) if chars.peek().is_some_and(|&(_, c)| c == 'x') => {
+                            chars.next();
+                            let start = zero + "0x".len();
+                            let end = skip_while(&mut chars, start, |c| c.is_ascii_hexdigit());
+                            let hex = &s[start..end];
+                            if hex.len() % 2 == 1 {
+                                return Err(chars
+                                    .next()
+                                    .map_or(PathParseError::UnexpectedEndError, unexpected));
+                            }
+                            let bytes = (0..hex.len())
+                                .step_by(2)
+                                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+                                .collect();
+                            PathSegment::Key(BencodexKey::Binary(Cow::Owned(bytes)))
+                        }
+                        Some((start, c)) if c.is_ascii_digit() => {
+                            let end = skip_while(&mut chars, start + 1, |c| c.is_ascii_digit());
+                            match s[start..end].parse() {
+                                Ok(index) => PathSegment::Index(index),
+                                Err(_) => return Err(unexpected((start, c))),
+                            }
+                        }
+                        Some(other) => return Err(unexpected(other)),
+                        None => return Err(PathParseError::UnexpectedEndError),
+                    };
+                    match chars.next() {
+                        Some((_, ']')) => segment,
+                        Some(other) => return Err(unexpected(other)),
+                        None => return Err(PathParseError::UnexpectedEndError),
+                    }
+                }
+                c => return Err(unexpected((point, c))),
+            };
+            path.push(segment);
+        }
+        Ok(path)
+    }
+}
+
+/// Consume characters while `predicate` holds and return the end of them in bytes.
+fn skip_while(
+    chars: &mut Peekable<CharIndices<'_>>,
+    start: usize,
+    predicate: impl Fn(char) -> bool,
+) -> usize {
+    let mut end = start;
+    while let Some(&(i, c)) = chars.peek()
+        && predicate(c)
+    {
+        chars.next();
+        end = i + c.len_utf8();
+    }
+    end
+}
+
+/// Parse a quoted text key after its opening `"` until its closing `"`.
+fn parse_quoted(chars: &mut Peekable<CharIndices<'_>>) -> Result<PathSegment, PathParseError> {
+    let unexpected =
+        |(point, char): (usize, char)| PathParseError::UnexpectedCharError { char, point };
+    let mut key = String::new();
+    loop {
+        match chars.next().ok_or(PathParseError::UnexpectedEndError)? {
+            (_, '"') => break,
+            (_, '\\') => {
+                let (point, c) = chars.next().ok_or(PathParseError::UnexpectedEndError)?;
+                key.push(match c {
+                    '"' | '\\' | '\'' => c,
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    '0' => '\0',
+                    'u' => {
+                        match chars.next().ok_or(PathParseError::UnexpectedEndError)? {
+                            (_, '{') => {}
+                            other => return Err(unexpected(other)),
+                        }
+                        let mut code: u32 = 0;
+                        loop {
+                            let (i, c) = chars.next().ok_or(PathParseError::UnexpectedEndError)?;
+                            match c {
+                                '}' => break,
+                                c => match c.to_digit(16) {
+                                    Some(digit) if code <= 0x10FFFF => code = code << 4 | digit,
+                                    _ => return Err(unexpected((i, c))),
+                                },
+                            }
+                        }
+                        char::from_u32(code)
+                            .ok_or(PathParseError::UnexpectedCharError { char: 'u', point })?
+                    }
+                    _ => return Err(unexpected((point, c))),
+                });
+            }
+            (_, c) => key.push(c),
+        }
+    }
+    Ok(PathSegment::Key(BencodexKey::Text(Cow::Owned(key))))
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod display_impl {
+        use super::super::*;
+        use alloc::vec;
+
+        #[test]
+        fn root() {
+            assert_eq!("$", Path::root().to_string());
+        }
+
+        #[test]
+        fn segments() {
+            let path = Path::from(vec![
+                PathSegment::Key("a".into()),
+                PathSegment::Index(3),
+                PathSegment::Key("0".into()),
+                PathSegment::Key("with \"quote\"".into()),
+                PathSegment::Key(BencodexKey::Binary(Cow::Owned(vec![0, 255]))),
+            ]);
+            assert_eq!(
+                "$.a[3][\"0\"][\"with \\\"quote\\\"\"][0x00ff]",
+                path.to_string()
+            );
+        }
+    }
+    mod from_str {
+        use super::super::*;
+        use alloc::vec;
+
+        #[test]
+        fn should_parse_displayed_path() {
+            let path = Path::from(vec![
+                PathSegment::Key("a_1".into()),
+                PathSegment::Index(30),
+                PathSegment::Key("0".into()),
+                PathSegment::Key("with \"quote\"\n\u{200B}\\".into()),
+                PathSegment::Key(BencodexKey::Binary(Cow::Owned(vec![0, 255]))),
+                PathSegment::Key(BencodexKey::Binary(Cow::Owned(vec![]))),
+                PathSegment::Key("\u{AC00}".into()),
+            ]);
+            assert_eq!(Ok(path.clone()), path.to_string().parse());
+            assert_eq!(Ok(Path::root()), "$".parse());
+        }
+
+        #[test]
+        fn should_return_error_with_invalid_path() {
+            assert_eq!(Err(PathParseError::MissingRootError), ".a".parse::<Path>());
+            for (path, char, point) in [
+                ("$a", 'a', 1),
+                ("$.0", '0', 2),
+                ("$.a-b", '-', 3),
+                ("$..", '.', 2),
+                ("$[0x123]", ']', 7),
+                ("$[-1]", '-', 2),
+                ("$[1x]", 'x', 3),
+                ("$[\"\\q\"]", 'q', 4),
+            ] {
+                assert_eq!(
+                    Err(PathParseError::UnexpectedCharError { char, point }),
+                    path.parse::<Path>(),
+                    "{}",
+                    path
+                );
+            }
+            for path in ["$.", "$[", "$[0", "$[\"a", "$[\"a\""] {
+                assert_eq!(
+                    Err(PathParseError::UnexpectedEndError),
+                    path.parse::<Path>(),
+                    "{}",
+                    path
+                );
+            }
+        }
+    }
+
+    mod resolve {
+        use super::super::*;
+        use alloc::vec;
+
+        #[test]
+        fn should_return_value_at_path() {
+            let mut inner = BencodexDictionary::new();
+            inner.insert(vec![0x12u8].into(), true.into());
+            let mut map = BencodexDictionary::new();
+            map.insert(
+                "items".into(),
+                BencodexValue::List(vec![BencodexValue::Dictionary(inner)]),
+            );
+            let value = BencodexValue::Dictionary(map);
+
+            let resolve = |path: &str| path.parse::<Path>().unwrap().resolve(&value).cloned();
+            assert_eq!(Some(value.clone()), resolve("$"));
+            assert_eq!(Some(true.into()), resolve("$.items[0][0x12]"));
+            assert_eq!(None, resolve("$.items[1]"));
+            assert_eq!(None, resolve("$.items.a"));
+            assert_eq!(None, resolve("$[0]"));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub use alloc::borrow::Cow;
+pub use alloc::boxed::Box;
 pub use alloc::collections::BTreeMap;
 pub use alloc::string::{String, ToString};
 pub use alloc::vec::Vec;
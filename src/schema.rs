@@ -0,0 +1,1083 @@
+//! Schema definition and validation for Bencodex values.
+//!
+//! A [`Schema`] describes the expected shape of a [`BencodexValue`]. It can be built in Rust or
+//! loaded from its own Bencodex representation with [`Schema::from_bencodex`], and
+//! [`validate`] reports every place where a value does not follow it.
+//!
+//! ```
+//! use bencodex::BencodexValue;
+//! use bencodex::schema::{ DictionarySchema, Schema, validate };
+//!
+//! let schema: Schema = DictionarySchema::new()
+//!     .required("address", Schema::binary_of_length(20))
+//!     .optional("memo", Schema::text())
+//!     .into();
+//!
+//! let mut value = bencodex::BencodexDictionary::new();
+//! value.insert("address".into(), vec![0u8; 20].into());
+//! assert!(validate(&BencodexValue::Dictionary(value), &schema).is_ok());
+//!
+//! let violations = validate(&BencodexValue::Null, &schema).unwrap_err();
+//! assert_eq!(violations[0].to_string(), "$: expected dictionary but got null");
+//! ```
+
+use crate::codec::types::*;
+use crate::path::{Path, PathSegment};
+use crate::prelude::*;
+use alloc::vec;
+use core::error::Error;
+use core::fmt;
+use core::result::Result;
+
+/// The expected shape of a [`BencodexValue`].
+#[derive(PartialEq, Debug, Clone)]
+pub enum Schema {
+    /// Any value.
+    Any,
+    /// [`BencodexValue::Null`].
+    Null,
+    /// [`BencodexValue::Boolean`].
+    Boolean,
+    /// [`BencodexValue::Number`] within an optional range.
+    Number(NumberSchema),
+    /// [`BencodexValue::Binary`] with an optional length range in bytes.
+    Binary(LengthRange),
+    /// [`BencodexValue::Text`].
+    Text(TextSchema),
+    /// [`BencodexValue::List`] whose items follow a schema.
+    List(ListSchema),
+    /// [`BencodexValue::Dictionary`] with known keys.
+    Dictionary(DictionarySchema),
+    /// A value following at least one of the schemas.
+    Union(Vec<Schema>),
+}
+
+/// An inclusive range of length. `None` means unbounded.
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct LengthRange {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+/// The schema of [`BencodexValue::Number`]. Both bounds are inclusive and `None` means unbounded.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct NumberSchema {
+    pub min: Option<BencodexNumber>,
+    pub max: Option<BencodexNumber>,
+}
+
+/// The schema of [`BencodexValue::Text`].
+///
+/// `length` is counted in Unicode scalar values. `pattern` is a glob pattern where `*` matches
+/// any sequence of characters, `?` matches a single character and `\` escapes the next one.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct TextSchema {
+    pub length: LengthRange,
+    pub pattern: Option<String>,
+}
+
+/// The schema of [`BencodexValue::List`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct ListSchema {
+    pub items: Box<Schema>,
+    pub length: LengthRange,
+}
+
+/// The schema of [`BencodexValue::Dictionary`].
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct DictionarySchema {
+    pub fields: Vec<FieldSchema>,
+    /// Whether keys not listed in `fields` are allowed.
+    pub allow_unknown_keys: bool,
+}
+
+/// A key of [`DictionarySchema`] and the schema of its value.
+#[derive(PartialEq, Debug, Clone)]
+pub struct FieldSchema {
+    pub key: BencodexKey<'static>,
+    pub schema: Schema,
+    pub required: bool,
+}
+
+impl LengthRange {
+    /// A range which only allows `length`.
+    pub fn exactly(length: usize) -> Self {
+        Self {
+            min: Some(length),
+            max: Some(length),
+        }
+    }
+
+    fn contains(&self, length: usize) -> bool {
+        self.min.is_none_or(|min| min <= length) && self.max.is_none_or(|max| length <= max)
+    }
+}
+
+impl Schema {
+    /// A number schema without bounds.
+    pub fn number() -> Self {
+        Schema::Number(NumberSchema::default())
+    }
+
+    /// A number schema allowing from `min` to `max`, inclusive.
+    pub fn number_in(min: impl Into<BencodexNumber>, max: impl Into<BencodexNumber>) -> Self {
+        Schema::Number(NumberSchema {
+            min: Some(min.into()),
+            max: Some(max.into()),
+        })
+    }
+
+    /// A binary schema without length limit.
+    pub fn binary() -> Self {
+        Schema::Binary(LengthRange::default())
+    }
+
+    /// A binary schema allowing only `length` bytes, e.g., 20 for addresses and 32 for hashes.
+    pub fn binary_of_length(length: usize) -> Self {
+        Schema::Binary(LengthRange::exactly(length))
+    }
+
+    /// A text schema without limit.
+    pub fn text() -> Self {
+        Schema::Text(TextSchema::default())
+    }
+
+    /// A text schema matching the glob `pattern`. See [`TextSchema`] for its syntax.
+    pub fn text_matching(pattern: impl Into<String>) -> Self {
+        Schema::Text(TextSchema {
+            length: LengthRange::default(),
+            pattern: Some(pattern.into()),
+        })
+    }
+
+    /// A list schema whose items follow `items`.
+    pub fn list_of(items: Schema) -> Self {
+        Schema::List(ListSchema {
+            items: Box::new(items),
+            length: LengthRange::default(),
+        })
+    }
+
+    /// A schema accepting a value following any of `variants`.
+    pub fn union(variants: Vec<Schema>) -> Self {
+        Schema::Union(variants)
+    }
+}
+
+impl DictionarySchema {
+    /// Create a dictionary schema without fields, which does not allow unknown keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a required field.
+    pub fn required(mut self, key: impl Into<BencodexKey<'static>>, schema: Schema) -> Self {
+        self.fields.push(FieldSchema {
+            key: key.into(),
+            schema,
+            required: true,
+        });
+        self
+    }
+
+    /// Add an optional field.
+    pub fn optional(mut self, key: impl Into<BencodexKey<'static>>, schema: Schema) -> Self {
+        self.fields.push(FieldSchema {
+            key: key.into(),
+            schema,
+            required: false,
+        });
+        self
+    }
+
+    /// Allow keys which are not listed in the fields.
+    pub fn allow_unknown_keys(mut self) -> Self {
+        self.allow_unknown_keys = true;
+        self
+    }
+}
+
+impl From<DictionarySchema> for Schema {
+    fn from(schema: DictionarySchema) -> Self {
+        Schema::Dictionary(schema)
+    }
+}
+
+/// A reason why a value does not follow a [`Schema`].
+#[derive(PartialEq, Debug, Clone)]
+pub enum ViolationKind {
+    /// The value has another type.
+    TypeMismatch {
+        expected: BencodexType,
+        actual: BencodexType,
+    },
+    /// The required key does not exist. The path points the missing key.
+    MissingKey,
+    /// The key is not allowed by the dictionary schema. The path points the key.
+    UnexpectedKey,
+    /// The length of binary, text or list is out of range.
+    LengthOutOfRange { range: LengthRange, actual: usize },
+    /// The number is out of range.
+    NumberOutOfRange {
+        min: Option<BencodexNumber>,
+        max: Option<BencodexNumber>,
+        actual: BencodexNumber,
+    },
+    /// The text does not match the pattern.
+    PatternMismatch { pattern: String },
+    /// The value does not follow any variant of the union.
+    NoMatchingVariant,
+}
+
+/// A place where a value does not follow a [`Schema`], returned by [`validate`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct Violation {
+    pub path: Path,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.kind {
+            ViolationKind::TypeMismatch { expected, actual } => {
+                write!(f, "expected {} but got {}", expected, actual)
+            }
+            ViolationKind::MissingKey => write!(f, "missing required key"),
+            ViolationKind::UnexpectedKey => write!(f, "unexpected key"),
+            ViolationKind::LengthOutOfRange { range, actual } => {
+                write!(f, "length {} is out of range", actual)?;
+                write_range(f, range.min.as_ref(), range.max.as_ref())
+            }
+            ViolationKind::NumberOutOfRange { min, max, actual } => {
+                write!(f, "number {} is out of range", actual)?;
+                write_range(f, min.as_ref(), max.as_ref())
+            }
+            ViolationKind::PatternMismatch { pattern } => {
+                write!(f, "text does not match pattern {:?}", pattern)
+            }
+            ViolationKind::NoMatchingVariant => write!(f, "no variant of the union matched"),
+        }
+    }
+}
+
+fn write_range<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    min: Option<&T>,
+    max: Option<&T>,
+) -> fmt::Result {
+    match (min, max) {
+        (Some(min), Some(max)) => write!(f, " [{}, {}]", min, max),
+        (Some(min), None) => write!(f, " [{}, ...]", min),
+        (None, Some(max)) => write!(f, " [..., {}]", max),
+        (None, None) => Ok(()),
+    }
+}
+
+/// Validate `value` with `schema`.
+///
+/// If the value follows the schema, return [`Ok`]. Otherwise, return every [`Violation`] found
+/// inside [`Err`].
+pub fn validate(value: &BencodexValue<'_>, schema: &Schema) -> Result<(), Vec<Violation>> {
+    let mut violations = Vec::new();
+    let mut path = Path::root();
+    validate_impl(value, schema, &mut path, &mut violations);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+fn validate_impl(
+    value: &BencodexValue<'_>,
+    schema: &Schema,
+    path: &mut Path,
+    violations: &mut Vec<Violation>,
+) {
+    let mut violate = |path: &Path, kind: ViolationKind| {
+        violations.push(Violation {
+            path: path.clone(),
+            kind,
+        })
+    };
+
+    match (schema, value) {
+        (Schema::Any, _) | (Schema::Null, BencodexValue::Null) => {}
+        (Schema::Boolean, BencodexValue::Boolean(_)) => {}
+        (Schema::Number(schema), BencodexValue::Number(n)) => {
+            let too_small = schema.min.as_ref().is_some_and(|min| n < min);
+            let too_large = schema.max.as_ref().is_some_and(|max| n > max);
+            if too_small || too_large {
+                violate(
+                    path,
+                    ViolationKind::NumberOutOfRange {
+                        min: schema.min.clone(),
+                        max: schema.max.clone(),
+                        actual: n.clone(),
+                    },
+                );
+            }
+        }
+        (Schema::Binary(range), BencodexValue::Binary(bytes)) => {
+            if !range.contains(bytes.len()) {
+                violate(
+                    path,
+                    ViolationKind::LengthOutOfRange {
+                        range: *range,
+                        actual: bytes.len(),
+                    },
+                );
+            }
+        }
+        (Schema::Text(schema), BencodexValue::Text(text)) => {
+            let length = text.chars().count();
+            if !schema.length.contains(length) {
+                violate(
+                    path,
+                    ViolationKind::LengthOutOfRange {
+                        range: schema.length,
+                        actual: length,
+                    },
+                );
+            }
+            if let Some(pattern) = &schema.pattern
+                && !glob_match(pattern, text)
+            {
+                violate(
+                    path,
+                    ViolationKind::PatternMismatch {
+                        pattern: pattern.clone(),
+                    },
+                );
+            }
+        }
+        (Schema::List(schema), BencodexValue::List(items)) => {
+            if !schema.length.contains(items.len()) {
+                violate(
+                    path,
+                    ViolationKind::LengthOutOfRange {
+                        range: schema.length,
+                        actual: items.len(),
+                    },
+                );
+            }
+            for (index, item) in items.iter().enumerate() {
+                path.push(PathSegment::Index(index));
+                validate_impl(item, &schema.items, path, violations);
+                path.pop();
+            }
+        }
+        (Schema::Dictionary(schema), BencodexValue::Dictionary(map)) => {
+            for field in &schema.fields {
+                path.push(PathSegment::Key(field.key.clone()));
+                match map.get(&field.key) {
+                    Some(value) => validate_impl(value, &field.schema, path, violations),
+                    None if field.required => violations.push(Violation {
+                        path: path.clone(),
+                        kind: ViolationKind::MissingKey,
+                    }),
+                    None => {}
+                }
+                path.pop();
+            }
+            if !schema.allow_unknown_keys {
+                for key in map.keys() {
+                    if !schema.fields.iter().any(|field| &field.key == key) {
+                        violations.push(Violation {
+                            path: path.join(key.clone().into()),
+                            kind: ViolationKind::UnexpectedKey,
+                        });
+                    }
+                }
+            }
+        }
+        (Schema::Union(variants), value) => {
+            let matched = variants.iter().any(|variant| {
+                let mut nested = Vec::new();
+                validate_impl(value, variant, path, &mut nested);
+                nested.is_empty()
+            });
+            if !matched {
+                violate(path, ViolationKind::NoMatchingVariant);
+            }
+        }
+        (schema, value) => violate(
+            path,
+            ViolationKind::TypeMismatch {
+                expected: schema
+                    .value_type()
+                    .expect("`Any` and `Union` accept every type and are matched above"),
+                actual: value.value_type(),
+            },
+        ),
+    }
+}
+
+impl Schema {
+    /// Get the type which this schema expects. `None` for [`Schema::Any`] and [`Schema::Union`].
+    pub fn value_type(&self) -> Option<BencodexType> {
+        match self {
+            Schema::Any | Schema::Union(_) => None,
+            Schema::Null => Some(BencodexType::Null),
+            Schema::Boolean => Some(BencodexType::Boolean),
+            Schema::Number(_) => Some(BencodexType::Number),
+            Schema::Binary(_) => Some(BencodexType::Binary),
+            Schema::Text(_) => Some(BencodexType::Text),
+            Schema::List(_) => Some(BencodexType::List),
+            Schema::Dictionary(_) => Some(BencodexType::Dictionary),
+        }
+    }
+}
+
+impl Schema {
+    /// Infer a schema which every value of `samples` follows.
+    ///
+    /// Numbers, binaries and texts are inferred without bounds since samples are not exhaustive.
+    /// A dictionary key is required only if it appears in every sampled dictionary, and values of
+    /// different types are inferred as a [`Schema::Union`] with a variant per type. Inferring from
+    /// no samples returns an empty union, which no value follows.
+    ///
+    /// ```
+    /// use bencodex::BencodexValue;
+    /// use bencodex::schema::Schema;
+    ///
+    /// let samples: Vec<BencodexValue> = vec![1.into(), "foo".into(), 2.into()];
+    /// assert_eq!(
+    ///     Schema::infer(&samples),
+    ///     Schema::union(vec![Schema::number(), Schema::text()])
+    /// );
+    /// ```
+    pub fn infer<'v, 'a: 'v>(samples: impl IntoIterator<Item = &'v BencodexValue<'a>>) -> Schema {
+        samples
+            .into_iter()
+            .fold(Schema::Union(Vec::new()), |schema, value| {
+                schema.unite(infer_value(value))
+            })
+    }
+
+    /// Unite two inferred schemas into a schema which values of both follow.
+    fn unite(self, other: Schema) -> Schema {
+        match (self, other) {
+            (Schema::Any, _) | (_, Schema::Any) => Schema::Any,
+            (Schema::Union(mut variants), other) => {
+                let others = match other {
+                    Schema::Union(others) => others,
+                    other => vec![other],
+                };
+                for other in others {
+                    let same_type = variants
+                        .iter()
+                        .position(|variant| variant.value_type() == other.value_type());
+                    match same_type {
+                        Some(index) => {
+                            let variant = core::mem::replace(&mut variants[index], Schema::Any);
+                            variants[index] = variant.unite(other);
+                        }
+                        None => variants.push(other),
+                    }
+                }
+                if variants.len() == 1 {
+                    variants.pop().unwrap()
+                } else {
+                    Schema::Union(variants)
+                }
+            }
+            (schema, other @ Schema::Union(_)) => Schema::Union(vec![schema]).unite(other),
+            (Schema::List(list), Schema::List(other)) => {
+                Schema::list_of(list.items.unite(*other.items))
+            }
+            (Schema::Dictionary(mut dict), Schema::Dictionary(other)) => {
+                for field in dict.fields.iter_mut() {
+                    if !other.fields.iter().any(|f| f.key == field.key) {
+                        field.required = false;
+                    }
+                }
+                for other_field in other.fields {
+                    match dict.fields.iter_mut().find(|f| f.key == other_field.key) {
+                        Some(field) => {
+                            let schema = core::mem::replace(&mut field.schema, Schema::Any);
+                            field.schema = schema.unite(other_field.schema);
+                            field.required &= other_field.required;
+                        }
+                        None => dict.fields.push(FieldSchema {
+                            required: false,
+                            ..other_field
+                        }),
+                    }
+                }
+                dict.allow_unknown_keys |= other.allow_unknown_keys;
+                Schema::Dictionary(dict)
+            }
+            (schema, other) if schema.value_type() == other.value_type() => schema,
+            (schema, other) => Schema::Union(vec![schema, other]),
+        }
+    }
+}
+
+fn infer_value(value: &BencodexValue<'_>) -> Schema {
+    match value {
+        BencodexValue::Null => Schema::Null,
+        BencodexValue::Boolean(_) => Schema::Boolean,
+        BencodexValue::Number(_) => Schema::number(),
+        BencodexValue::Binary(_) => Schema::binary(),
+        BencodexValue::Text(_) => Schema::text(),
+        BencodexValue::List(items) => Schema::list_of(Schema::infer(items)),
+        BencodexValue::Dictionary(map) => Schema::Dictionary(DictionarySchema {
+            fields: map
+                .iter()
+                .map(|(key, value)| FieldSchema {
+                    key: key.clone().into_owned(),
+                    schema: infer_value(value),
+                    required: true,
+                })
+                .collect(),
+            allow_unknown_keys: false,
+        }),
+    }
+}
+
+/// Match `text` against a glob `pattern` supporting `*`, `?` and `\` escapes.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // The position of the last `*` in the pattern and the text position it is matched from.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            Some('\\') if pattern.get(p + 1) == Some(&text[t]) => {
+                p += 2;
+                t += 1;
+                continue;
+            }
+            Some(c) if *c != '\\' && *c == text[t] => {
+                p += 1;
+                t += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        match backtrack {
+            Some((star, from)) => {
+                p = star + 1;
+                t = from + 1;
+                backtrack = Some((star, from + 1));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// The error type which is returned from [`Schema::from_bencodex`].
+#[derive(PartialEq, Debug)]
+pub enum SchemaError {
+    /// This should be used when the Bencodex value at `path` is not a valid schema.
+    InvalidSchemaError { path: Path, reason: String },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::InvalidSchemaError { path, reason } => write!(f, "{}: {}", path, reason),
+        }
+    }
+}
+
+impl Error for SchemaError {}
+
+impl Schema {
+    /// Express this schema as a Bencodex value.
+    ///
+    /// Every schema is a dictionary with a `type` key, one of `any`, `null`, `boolean`,
+    /// `number`, `binary`, `text`, `list`, `dictionary` and `union`. The other keys depend on
+    /// the type:
+    ///
+    /// - `number`: `min` and `max`, optional numbers.
+    /// - `binary`: `minLength` and `maxLength`, optional numbers.
+    /// - `text`: `minLength`, `maxLength` and `pattern`, an optional text.
+    /// - `list`: `items`, a schema, and `minLength` and `maxLength`.
+    /// - `dictionary`: `fields`, a list of dictionaries having `key`, `schema` and `required`,
+    ///   and `allowUnknownKeys`, a boolean.
+    /// - `union`: `variants`, a list of schemas.
+    ///
+    /// ```
+    /// use bencodex::schema::Schema;
+    ///
+    /// let schema = Schema::list_of(Schema::binary_of_length(32));
+    /// assert_eq!(Schema::from_bencodex(&schema.to_bencodex()), Ok(schema));
+    /// ```
+    pub fn to_bencodex(&self) -> BencodexValue<'static> {
+        let mut map = BencodexDictionary::new();
+        let type_name = match self {
+            Schema::Any => "any",
+            Schema::Union(_) => "union",
+            schema => schema
+                .value_type()
+                .expect("only `Any` and `Union` do not have a value type")
+                .name(),
+        };
+        map.insert("type".into(), type_name.into());
+
+        match self {
+            Schema::Any | Schema::Null | Schema::Boolean => {}
+            Schema::Number(schema) => {
+                if let Some(min) = &schema.min {
+                    map.insert("min".into(), BencodexValue::Number(min.clone()));
+                }
+                if let Some(max) = &schema.max {
+                    map.insert("max".into(), BencodexValue::Number(max.clone()));
+                }
+            }
+            Schema::Binary(range) => insert_length_range(&mut map, range),
+            Schema::Text(schema) => {
+                insert_length_range(&mut map, &schema.length);
+                if let Some(pattern) = &schema.pattern {
+                    map.insert("pattern".into(), pattern.clone().into());
+                }
+            }
+            Schema::List(schema) => {
+                map.insert("items".into(), schema.items.to_bencodex());
+                insert_length_range(&mut map, &schema.length);
+            }
+            Schema::Dictionary(schema) => {
+                let fields = schema
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let mut map = BencodexDictionary::new();
+                        map.insert("key".into(), key_to_value(field.key.clone()));
+                        map.insert("schema".into(), field.schema.to_bencodex());
+                        map.insert("required".into(), field.required.into());
+                        BencodexValue::Dictionary(map)
+                    })
+                    .collect();
+                map.insert("fields".into(), BencodexValue::List(fields));
+                map.insert("allowUnknownKeys".into(), schema.allow_unknown_keys.into());
+            }
+            Schema::Union(variants) => {
+                map.insert(
+                    "variants".into(),
+                    BencodexValue::List(variants.iter().map(Schema::to_bencodex).collect()),
+                );
+            }
+        }
+
+        BencodexValue::Dictionary(map)
+    }
+
+    /// Load a schema from its Bencodex representation. See [`Schema::to_bencodex`] for the format.
+    pub fn from_bencodex(value: &BencodexValue<'_>) -> Result<Schema, SchemaError> {
+        from_bencodex_impl(value, &mut Path::root())
+    }
+}
+
+fn insert_length_range(map: &mut BencodexDictionary<'static>, range: &LengthRange) {
+    if let Some(min) = range.min {
+        map.insert("minLength".into(), BencodexValue::Number(min.into()));
+    }
+    if let Some(max) = range.max {
+        map.insert("maxLength".into(), BencodexValue::Number(max.into()));
+    }
+}
+
+fn key_to_value(key: BencodexKey<'static>) -> BencodexValue<'static> {
+    match key {
+        BencodexKey::Binary(b) => BencodexValue::Binary(b),
+        BencodexKey::Text(t) => BencodexValue::Text(t),
+    }
+}
+
+fn invalid<T>(path: &Path, reason: &str) -> Result<T, SchemaError> {
+    Err(SchemaError::InvalidSchemaError {
+        path: path.clone(),
+        reason: reason.to_string(),
+    })
+}
+
+/// Get an optional field of a schema dictionary, with the path pointing it.
+fn field<'v, 'a>(
+    map: &'v BencodexDictionary<'a>,
+    path: &Path,
+    key: &'static str,
+) -> (Option<&'v BencodexValue<'a>>, Path) {
+    let key = BencodexKey::from(key);
+    (map.get(&key), path.join(key.into()))
+}
+
+fn length_field(
+    map: &BencodexDictionary<'_>,
+    path: &Path,
+    key: &'static str,
+) -> Result<Option<usize>, SchemaError> {
+    match field(map, path, key) {
+        (None, _) => Ok(None),
+        (Some(BencodexValue::Number(n)), path) => match usize::try_from(n) {
+            Ok(n) => Ok(Some(n)),
+            Err(_) => invalid(&path, "length should be a non-negative number"),
+        },
+        (Some(_), path) => invalid(&path, "length should be a number"),
+    }
+}
+
+fn length_range_fields(
+    map: &BencodexDictionary<'_>,
+    path: &Path,
+) -> Result<LengthRange, SchemaError> {
+    Ok(LengthRange {
+        min: length_field(map, path, "minLength")?,
+        max: length_field(map, path, "maxLength")?,
+    })
+}
+
+fn number_field(
+    map: &BencodexDictionary<'_>,
+    path: &Path,
+    key: &'static str,
+) -> Result<Option<BencodexNumber>, SchemaError> {
+    match field(map, path, key) {
+        (None, _) => Ok(None),
+        (Some(BencodexValue::Number(n)), _) => Ok(Some(n.clone())),
+        (Some(_), path) => invalid(&path, "bound should be a number"),
+    }
+}
+
+fn schema_list_field(
+    map: &BencodexDictionary<'_>,
+    path: &Path,
+    key: &'static str,
+) -> Result<Vec<Schema>, SchemaError> {
+    match field(map, path, key) {
+        (Some(BencodexValue::List(items)), mut path) => items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                path.push(PathSegment::Index(index));
+                let schema = from_bencodex_impl(item, &mut path);
+                path.pop();
+                schema
+            })
+            .collect(),
+        (_, path) => invalid(&path, "should be a list"),
+    }
+}
+
+fn from_bencodex_impl(value: &BencodexValue<'_>, path: &mut Path) -> Result<Schema, SchemaError> {
+    let map = match value {
+        BencodexValue::Dictionary(map) => map,
+        _ => return invalid(path, "schema should be a dictionary"),
+    };
+
+    let type_name = match field(map, path, "type") {
+        (Some(BencodexValue::Text(name)), _) => name,
+        (_, path) => return invalid(&path, "type should be a text"),
+    };
+
+    Ok(match type_name.as_ref() {
+        "any" => Schema::Any,
+        "null" => Schema::Null,
+        "boolean" => Schema::Boolean,
+        "number" => Schema::Number(NumberSchema {
+            min: number_field(map, path, "min")?,
+            max: number_field(map, path, "max")?,
+        }),
+        "binary" => Schema::Binary(length_range_fields(map, path)?),
+        "text" => Schema::Text(TextSchema {
+            length: length_range_fields(map, path)?,
+            pattern: match field(map, path, "pattern") {
+                (None, _) => None,
+                (Some(BencodexValue::Text(pattern)), _) => Some(pattern.to_string()),
+                (Some(_), path) => return invalid(&path, "pattern should be a text"),
+            },
+        }),
+        "list" => Schema::List(ListSchema {
+            items: match field(map, path, "items") {
+                (Some(items), mut path) => Box::new(from_bencodex_impl(items, &mut path)?),
+                (None, path) => return invalid(&path, "items should exist"),
+            },
+            length: length_range_fields(map, path)?,
+        }),
+        "dictionary" => {
+            let allow_unknown_keys = match field(map, path, "allowUnknownKeys") {
+                (None, _) => false,
+                (Some(BencodexValue::Boolean(b)), _) => *b,
+                (Some(_), path) => return invalid(&path, "should be a boolean"),
+            };
+            let fields = match field(map, path, "fields") {
+                (None, _) => Vec::new(),
+                (Some(BencodexValue::List(fields)), path) => fields
+                    .iter()
+                    .enumerate()
+                    .map(|(index, field)| {
+                        field_from_bencodex(field, &path.join(PathSegment::Index(index)))
+                    })
+                    .collect::<Result<_, _>>()?,
+                (Some(_), path) => return invalid(&path, "fields should be a list"),
+            };
+            Schema::Dictionary(DictionarySchema {
+                fields,
+                allow_unknown_keys,
+            })
+        }
+        "union" => Schema::Union(schema_list_field(map, path, "variants")?),
+        _ => return invalid(path, "unknown schema type"),
+    })
+}
+
+fn field_from_bencodex(value: &BencodexValue<'_>, path: &Path) -> Result<FieldSchema, SchemaError> {
+    let map = match value {
+        BencodexValue::Dictionary(map) => map,
+        _ => return invalid(path, "field should be a dictionary"),
+    };
+
+    let key = match field(map, path, "key") {
+        (Some(BencodexValue::Text(t)), _) => BencodexKey::Text(Cow::Owned(t.to_string())),
+        (Some(BencodexValue::Binary(b)), _) => BencodexKey::Binary(Cow::Owned(b.to_vec())),
+        (_, path) => return invalid(&path, "key should be a text or a binary"),
+    };
+    let schema = match field(map, path, "schema") {
+        (Some(schema), mut path) => from_bencodex_impl(schema, &mut path)?,
+        (None, path) => return invalid(&path, "schema should exist"),
+    };
+    let required = match field(map, path, "required") {
+        (None, _) => false,
+        (Some(BencodexValue::Boolean(b)), _) => *b,
+        (Some(_), path) => return invalid(&path, "required should be a boolean"),
+    };
+
+    Ok(FieldSchema {
+        key,
+        schema,
+        required,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn violations(value: &BencodexValue<'_>, schema: &Schema) -> Vec<String> {
+        match validate(value, schema) {
+            Ok(()) => Vec::new(),
+            Err(violations) => violations.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    fn transfer_schema() -> Schema {
+        DictionarySchema::new()
+            .required("sender", Schema::binary_of_length(20))
+            .required("amount", Schema::number_in(1, 1_000_000))
+            .optional("memo", Schema::text_matching("memo:*"))
+            .optional("tags", Schema::list_of(Schema::text()))
+            .into()
+    }
+
+    mod validate {
+        use super::*;
+
+        #[test]
+        fn should_accept_valid_value() {
+            let mut map = BencodexDictionary::new();
+            map.insert("sender".into(), vec![0u8; 20].into());
+            map.insert("amount".into(), 100.into());
+            map.insert("memo".into(), "memo:hello".into());
+            map.insert("tags".into(), vec!["a", "b"].into());
+            assert!(validate(&BencodexValue::Dictionary(map), &transfer_schema()).is_ok());
+        }
+
+        #[test]
+        fn should_return_every_violation_with_path() {
+            let mut map = BencodexDictionary::new();
+            map.insert("sender".into(), vec![0u8; 19].into());
+            map.insert("memo".into(), "hello".into());
+            map.insert(
+                "tags".into(),
+                BencodexValue::from(vec![BencodexValue::from("a"), BencodexValue::from(1)]),
+            );
+            map.insert("unknown".into(), BencodexValue::Null);
+            assert_eq!(
+                violations(&BencodexValue::Dictionary(map), &transfer_schema()),
+                vec![
+                    "$.sender: length 19 is out of range [20, 20]",
+                    "$.amount: missing required key",
+                    "$.memo: text does not match pattern \"memo:*\"",
+                    "$.tags[1]: expected text but got number",
+                    "$.unknown: unexpected key",
+                ]
+            );
+        }
+
+        #[test]
+        fn should_check_number_range() {
+            let schema = Schema::number_in(-1, 1);
+            assert!(validate(&(-1).into(), &schema).is_ok());
+            assert_eq!(
+                violations(&2.into(), &schema),
+                vec!["$: number 2 is out of range [-1, 1]"]
+            );
+        }
+
+        #[test]
+        fn should_check_union() {
+            let schema = Schema::union(vec![Schema::Null, Schema::binary_of_length(32)]);
+            assert!(validate(&BencodexValue::Null, &schema).is_ok());
+            assert!(validate(&vec![0u8; 32].into(), &schema).is_ok());
+            assert_eq!(
+                violations(&vec![0u8; 31].into(), &schema),
+                vec!["$: no variant of the union matched"]
+            );
+        }
+
+        #[test]
+        fn should_allow_unknown_keys() {
+            let schema: Schema = DictionarySchema::new().allow_unknown_keys().into();
+            let mut map = BencodexDictionary::new();
+            map.insert(vec![1u8].into(), BencodexValue::Null);
+            assert!(validate(&BencodexValue::Dictionary(map), &schema).is_ok());
+        }
+    }
+
+    mod glob_match {
+        use super::super::glob_match;
+
+        #[test]
+        fn should_match() {
+            assert!(glob_match("", ""));
+            assert!(glob_match("*", "anything"));
+            assert!(glob_match("a?c", "abc"));
+            assert!(glob_match("a*c*e", "abcde"));
+            assert!(glob_match("*.bin", "data.bin"));
+            assert!(glob_match("\\*", "*"));
+            assert!(glob_match("한*", "한글"));
+        }
+
+        #[test]
+        fn should_not_match() {
+            assert!(!glob_match("", "a"));
+            assert!(!glob_match("a?c", "ac"));
+            assert!(!glob_match("*.bin", "data.bi"));
+            assert!(!glob_match("\\*", "a"));
+        }
+    }
+
+    mod bencodex_representation {
+        use super::*;
+
+        #[test]
+        fn should_roundtrip() {
+            let schemas = vec![
+                Schema::Any,
+                Schema::Null,
+                Schema::Boolean,
+                Schema::number(),
+                Schema::number_in(-5, 5),
+                Schema::binary(),
+                Schema::binary_of_length(20),
+                Schema::text(),
+                Schema::text_matching("a*"),
+                Schema::list_of(Schema::number()),
+                Schema::union(vec![Schema::Null, Schema::text()]),
+                transfer_schema(),
+                DictionarySchema::new()
+                    .required(vec![0u8, 1], Schema::Any)
+                    .allow_unknown_keys()
+                    .into(),
+            ];
+            for schema in schemas {
+                assert_eq!(Schema::from_bencodex(&schema.to_bencodex()), Ok(schema));
+            }
+        }
+
+        #[test]
+        fn should_return_error_with_path() {
+            let mut items = BencodexDictionary::new();
+            items.insert("type".into(), "float".into());
+            let mut list = BencodexDictionary::new();
+            list.insert("type".into(), "list".into());
+            list.insert("items".into(), BencodexValue::Dictionary(items));
+
+            let error = Schema::from_bencodex(&BencodexValue::Dictionary(list)).unwrap_err();
+            assert_eq!(error.to_string(), "$.items: unknown schema type");
+        }
+    }
+    mod infer {
+        use super::*;
+
+        #[test]
+        fn should_infer_union_of_types() {
+            let samples: Vec<BencodexValue> = vec![
+                1.into(),
+                BencodexValue::Null,
+                "foo".into(),
+                2.into(),
+                vec![0u8].into(),
+            ];
+            assert_eq!(
+                Schema::infer(&samples),
+                Schema::union(vec![
+                    Schema::number(),
+                    Schema::Null,
+                    Schema::text(),
+                    Schema::binary(),
+                ])
+            );
+            assert_eq!(Schema::infer(&samples[..1]), Schema::number());
+            assert_eq!(Schema::infer(&[]), Schema::union(vec![]));
+        }
+
+        #[test]
+        fn should_infer_list_items() {
+            let samples: Vec<BencodexValue> = vec![
+                BencodexValue::List(vec![1.into()]),
+                BencodexValue::List(vec![]),
+                BencodexValue::List(vec![true.into(), 2.into()]),
+            ];
+            assert_eq!(
+                Schema::infer(&samples),
+                Schema::list_of(Schema::union(vec![Schema::number(), Schema::Boolean]))
+            );
+        }
+
+        #[test]
+        fn should_infer_optional_keys() {
+            let mut first = BencodexDictionary::new();
+            first.insert("sender".into(), vec![0u8; 20].into());
+            first.insert("memo".into(), "hello".into());
+            let mut second = BencodexDictionary::new();
+            second.insert("sender".into(), vec![1u8; 20].into());
+            second.insert("amount".into(), 1.into());
+            let samples = vec![
+                BencodexValue::Dictionary(first),
+                BencodexValue::Dictionary(second),
+            ];
+
+            let schema = Schema::infer(&samples);
+            assert_eq!(
+                schema,
+                DictionarySchema::new()
+                    .optional("memo", Schema::text())
+                    .required("sender", Schema::binary())
+                    .optional("amount", Schema::number())
+                    .into()
+            );
+            for sample in &samples {
+                assert_eq!(validate(sample, &schema), Ok(()));
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Run the CLI with `args`, writing `stdin` to it.
+fn run(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bencodex"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Write `content` to a file in the temporary directory, unique to this process.
+fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bencodex-cli-{}-{}", std::process::id(), name));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn validate_rejects_trailing_data() {
+    let output = run(&["validate"], b"i1ejunk");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("trailing data"));
+
+    let output = run(&["validate"], b"i1e");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"The input is valid.\n");
+}
+
+#[test]
+fn diff_rejects_trailing_data() {
+    let left = temp_file("diff-left.bin", b"i1e");
+    let right = temp_file("diff-right.bin", b"i1ei2e");
+    let output = run(
+        &["diff", left.to_str().unwrap(), right.to_str().unwrap()],
+        b"",
+    );
+    std::fs::remove_file(left).unwrap();
+    std::fs::remove_file(right).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("trailing data"));
+}
+
+#[test]
+fn encode_json_with_many_values_writes_nothing() {
+    let output = run(&["encode"], b"i1eu1:a");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("more than one value"));
+
+    let output = run(&["encode", "--input-format", "hex"], b"693165\n693265\n");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+
+    let output = run(&["encode", "--output-format", "ndjson"], b"i1eu1:a");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(output.stdout, b"\"1\"\n\"\xef\xbb\xbfa\"\n");
+}
@@ -1,10 +1,8 @@
 use base64::Engine;
 use num_bigint::BigInt;
-use num_traits::cast::FromPrimitive;
 #[cfg(feature = "json")]
 use serde_json::Value;
 use std::borrow::Cow;
-use std::collections::BTreeMap;
 use std::fs;
 use std::fs::DirEntry;
 use std::ops::Deref;
@@ -15,7 +13,7 @@ use yaml_rust::parser::MarkedEventReceiver;
 use yaml_rust::parser::Parser;
 use yaml_rust::scanner::{Marker, ScanError, TokenType};
 
-use bencodex::codec::types::{BencodexKey, BencodexValue};
+use bencodex::codec::types::{BencodexDictionary, BencodexKey, BencodexValue};
 #[cfg(feature = "json")]
 use bencodex::json::BinaryEncoding;
 
@@ -71,7 +69,7 @@ impl MarkedEventReceiver for TestsuiteYamlLoader {
             Event::MappingStart(aid) => {
                 self.key_stack.push(None);
                 self.doc_stack
-                    .push((BencodexValue::Dictionary(BTreeMap::new()), aid));
+                    .push((BencodexValue::Dictionary(BencodexDictionary::new()), aid));
             }
             Event::MappingEnd => {
                 self.key_stack.pop().unwrap();
@@ -92,7 +90,7 @@ impl MarkedEventReceiver for TestsuiteYamlLoader {
                             }
                             "int" => match v.parse::<i64>() {
                                 Err(_) => unreachable!(),
-                                Ok(v) => BencodexValue::Number(BigInt::from_i64(v).unwrap()),
+                                Ok(v) => BencodexValue::Number(v.into()),
                             },
                             "binary" => BencodexValue::Binary(Cow::Owned(
                                 base64::engine::general_purpose::STANDARD
@@ -111,7 +109,7 @@ impl MarkedEventReceiver for TestsuiteYamlLoader {
                 } else {
                     // Datatype is not specified, or unrecognized
                     if let Ok(i) = BigInt::from_str(&v) {
-                        BencodexValue::Number(i)
+                        BencodexValue::Number(i.into())
                     } else if let Ok(b) = v.parse::<bool>() {
                         BencodexValue::Boolean(b)
                     } else if v == "null" {
@@ -228,8 +226,10 @@ fn base64_to_hex(s: &String) -> String {
 #[cfg(feature = "json")]
 fn traverse(value: &Value, bytes_encode_method: &BinaryEncoding) -> Value {
     let converter = match bytes_encode_method {
-        BinaryEncoding::Hex => base64_to_hex,
-        BinaryEncoding::Base64 => hex_to_base64,
+        BinaryEncoding::Hex | BinaryEncoding::HexUpper => base64_to_hex,
+        BinaryEncoding::Base64 | BinaryEncoding::Base64Url | BinaryEncoding::Base64UrlNoPad => {
+            hex_to_base64
+        }
     };
     match value {
         Value::Object(obj) => {
@@ -1,7 +1,37 @@
 use super::bencodex_value;
-use bencodex::json::{BinaryEncoding, JsonEncodeOptions, to_json, to_json_with_options};
+use bencodex::json::{
+    BinaryEncoding, JsonDecodeOptions, JsonEncodeOptions, KeyOrder, NumberEncoding, TextEncoding,
+    from_json_reader, from_json_string, from_json_string_with_options, to_json, to_json_pretty,
+    to_json_with_options, to_json_writer,
+};
 use proptest::prelude::*;
 
+fn json_encode_options() -> impl Strategy<Value = JsonEncodeOptions> {
+    (
+        prop_oneof![
+            Just(BinaryEncoding::Base64),
+            Just(BinaryEncoding::Hex),
+            Just(BinaryEncoding::Base64Url),
+            Just(BinaryEncoding::Base64UrlNoPad),
+            Just(BinaryEncoding::HexUpper),
+        ],
+        prop_oneof![Just(TextEncoding::Bom), Just(TextEncoding::BomIfAmbiguous)],
+        prop_oneof![
+            Just(NumberEncoding::String),
+            Just(NumberEncoding::NumberIfSafe)
+        ],
+        prop_oneof![Just(KeyOrder::Bencodex), Just(KeyOrder::Json)],
+    )
+        .prop_map(
+            |(binary_encoding, text_encoding, number_encoding, key_order)| JsonEncodeOptions {
+                binary_encoding,
+                text_encoding,
+                number_encoding,
+                key_order,
+            },
+        )
+}
+
 proptest! {
     #[test]
     fn json_encode_produces_valid_json(value in bencodex_value()) {
@@ -13,8 +43,57 @@ proptest! {
     fn json_encode_hex_produces_valid_json(value in bencodex_value()) {
         let options = JsonEncodeOptions {
             binary_encoding: BinaryEncoding::Hex,
+            ..Default::default()
         };
         let json_str = to_json_with_options(&value, options).expect("Failed to encode JSON.");
         prop_assert!(serde_json::from_str::<serde_json::Value>(&json_str).is_ok());
     }
+
+    #[test]
+    fn json_encode_writer_matches_string(value in bencodex_value()) {
+        for binary_encoding in [BinaryEncoding::Base64, BinaryEncoding::Hex] {
+            let options = JsonEncodeOptions {
+                binary_encoding,
+                ..Default::default()
+            };
+            let json_str = to_json_with_options(&value, options).expect("Failed to encode JSON.");
+            let mut buf = Vec::new();
+            to_json_writer(&mut buf, &value, options).expect("Failed to encode JSON.");
+            prop_assert_eq!(json_str.as_bytes(), buf.as_slice());
+        }
+    }
+
+    #[test]
+    fn json_encode_pretty_is_same_json(value in bencodex_value()) {
+        let options = JsonEncodeOptions::default();
+        let json_str = to_json_with_options(&value, options).expect("Failed to encode JSON.");
+        let pretty_str = to_json_pretty(&value, options).expect("Failed to encode JSON.");
+        prop_assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json_str).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&pretty_str).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_decode_roundtrip(value in bencodex_value()) {
+        let json_str = to_json(&value).expect("Failed to encode JSON.");
+        prop_assert_eq!(&from_json_string(&json_str).expect("Failed to decode JSON."), &value);
+        prop_assert_eq!(
+            &from_json_reader(json_str.as_bytes()).expect("Failed to decode JSON."),
+            &value
+        );
+    }
+
+    #[test]
+    fn json_decode_roundtrip_with_options(
+        value in bencodex_value(),
+        options in json_encode_options(),
+    ) {
+        let json_str = to_json_with_options(&value, options).expect("Failed to encode JSON.");
+        prop_assert_eq!(
+            &from_json_string_with_options(&json_str, JsonDecodeOptions::from(options))
+                .expect("Failed to decode JSON."),
+            &value
+        );
+    }
 }
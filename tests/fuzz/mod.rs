@@ -29,7 +29,7 @@ pub fn leaf_value() -> impl Strategy<Value = BencodexValue<'static>> {
     prop_oneof![
         Just(BencodexValue::Null),
         any::<bool>().prop_map(BencodexValue::Boolean),
-        bigint().prop_map(BencodexValue::Number),
+        bigint().prop_map(|n| BencodexValue::Number(n.into())),
         prop::collection::vec(any::<u8>(), 0..100)
             .prop_map(|v| BencodexValue::Binary(Cow::Owned(v))),
         any::<String>().prop_map(|s| BencodexValue::Text(Cow::Owned(s))),
@@ -45,7 +45,7 @@ pub fn bencodex_value() -> impl Strategy<Value = BencodexValue<'static>> {
             prop_oneof![
                 prop::collection::vec(inner.clone(), 0..10).prop_map(BencodexValue::List),
                 prop::collection::btree_map(bencodex_key(), inner, 0..10)
-                    .prop_map(BencodexValue::Dictionary),
+                    .prop_map(|map| BencodexValue::Dictionary(map.into_iter().collect())),
             ]
         },
     )
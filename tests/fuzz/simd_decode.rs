@@ -1,5 +1,10 @@
 use super::bencodex_value;
-use bencodex::simd::decode_simd;
+use bencodex::codec::simd::stage1::build_structural_index_with_backend;
+use bencodex::codec::simd::structural::StructuralIndex;
+use bencodex::codec::simd::utf8::validate_utf8;
+use bencodex::simd::{
+    Backend, available_backends, decode_simd, decode_simd_lenient, decode_simd_with_backend,
+};
 use bencodex::{BencodexValue, Decode, Encode};
 use proptest::prelude::*;
 use std::borrow::Cow;
@@ -57,9 +62,10 @@ fn edge_case_bytes() -> impl Strategy<Value = Vec<u8>> {
         Just(b"l".to_vec()),
         Just(b"d".to_vec()),
         Just(vec![0xFF]),
-        // SIMD boundary tests (16, 32 bytes)
+        // SIMD boundary tests (16, 32, 64 bytes)
         prop::collection::vec(any::<u8>(), 15..=17),
         prop::collection::vec(any::<u8>(), 31..=33),
+        prop::collection::vec(any::<u8>(), 63..=65),
     ]
 }
 
@@ -100,3 +106,161 @@ proptest! {
         prop_assert_eq!(value, decoded);
     }
 }
+
+// 7. Every structural scanner available on this machine should agree with the scalar one
+fn assert_backends_agree(input: &[u8]) -> Result<(), TestCaseError> {
+    let expected: StructuralIndex = build_structural_index_with_backend(input, Backend::Scalar);
+    for backend in available_backends() {
+        let index: StructuralIndex = build_structural_index_with_backend(input, backend);
+        prop_assert_eq!(expected.indices(), index.indices(), "backend: {}", backend);
+        prop_assert_eq!(expected.texts(), index.texts(), "backend: {}", backend);
+        prop_assert_eq!(
+            decode_simd(input),
+            decode_simd_with_backend(input, backend),
+            "backend: {}",
+            backend
+        );
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn simd_backends_agree_on_values(value in bencodex_value()) {
+        assert_backends_agree(&encode_to_vec(&value))?;
+    }
+
+    #[test]
+    fn simd_backends_agree_on_random_input(data in prop::collection::vec(any::<u8>(), 0..1000)) {
+        assert_backends_agree(&data)?;
+    }
+
+    #[test]
+    fn simd_backends_agree_on_edge_cases(data in edge_case_bytes()) {
+        assert_backends_agree(&data)?;
+    }
+}
+
+// 8. UTF-8 validation should agree with the standard library on every available backend
+fn validate_with_available_backends(input: &[u8]) -> Vec<(&'static str, bool)> {
+    #[allow(unused_mut)]
+    let mut results = vec![("dispatched", validate_utf8(input))];
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        use bencodex::codec::simd::arch::x86_64::*;
+        if is_x86_feature_detected!("sse4.2") {
+            results.push(("sse4.2", unsafe { validate_utf8_sse42(input) }));
+        }
+        if is_x86_feature_detected!("avx2") {
+            results.push(("avx2", unsafe { validate_utf8_avx2(input) }));
+        }
+        if is_x86_feature_detected!("avx512bw") {
+            results.push(("avx512bw", unsafe { validate_utf8_avx512(input) }));
+        }
+    }
+
+    results
+}
+
+fn assert_utf8_agrees(input: &[u8]) -> Result<(), TestCaseError> {
+    let expected = std::str::from_utf8(input).is_ok();
+    for (backend, valid) in validate_with_available_backends(input) {
+        prop_assert_eq!(expected, valid, "backend: {}", backend);
+    }
+    Ok(())
+}
+
+/// Mostly valid UTF-8 with a few bytes replaced, so errors appear between valid sequences
+fn corrupted_utf8() -> impl Strategy<Value = Vec<u8>> {
+    (
+        ".{0,200}",
+        prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 0..3),
+    )
+        .prop_map(|(text, replacements)| {
+            let mut bytes = text.into_bytes();
+            if !bytes.is_empty() {
+                for (index, byte) in replacements {
+                    let i = index.index(bytes.len());
+                    bytes[i] = byte;
+                }
+            }
+            bytes
+        })
+}
+
+proptest! {
+    #[test]
+    fn simd_utf8_agrees_on_random_input(data in prop::collection::vec(any::<u8>(), 0..300)) {
+        assert_utf8_agrees(&data)?;
+    }
+
+    #[test]
+    fn simd_utf8_agrees_on_corrupted_text(data in corrupted_utf8()) {
+        assert_utf8_agrees(&data)?;
+    }
+
+    #[test]
+    fn simd_utf8_accepts_valid_text(text in ".{0,300}") {
+        assert_utf8_agrees(text.as_bytes())?;
+    }
+}
+
+// 9. A tape should convert into the same value as decode_simd returns
+proptest! {
+    #[test]
+    fn simd_tape_same_as_decode_simd(value in bencodex_value()) {
+        let encoded = encode_to_vec(&value);
+        let tape = bencodex::simd::tape::decode_tape(&encoded).expect("should decode");
+        prop_assert_eq!(value, tape.to_value().into_owned());
+    }
+
+    #[test]
+    fn simd_tape_accepts_what_decode_simd_accepts(data in edge_case_bytes()) {
+        let tape = bencodex::simd::tape::decode_tape(&data);
+        if let Ok(tape) = &tape {
+            prop_assert_eq!(decode_simd(&data), Ok(tape.to_value()));
+        }
+    }
+}
+
+// 10. Lenient decoding with SIMD should report the same issues as the scalar one
+/// Canonical encodings with zeros inserted, which become leading zeros of lengths and numbers
+/// or make the input invalid
+fn with_inserted_zeros() -> impl Strategy<Value = Vec<u8>> {
+    (
+        bencodex_value(),
+        prop::collection::vec(any::<prop::sample::Index>(), 0..4),
+    )
+        .prop_map(|(value, indices)| {
+            let mut encoded = encode_to_vec(&value);
+            for index in indices {
+                let i = index.index(encoded.len() + 1);
+                encoded.insert(i, b'0');
+            }
+            encoded
+        })
+}
+
+fn assert_lenient_agrees(input: &[u8]) -> Result<(), TestCaseError> {
+    let scalar = bencodex::canonical::decode_lenient(input);
+    let simd = decode_simd_lenient(input);
+    match (&scalar, &simd) {
+        (Ok(s), Ok(v)) => prop_assert_eq!(s, v),
+        (Err(_), Err(_)) => {}
+        _ => prop_assert!(false, "Mismatch: scalar={:?}, simd={:?}", scalar, simd),
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn simd_lenient_agrees_on_inserted_zeros(data in with_inserted_zeros()) {
+        assert_lenient_agrees(&data)?;
+    }
+
+    #[test]
+    fn simd_lenient_agrees_on_random_input(data in prop::collection::vec(any::<u8>(), 0..1000)) {
+        assert_lenient_agrees(&data)?;
+    }
+}
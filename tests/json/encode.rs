@@ -1,12 +1,18 @@
 use super::super::codec::utils;
-use bencodex::json::{BinaryEncoding, JsonEncodeOptions, to_json_with_options};
+use bencodex::json::{
+    BinaryEncoding, JsonEncodeOptions, KeyOrder, NumberEncoding, TextEncoding, to_json_with_options,
+};
 
 const SPEC_TEST_BASE64_OPTIONS: JsonEncodeOptions = JsonEncodeOptions {
     binary_encoding: BinaryEncoding::Base64,
+    text_encoding: TextEncoding::Bom,
+    number_encoding: NumberEncoding::String,
+    key_order: KeyOrder::Bencodex,
 };
 
 const SPEC_TEST_HEX_OPTIONS: JsonEncodeOptions = JsonEncodeOptions {
     binary_encoding: BinaryEncoding::Hex,
+    ..SPEC_TEST_BASE64_OPTIONS
 };
 
 #[test]
@@ -1,3 +1,5 @@
+#[cfg(feature = "json-cli")]
+pub mod cli;
 pub mod codec;
 #[cfg(all(any(feature = "json", feature = "simd"), not(miri)))]
 pub mod fuzz;